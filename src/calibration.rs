@@ -0,0 +1,301 @@
+use std::fmt::Display;
+
+use nalgebra::{DMatrix, DVector, Isometry3, Matrix3, Point3, Rotation3, UnitQuaternion, Vector3};
+use robot_behavior::{RobotException, RobotResult};
+
+use crate::frame::{iso_to_pose, pose_to_iso};
+
+/// 求解工具位置至少需要的接触点数
+pub const TCP_CALIBRATION_MIN_POINTS: usize = 4;
+/// 接触点姿态过于接近时最小奇异值与最大奇异值之比的下限
+const CONDITION_EPS: f64 = 1e-4;
+/// 方向点与参考点之间的最小距离，单位 mm
+const MIN_DIRECTION_DISTANCE: f64 = 1.;
+/// 三点夹角的正弦小于该值时视为共线
+const COLLINEAR_SINE: f64 = 1e-3;
+
+/// 工具坐标（TCP）标定
+///
+/// - 四点法：以至少 4 个差异较大的姿态让工具末端接触同一个固定点，最小二乘求解工具末端在法兰坐标系下的位置
+/// - 六点法：在四点法的基础上，保持最后一个接触点的姿态，分别沿期望的工具 X 方向与 Z 方向平移后各记录一点，
+///   额外求解工具姿态
+///
+/// 记录的位姿均为法兰在基坐标系下的位姿，单位 mm 与 °，可以通过
+/// [`HansRobot::read_flange_pose`](crate::HansRobot::read_flange_pose) 读取。
+#[derive(Debug, Clone, Default)]
+pub struct TcpCalibration {
+    points: Vec<[f64; 6]>,
+    x_point: Option<[f64; 6]>,
+    z_point: Option<[f64; 6]>,
+}
+
+/// TCP 标定结果
+#[derive(Debug, Clone, PartialEq)]
+pub struct TcpCalibrationResult {
+    /// 工具坐标在法兰坐标系下的位姿，四点法时姿态为零
+    pub tcp: [f64; 6],
+    /// 固定点在基坐标系下的位置，单位 mm
+    pub reference: [f64; 3],
+    /// 每个接触点求得的工具末端与固定点的距离，单位 mm
+    pub residuals: Vec<f64>,
+    /// 残差的均方根，单位 mm
+    pub rms_error: f64,
+    /// 残差的最大值，单位 mm
+    pub max_error: f64,
+}
+
+impl Display for TcpCalibrationResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "tcp {:?}, rms error {:.3}mm, max error {:.3}mm",
+            self.tcp, self.rms_error, self.max_error
+        )
+    }
+}
+
+impl TcpCalibration {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 记录一个工具末端接触固定点时的法兰位姿
+    pub fn add_point(&mut self, flange: [f64; 6]) {
+        self.points.push(flange);
+    }
+
+    /// 六点法：记录沿工具 X 方向离开固定点后的法兰位姿
+    pub fn set_x_point(&mut self, flange: [f64; 6]) {
+        self.x_point = Some(flange);
+    }
+
+    /// 六点法：记录沿工具 Z 方向离开固定点后的法兰位姿
+    pub fn set_z_point(&mut self, flange: [f64; 6]) {
+        self.z_point = Some(flange);
+    }
+
+    pub fn points(&self) -> &[[f64; 6]] {
+        &self.points
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// 求解工具坐标，记录了 X 与 Z 方向点时按六点法同时求解姿态
+    pub fn solve(&self) -> RobotResult<TcpCalibrationResult> {
+        if self.points.len() < TCP_CALIBRATION_MIN_POINTS {
+            return Err(RobotException::InvalidInstruction(format!(
+                "tcp calibration needs at least {TCP_CALIBRATION_MIN_POINTS} points, got {}",
+                self.points.len()
+            )));
+        }
+        let flanges: Vec<_> = self.points.iter().map(pose_to_iso).collect();
+
+        // 每个接触点满足 R_i * t + p_i = P，未知量为工具偏移 t 与固定点 P
+        let rows = 3 * flanges.len();
+        let mut a = DMatrix::zeros(rows, 6);
+        let mut b = DVector::zeros(rows);
+        for (i, flange) in flanges.iter().enumerate() {
+            let r = flange.rotation.to_rotation_matrix();
+            a.view_mut((3 * i, 0), (3, 3)).copy_from(r.matrix());
+            a.view_mut((3 * i, 3), (3, 3))
+                .copy_from(&-Matrix3::identity());
+            b.rows_mut(3 * i, 3).copy_from(&-flange.translation.vector);
+        }
+        let svd = a.svd(true, true);
+        let max = svd.singular_values.max();
+        if svd.singular_values.min() <= max * CONDITION_EPS {
+            return Err(RobotException::UnprocessableInstructionError(
+                "tcp calibration points are too similar in orientation".into(),
+            ));
+        }
+        let x = svd
+            .solve(&b, f64::EPSILON)
+            .map_err(|e| RobotException::UnprocessableInstructionError(e.into()))?;
+        let offset = Vector3::new(x[0], x[1], x[2]);
+        let reference = Vector3::new(x[3], x[4], x[5]);
+
+        let residuals: Vec<f64> = flanges
+            .iter()
+            .map(|flange| ((flange * Point3::from(offset)).coords - reference).norm())
+            .collect();
+        let rms_error =
+            (residuals.iter().map(|r| r * r).sum::<f64>() / residuals.len() as f64).sqrt();
+        let max_error = residuals.iter().copied().fold(0., f64::max);
+
+        let rotation = match (self.x_point, self.z_point) {
+            (Some(x_point), Some(z_point)) => {
+                tool_rotation(&x_point, &z_point, &offset, &reference)?
+            }
+            (None, None) => UnitQuaternion::identity(),
+            _ => {
+                return Err(RobotException::InvalidInstruction(
+                    "six-point tcp calibration needs both the x and z points".into(),
+                ));
+            }
+        };
+        let tcp = Isometry3::from_parts(offset.into(), rotation);
+
+        Ok(TcpCalibrationResult {
+            tcp: iso_to_pose(&tcp),
+            reference: reference.into(),
+            residuals,
+            rms_error,
+            max_error,
+        })
+    }
+}
+
+/// 由 X、Z 方向点求工具坐标系相对法兰的姿态，两点应保持最后一个接触点的姿态
+fn tool_rotation(
+    x_point: &[f64; 6],
+    z_point: &[f64; 6],
+    offset: &Vector3<f64>,
+    reference: &Vector3<f64>,
+) -> RobotResult<UnitQuaternion<f64>> {
+    let x_flange = pose_to_iso(x_point);
+    let tip = |flange: &[f64; 6]| pose_to_iso(flange) * Point3::from(*offset);
+    let x = tip(x_point).coords - reference;
+    let z = tip(z_point).coords - reference;
+    if x.norm() < MIN_DIRECTION_DISTANCE || z.norm() < MIN_DIRECTION_DISTANCE {
+        return Err(RobotException::InvalidInstruction(
+            "tcp calibration direction points are too close to the reference point".into(),
+        ));
+    }
+    let x = x.normalize();
+    let z = z - x * x.dot(&z);
+    if z.norm() < MIN_DIRECTION_DISTANCE {
+        return Err(RobotException::InvalidInstruction(
+            "tcp calibration x and z directions are parallel".into(),
+        ));
+    }
+    let z = z.normalize();
+    let y = z.cross(&x);
+    let base_to_tool = Rotation3::from_basis_unchecked(&[x, y, z]);
+    Ok(x_flange.rotation.inverse() * UnitQuaternion::from_rotation_matrix(&base_to_tool))
+}
+
+/// 用户坐标（UCS）三点示教
+///
+/// 依次示教原点、X 轴正方向上的一点以及 XY 平面内 Y 为正的一点，位置均为工具末端在基坐标系下的位置，
+/// 单位 mm，可以通过 [`HansRobot::read_tcp_pose`](crate::HansRobot::read_tcp_pose) 读取。
+#[derive(Debug, Clone, Default)]
+pub struct UserFrameTeaching {
+    origin: Option<[f64; 3]>,
+    x_point: Option<[f64; 3]>,
+    xy_point: Option<[f64; 3]>,
+}
+
+impl UserFrameTeaching {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 记录原点，只使用位姿中的位置
+    pub fn set_origin(&mut self, pose: [f64; 6]) {
+        self.origin = Some([pose[0], pose[1], pose[2]]);
+    }
+
+    /// 记录 X 轴正方向上的一点
+    pub fn set_x_point(&mut self, pose: [f64; 6]) {
+        self.x_point = Some([pose[0], pose[1], pose[2]]);
+    }
+
+    /// 记录 XY 平面内 Y 为正的一点
+    pub fn set_xy_point(&mut self, pose: [f64; 6]) {
+        self.xy_point = Some([pose[0], pose[1], pose[2]]);
+    }
+
+    /// 求解用户坐标在基坐标系下的位姿
+    pub fn solve(&self) -> RobotResult<[f64; 6]> {
+        let (Some(origin), Some(x_point), Some(xy_point)) =
+            (self.origin, self.x_point, self.xy_point)
+        else {
+            return Err(RobotException::InvalidInstruction(
+                "user frame teaching needs the origin, x and xy points".into(),
+            ));
+        };
+        let origin = Vector3::from(origin);
+        let x = Vector3::from(x_point) - origin;
+        let xy = Vector3::from(xy_point) - origin;
+        if x.norm() < MIN_DIRECTION_DISTANCE || xy.norm() < MIN_DIRECTION_DISTANCE {
+            return Err(RobotException::InvalidInstruction(
+                "user frame points are too close to the origin".into(),
+            ));
+        }
+        let x = x.normalize();
+        let y = xy - x * x.dot(&xy);
+        if y.norm() < xy.norm() * COLLINEAR_SINE {
+            return Err(RobotException::InvalidInstruction(
+                "user frame points are collinear".into(),
+            ));
+        }
+        let y = y.normalize();
+        let z = x.cross(&y);
+        let rotation = Rotation3::from_basis_unchecked(&[x, y, z]);
+        Ok(iso_to_pose(&Isometry3::from_parts(
+            origin.into(),
+            UnitQuaternion::from_rotation_matrix(&rotation),
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tcp_calibration_six_point() {
+        let tcp = pose_to_iso(&[10., -5., 120., 0., 0., 90.]);
+        let reference = Point3::new(500., 100., 50.);
+        // 由工具末端在基坐标系下的位姿反求法兰位姿
+        let flange = |tool: [f64; 6]| iso_to_pose(&(pose_to_iso(&tool) * tcp.inverse()));
+
+        let mut calibration = TcpCalibration::new();
+        for [rx, ry, rz] in [[180., 0., 0.], [150., 20., 30.], [200., -25., -40.]] {
+            calibration.add_point(flange([reference.x, reference.y, reference.z, rx, ry, rz]));
+        }
+        assert!(calibration.solve().is_err());
+        let last = [reference.x, reference.y, reference.z, 170., 10., 90.];
+        calibration.add_point(flange(last));
+
+        let result = calibration.solve().unwrap();
+        assert!(result.max_error < 1e-6);
+        assert!((result.tcp[2] - 120.).abs() < 1e-6);
+        assert_eq!(result.tcp[3..], [0.; 3]);
+
+        // 沿工具 X、Z 方向平移 50mm
+        let moved = |axis: Vector3<f64>| {
+            let tool = pose_to_iso(&last);
+            let mut pose = last;
+            let shift = tool.rotation * axis * 50.;
+            pose[0] += shift.x;
+            pose[1] += shift.y;
+            pose[2] += shift.z;
+            flange(pose)
+        };
+        calibration.set_x_point(moved(Vector3::x()));
+        calibration.set_z_point(moved(Vector3::z()));
+        let result = calibration.solve().unwrap();
+        let solved = pose_to_iso(&result.tcp);
+        assert!((solved.translation.vector - tcp.translation.vector).norm() < 1e-6);
+        assert!(solved.rotation.angle_to(&tcp.rotation) < 1e-6);
+    }
+
+    #[test]
+    fn test_user_frame_teaching() {
+        let mut teaching = UserFrameTeaching::new();
+        teaching.set_origin([400., 100., 20., 180., 0., 0.]);
+        teaching.set_x_point([400., 300., 20., 180., 0., 0.]);
+        assert!(teaching.solve().is_err());
+        teaching.set_xy_point([400., 500., 20., 180., 0., 0.]);
+        assert!(teaching.solve().is_err());
+
+        teaching.set_xy_point([300., 250., 20., 180., 0., 0.]);
+        let frame = pose_to_iso(&teaching.solve().unwrap());
+        let local = frame.inverse() * Point3::new(300., 250., 20.);
+        assert!((local - Point3::new(150., 100., 0.)).norm() < 1e-9);
+        assert!((frame.rotation.euler_angles().2.to_degrees() - 90.).abs() < 1e-9);
+    }
+}
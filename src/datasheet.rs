@@ -0,0 +1,223 @@
+use std::io::{ErrorKind, Read};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+use robot_behavior::{RobotException, RobotResult};
+
+use crate::robot_state::RobotState;
+
+/// 建立数据推送连接的超时，与指令端口默认的 [`NetworkConfig::connect_timeout`](crate::NetworkConfig::connect_timeout) 一致
+const DATASHEET_CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+/// 数据推送端口的读超时，控制器默认以毫秒级周期推送，超过该时间视为连接异常
+const DATASHEET_TIMEOUT: Duration = Duration::from_secs(3);
+/// 单个 JSON 对象的长度上限，超过该值说明数据流已经错位
+const MAX_FRAME_LEN: usize = 64 * 1024;
+
+/// 将控制器连续推送的 JSON 字节流切分为完整的 JSON 对象
+///
+/// 控制器在数据推送端口上不带任何分隔符地连续发送 JSON 对象，
+/// 这里通过统计花括号深度（并跳过字符串中的括号与转义字符）来确定帧边界。
+/// 一个对象超过 64 KiB 仍未结束时丢弃已缓存的数据，从下一个 `{` 重新同步。
+#[derive(Default)]
+pub struct JsonFramer {
+    buffer: Vec<u8>,
+    /// 已扫描位置，避免每次重复扫描
+    cursor: usize,
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+}
+
+impl JsonFramer {
+    /// 追加新收到的字节
+    pub fn push(&mut self, data: &[u8]) {
+        self.buffer.extend_from_slice(data);
+    }
+
+    /// 取出下一个完整的 JSON 对象，如果数据还不完整则返回 `None`
+    pub fn next_frame(&mut self) -> Option<Vec<u8>> {
+        while self.cursor < self.buffer.len() {
+            if self.depth > 0 && self.cursor >= MAX_FRAME_LEN {
+                self.reset();
+                continue;
+            }
+            let byte = self.buffer[self.cursor];
+            self.cursor += 1;
+
+            if self.depth == 0 {
+                // 帧与帧之间的空白或残缺数据直接丢弃
+                if byte == b'{' {
+                    self.buffer.drain(..self.cursor - 1);
+                    self.cursor = 1;
+                    self.depth = 1;
+                }
+                continue;
+            }
+
+            if self.in_string {
+                match (self.escaped, byte) {
+                    (true, _) => self.escaped = false,
+                    (false, b'\\') => self.escaped = true,
+                    (false, b'"') => self.in_string = false,
+                    _ => {}
+                }
+                continue;
+            }
+
+            match byte {
+                b'"' => self.in_string = true,
+                b'{' => self.depth += 1,
+                b'}' => {
+                    self.depth -= 1;
+                    if self.depth == 0 {
+                        let frame = self.buffer.drain(..self.cursor).collect();
+                        self.cursor = 0;
+                        return Some(frame);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if self.depth == 0 {
+            self.buffer.clear();
+            self.cursor = 0;
+        }
+        None
+    }
+
+    /// 丢弃当前未完成的对象
+    fn reset(&mut self) {
+        self.buffer.drain(..self.cursor);
+        self.cursor = 0;
+        self.depth = 0;
+        self.in_string = false;
+        self.escaped = false;
+    }
+}
+
+/// 数据推送端口读取器
+///
+/// 连接到 [`PORT_DATASHEET_JSON_1`](crate::network::PORT_DATASHEET_JSON_1) 等端口，
+/// 将控制器推送的 JSON 报文解析为 [`RobotState`]
+pub struct DatasheetReader {
+    stream: TcpStream,
+    framer: JsonFramer,
+}
+
+impl DatasheetReader {
+    /// 连接到指定 IP 与 JSON 数据推送端口
+    pub fn connect(host: &str, port: u16) -> RobotResult<Self> {
+        let mut last_error = None;
+        let stream = (host, port).to_socket_addrs()?.find_map(|addr| {
+            TcpStream::connect_timeout(&addr, DATASHEET_CONNECT_TIMEOUT)
+                .inspect_err(|e| last_error = Some(e.to_string()))
+                .ok()
+        });
+        let Some(stream) = stream else {
+            return Err(RobotException::NetworkError(format!(
+                "failed to connect to datasheet {host}:{port}: {}",
+                last_error.unwrap_or_else(|| "no address resolved".into())
+            )));
+        };
+        stream.set_read_timeout(Some(DATASHEET_TIMEOUT))?;
+        Ok(DatasheetReader { stream, framer: JsonFramer::default() })
+    }
+
+    /// 阻塞读取下一帧状态
+    pub fn next_state(&mut self) -> RobotResult<RobotState> {
+        loop {
+            if let Some(state) = self.framer.next_frame().map(|frame| parse_frame(&frame)) {
+                return state;
+            }
+            self.fill()?;
+        }
+    }
+
+    /// 读取最新一帧状态，丢弃缓冲区中已经过时的帧
+    ///
+    /// 如果当前没有任何缓存数据，则阻塞等待下一帧
+    pub fn latest_state(&mut self) -> RobotResult<RobotState> {
+        self.stream.set_nonblocking(true)?;
+        let drained = self.drain();
+        self.stream.set_nonblocking(false)?;
+        drained?;
+
+        let mut latest = None;
+        while let Some(state) = self.framer.next_frame().map(|frame| parse_frame(&frame)) {
+            latest = Some(state);
+        }
+        match latest {
+            Some(state) => state,
+            None => self.next_state(),
+        }
+    }
+
+    /// 复制底层连接，用于在其他线程中关闭连接以打断阻塞的读取
+    pub fn try_clone_stream(&self) -> RobotResult<TcpStream> {
+        Ok(self.stream.try_clone()?)
+    }
+
+    /// 读取一次数据到分帧器中
+    fn fill(&mut self) -> RobotResult<()> {
+        let mut buffer = [0_u8; 4096];
+        let n = self.stream.read(&mut buffer)?;
+        if n == 0 {
+            return Err(RobotException::NetworkError(
+                "datasheet connection closed by controller".into(),
+            ));
+        }
+        self.framer.push(&buffer[..n]);
+        Ok(())
+    }
+
+    /// 在非阻塞模式下读取所有已到达的数据
+    fn drain(&mut self) -> RobotResult<()> {
+        let mut buffer = [0_u8; 4096];
+        loop {
+            match self.stream.read(&mut buffer) {
+                Ok(0) => {
+                    return Err(RobotException::NetworkError(
+                        "datasheet connection closed by controller".into(),
+                    ));
+                }
+                Ok(n) => self.framer.push(&buffer[..n]),
+                Err(e) if e.kind() == ErrorKind::WouldBlock => return Ok(()),
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+}
+
+fn parse_frame(frame: &[u8]) -> RobotResult<RobotState> {
+    serde_json::from_slice(frame)
+        .map_err(|e| RobotException::DeserializeError(format!("invalid datasheet frame: {e}")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_framer() {
+        let mut framer = JsonFramer::default();
+        framer.push(br#"  {"a":{"b":"}{"},"#);
+        assert_eq!(framer.next_frame(), None);
+        framer.push(br#""c":"\"}"}{"d":1}{"e""#);
+        assert_eq!(
+            framer.next_frame().unwrap(),
+            br#"{"a":{"b":"}{"},"c":"\"}"}"#.to_vec()
+        );
+        assert_eq!(framer.next_frame().unwrap(), br#"{"d":1}"#.to_vec());
+        assert_eq!(framer.next_frame(), None);
+        framer.push(b":2}");
+        assert_eq!(framer.next_frame().unwrap(), br#"{"e":2}"#.to_vec());
+
+        framer.push(br#"{"f":""#);
+        framer.push(&[b'x'; MAX_FRAME_LEN]);
+        assert_eq!(framer.next_frame(), None);
+        assert!(framer.buffer.len() <= MAX_FRAME_LEN);
+        framer.push(br#""}{"g":3}"#);
+        assert_eq!(framer.next_frame().unwrap(), br#"{"g":3}"#.to_vec());
+    }
+}
@@ -0,0 +1,340 @@
+use std::fmt::{Display, Write};
+
+use robot_behavior::RobotResult;
+
+use crate::{RobotError, RobotMode, robot_impl::RobotImpl, types::*};
+
+/// 关节伺服故障类型
+///
+/// 关节驱动器以 CiA 402 行规接入 EtherCAT 主站，`ReadAxisErrorCode` 返回驱动器的错误码
+/// （对象 `0x603F`）。这里只映射 CiA 301/402 标准错误码表中定义的码段；
+/// 厂商自定义码段 `0xFF00 ~ 0xFFFF` 以及表中没有的错误码不做猜测，保留为 [`AxisError::code`]。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AxisFault {
+    /// 通用故障
+    Generic,
+    /// 过流
+    Overcurrent,
+    /// 输出短路或对地漏电
+    ShortCircuit,
+    /// 过压
+    Overvoltage,
+    /// 欠压
+    Undervoltage,
+    /// 其他电压故障
+    Voltage,
+    /// 过温
+    Overheat,
+    /// 驱动器硬件故障
+    Hardware,
+    /// 驱动器软件或参数故障
+    Software,
+    /// 制动电路故障
+    Brake,
+    /// 电机故障，例如堵转
+    Motor,
+    /// 编码器故障
+    Encoder,
+    /// 总线通信故障
+    Communication,
+    /// 速度控制故障，例如超速
+    Overspeed,
+    /// 位置跟随误差过大
+    FollowingError,
+    /// 超出位置限位
+    PositionLimit,
+    /// 其他位置控制故障
+    PositionControl,
+    /// 外部故障输入
+    External,
+}
+
+impl AxisFault {
+    /// 按照 CiA 301/402 标准错误码分段归类，没有故障或不在标准码段内时返回 `None`
+    pub fn from_code(code: u16) -> Option<Self> {
+        let fault = match code {
+            0x1000..=0x1FFF => AxisFault::Generic,
+            0x2320..=0x233F => AxisFault::ShortCircuit,
+            0x2000..=0x2FFF => AxisFault::Overcurrent,
+            0x3110 | 0x3210 | 0x3310 => AxisFault::Overvoltage,
+            0x3120 | 0x3220 | 0x3320 => AxisFault::Undervoltage,
+            0x3000..=0x3FFF => AxisFault::Voltage,
+            0x4000..=0x4FFF => AxisFault::Overheat,
+            0x5000..=0x5FFF => AxisFault::Hardware,
+            0x6000..=0x6FFF => AxisFault::Software,
+            0x7110..=0x711F => AxisFault::Brake,
+            0x7120..=0x712F => AxisFault::Motor,
+            0x7300..=0x73FF => AxisFault::Encoder,
+            0x7500..=0x75FF | 0x8100..=0x81FF => AxisFault::Communication,
+            0x8400..=0x84FF => AxisFault::Overspeed,
+            0x8611 => AxisFault::FollowingError,
+            0x8612 => AxisFault::PositionLimit,
+            0x8500..=0x86FF => AxisFault::PositionControl,
+            0x9000..=0x9FFF => AxisFault::External,
+            _ => return None,
+        };
+        Some(fault)
+    }
+
+    /// 中文描述
+    pub fn description_zh(&self) -> &'static str {
+        match self {
+            AxisFault::Generic => "通用故障",
+            AxisFault::Overcurrent => "过流",
+            AxisFault::ShortCircuit => "输出短路或对地漏电",
+            AxisFault::Overvoltage => "过压",
+            AxisFault::Undervoltage => "欠压",
+            AxisFault::Voltage => "电压异常",
+            AxisFault::Overheat => "过温",
+            AxisFault::Hardware => "驱动器硬件故障",
+            AxisFault::Software => "驱动器软件或参数故障",
+            AxisFault::Brake => "制动电路故障",
+            AxisFault::Motor => "电机故障",
+            AxisFault::Encoder => "编码器故障",
+            AxisFault::Communication => "总线通信故障",
+            AxisFault::Overspeed => "超速",
+            AxisFault::FollowingError => "位置跟随误差过大",
+            AxisFault::PositionLimit => "超出位置限位",
+            AxisFault::PositionControl => "位置控制故障",
+            AxisFault::External => "外部故障输入",
+        }
+    }
+
+    /// 英文描述
+    pub fn description_en(&self) -> &'static str {
+        match self {
+            AxisFault::Generic => "generic fault",
+            AxisFault::Overcurrent => "overcurrent",
+            AxisFault::ShortCircuit => "output short circuit or earth leakage",
+            AxisFault::Overvoltage => "overvoltage",
+            AxisFault::Undervoltage => "undervoltage",
+            AxisFault::Voltage => "voltage fault",
+            AxisFault::Overheat => "overtemperature",
+            AxisFault::Hardware => "drive hardware fault",
+            AxisFault::Software => "drive software or parameter fault",
+            AxisFault::Brake => "brake circuit fault",
+            AxisFault::Motor => "motor fault",
+            AxisFault::Encoder => "encoder fault",
+            AxisFault::Communication => "fieldbus communication fault",
+            AxisFault::Overspeed => "overspeed",
+            AxisFault::FollowingError => "following error too large",
+            AxisFault::PositionLimit => "position limit exceeded",
+            AxisFault::PositionControl => "position control fault",
+            AxisFault::External => "external fault input",
+        }
+    }
+
+    /// 处理建议
+    pub fn remediation(&self) -> &'static str {
+        match self {
+            AxisFault::Overcurrent | AxisFault::Motor => {
+                "检查负载是否超限、关节是否发生碰撞或卡死，复位后降低速度重试"
+            }
+            AxisFault::ShortCircuit => "断电后检查电机动力线与接地，故障未排除前不要重新上电",
+            AxisFault::Overvoltage => "检查减速是否过猛、制动电阻是否正常，适当降低加速度",
+            AxisFault::Undervoltage | AxisFault::Voltage => "检查电箱供电与 48V 电源",
+            AxisFault::Overheat => "停机冷却，检查环境温度与关节负载率",
+            AxisFault::Brake => "检查抱闸与制动电路，必要时联系厂商维修",
+            AxisFault::Encoder => "检查编码器线缆与接头，复位后仍报警需要重新标定零位",
+            AxisFault::Communication => "检查 EtherCAT 线缆与接头，重启控制器",
+            AxisFault::Overspeed | AxisFault::FollowingError | AxisFault::PositionControl => {
+                "检查是否发生碰撞，降低速度或加速度，确认负载参数设置正确"
+            }
+            AxisFault::PositionLimit => "在自由驱动或点动模式下将关节移回限位范围内",
+            AxisFault::External => "检查外部安全输入信号",
+            AxisFault::Generic | AxisFault::Hardware | AxisFault::Software => {
+                "记录错误码并复位，反复出现时联系厂商"
+            }
+        }
+    }
+}
+
+/// 单个关节的故障
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AxisError {
+    /// 关节序号，从 0 开始
+    pub axis: usize,
+    /// 驱动器原始错误码
+    pub code: u16,
+    /// 错误码所属的故障类型，不在标准码段内时为 `None`
+    pub fault: Option<AxisFault>,
+}
+
+impl Display for AxisError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let description = self
+            .fault
+            .map_or("unmapped servo fault", |f| f.description_en());
+        write!(
+            f,
+            "axis {}: {description} (0x{:04X})",
+            self.axis + 1,
+            self.code
+        )
+    }
+}
+
+/// 解析 `ReadAxisErrorCode` 返回的错误码，只保留有故障的关节
+pub fn decode_axis_errors(codes: &[u16]) -> Vec<AxisError> {
+    codes
+        .iter()
+        .enumerate()
+        .filter(|(_, code)| **code != 0)
+        .map(|(axis, &code)| AxisError { axis, code, fault: AxisFault::from_code(code) })
+        .collect()
+}
+
+/// 机器人故障诊断报告，汇总状态机、状态标志、急停信息与关节故障
+#[derive(Debug)]
+pub struct FaultReport {
+    pub mode: RobotMode,
+    pub flag: RobotFlag,
+    pub emergency: EmergencyInfo,
+    pub axis_errors: Vec<AxisError>,
+    /// 控制器报警码，来自 JSON 推送数据中的 `Error_Code`
+    ///
+    /// `ReadRobotFlag` 中的错误码只有一个字节，装不下目录中的错误码，因此不用于解析；
+    /// 只读取指令端口时或控制器没有报警时为 `None`。
+    pub controller_error: Option<RobotError>,
+}
+
+impl FaultReport {
+    /// 是否存在任何故障
+    pub fn has_fault(&self) -> bool {
+        self.flag.is_error
+            || self.flag.is_emergency_stop
+            || self.flag.is_safety_guard
+            || self.emergency.is_estop
+            || self.emergency.is_safety_guard
+            || !self.axis_errors.is_empty()
+            || self.mode.is_error()
+            || self.mode.is_safety_stop()
+    }
+
+    /// 中文故障摘要
+    pub fn summary_zh(&self) -> String {
+        let mut summary = String::new();
+        let _ = self.render(&mut summary, true);
+        summary
+    }
+
+    /// 按中文或英文输出报告，中文时附带处理建议
+    fn render(&self, out: &mut impl Write, zh: bool) -> std::fmt::Result {
+        let text = |zh_text, en_text| if zh { zh_text } else { en_text };
+        let describe = |error: &RobotError| {
+            if zh {
+                format!(
+                    "{} ({})，{}",
+                    error.description_zh(),
+                    error.code(),
+                    error.remediation()
+                )
+            } else {
+                format!("{error}")
+            }
+        };
+
+        write!(out, "{}{:?}", text("状态机: ", "state: "), self.mode)?;
+        if !self.has_fault() {
+            return write!(out, "{}", text("，无故障", ", no fault"));
+        }
+        if self.emergency.is_estop || self.flag.is_emergency_stop {
+            let label = text("急停已触发，代码", "emergency stop, code");
+            write!(out, "\n{label} {}", self.emergency.esto_code)?;
+        }
+        if self.emergency.is_safety_guard || self.flag.is_safety_guard {
+            let label = text("安全光幕已触发，代码", "safety guard, code");
+            write!(out, "\n{label} {}", self.emergency.safety_guard_code)?;
+        }
+        if let Some(error) = &self.controller_error {
+            let label = text("控制器报警", "controller error");
+            write!(out, "\n{label}: {}", describe(error))?;
+        }
+        if self.flag.is_error {
+            let label = text("报警关节", "error axis");
+            write!(out, "\n{label} {}", self.flag.error_id)?;
+        }
+        for error in &self.axis_errors {
+            let axis = text("关节", "axis");
+            let description = match (error.fault, zh) {
+                (Some(fault), true) => {
+                    format!(
+                        "{} (0x{:04X})，{}",
+                        fault.description_zh(),
+                        error.code,
+                        fault.remediation()
+                    )
+                }
+                (None, true) => format!(
+                    "未归类的伺服故障 (0x{:04X})，记录错误码并联系厂商",
+                    error.code
+                ),
+                (Some(fault), false) => {
+                    format!("{} (0x{:04X})", fault.description_en(), error.code)
+                }
+                (None, false) => format!("unmapped servo fault (0x{:04X})", error.code),
+            };
+            write!(out, "\n{axis} {}: {description}", error.axis + 1)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for FaultReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.render(f, false)
+    }
+}
+
+impl<const N: usize> RobotImpl<N> {
+    /// 读取状态机、状态标志、急停信息与关节错误码，生成故障诊断报告
+    ///
+    /// 指令端口读不到完整的控制器报警码，报告中的 `controller_error` 总是 `None`，
+    /// 需要时使用 [`HansRobot::diagnose`](crate::HansRobot::diagnose)。
+    pub fn diagnose(&mut self) -> RobotResult<FaultReport> {
+        Ok(FaultReport {
+            mode: self.state_read_cur_fsm(0)?,
+            flag: self.state_read_robot_state(0)?,
+            emergency: self.state_read_emergency_info(0)?,
+            axis_errors: decode_axis_errors(&self.state_read_axis_error_code(0)?),
+            controller_error: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_axis_errors() {
+        let errors = decode_axis_errors(&[0, 0x2310, 0, 0x7305, 0x8611, 0xFF01]);
+        let faults: Vec<_> = errors.iter().map(|e| (e.axis, e.fault)).collect();
+        assert_eq!(
+            faults,
+            [
+                (1, Some(AxisFault::Overcurrent)),
+                (3, Some(AxisFault::Encoder)),
+                (4, Some(AxisFault::FollowingError)),
+                (5, None)
+            ]
+        );
+        assert_eq!(errors[0].to_string(), "axis 2: overcurrent (0x2310)");
+        assert_eq!(errors[3].code, 0xFF01);
+
+        let report = FaultReport {
+            mode: RobotMode::Error,
+            flag: RobotFlag { is_error: true, error_id: 2, ..RobotFlag::default() },
+            emergency: EmergencyInfo::default(),
+            axis_errors: errors,
+            controller_error: Some(RobotError::RECParametersError),
+        };
+        assert!(report.has_fault());
+        let summary = report.summary_zh();
+        assert!(summary.contains("关节 2: 过流 (0x2310)"));
+        assert!(summary.contains("关节 6: 未归类的伺服故障 (0xFF01)"));
+        let text = report.to_string();
+        assert!(text.contains("controller error: Invalid command parameters (40034)"));
+        assert!(text.contains("error axis 2"));
+    }
+}
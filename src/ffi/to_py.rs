@@ -11,20 +11,20 @@ pub struct PyHansS30(HansS30);
 #[pymethods]
 impl PyHansS30 {
     #[new]
-    fn new(ip: &str) -> Self {
-        PyHansS30(HansS30::new(ip))
+    fn new(ip: &str) -> PyResult<Self> {
+        HansS30::new(ip).map(PyHansS30).map_err(Into::into)
     }
 
     fn __repr__(&self) -> String {
         "HansS30".to_string()
     }
 
-    fn connect(&mut self, ip: &str, port: u16) {
-        self.0.connect(ip, port)
+    fn connect(&mut self, ip: &str, port: u16) -> PyResult<()> {
+        self.0.connect(ip, port).map_err(Into::into)
     }
 
-    fn disconnect(&mut self) {
-        self.0.disconnect()
+    fn disconnect(&mut self) -> PyResult<()> {
+        self.0.disconnect().map_err(Into::into)
     }
 
     fn read_joint(&mut self) -> PyResult<[f64; HANS_DOF]> {
@@ -0,0 +1,237 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use nalgebra::{Isometry3, Translation3, UnitQuaternion};
+use robot_behavior::{RobotException, RobotResult};
+use serde::{Deserialize, Serialize};
+
+use crate::{DEFAULT_TCP_NAME, DEFAULT_UCS_NAME, robot_impl::RobotImpl};
+
+/// 一个具名的坐标系，位姿为 x、y、z、rx、ry、rz，单位 mm 与 °
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Frame {
+    pub name: String,
+    pub pose: [f64; 6],
+}
+
+/// 工具坐标（TCP）与用户坐标（UCS）的注册表
+///
+/// 默认包含位姿为零的 [`DEFAULT_TCP_NAME`] 与 [`DEFAULT_UCS_NAME`]，可以保存为 JSON 文件并在
+/// 下次启动时读取。运动时按名称选择坐标系，选中的坐标系会在下发运动前写入控制器。
+///
+/// 注册表中的名称只存在于本地，不会在控制器上定义同名的坐标系：选中的位姿通过
+/// `SetCurTCP` 与 `SetCurUCS` 覆盖控制器的当前工具坐标与用户坐标，运动指令总是引用
+/// [`DEFAULT_TCP_NAME`] 与 [`DEFAULT_UCS_NAME`]。示教器上看到的仍是当前坐标系，
+/// 而不是这里的名称。
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FrameRegistry {
+    tools: BTreeMap<String, [f64; 6]>,
+    user_frames: BTreeMap<String, [f64; 6]>,
+}
+
+impl Default for FrameRegistry {
+    fn default() -> Self {
+        FrameRegistry {
+            tools: BTreeMap::from([(DEFAULT_TCP_NAME.to_string(), [0.; 6])]),
+            user_frames: BTreeMap::from([(DEFAULT_UCS_NAME.to_string(), [0.; 6])]),
+        }
+    }
+}
+
+impl FrameRegistry {
+    /// 从 JSON 文件读取注册表，文件中缺少的默认坐标系会被补上
+    pub fn load(path: impl AsRef<Path>) -> RobotResult<Self> {
+        let path = path.as_ref();
+        let data = fs::read_to_string(path).map_err(|e| {
+            RobotException::UnprocessableInstructionError(format!(
+                "cannot read frame file {}: {e}",
+                path.display()
+            ))
+        })?;
+        let mut registry: FrameRegistry = serde_json::from_str(&data)?;
+        let defaults = FrameRegistry::default();
+        for (name, pose) in defaults.tools {
+            registry.tools.entry(name).or_insert(pose);
+        }
+        for (name, pose) in defaults.user_frames {
+            registry.user_frames.entry(name).or_insert(pose);
+        }
+        Ok(registry)
+    }
+
+    /// 将注册表保存为 JSON 文件
+    pub fn save(&self, path: impl AsRef<Path>) -> RobotResult<()> {
+        let path = path.as_ref();
+        let data = serde_json::to_string_pretty(self)?;
+        fs::write(path, data).map_err(|e| {
+            RobotException::UnprocessableInstructionError(format!(
+                "cannot write frame file {}: {e}",
+                path.display()
+            ))
+        })
+    }
+
+    /// 定义或覆盖一个工具坐标
+    pub fn define_tool(&mut self, name: impl Into<String>, pose: [f64; 6]) {
+        self.tools.insert(name.into(), pose);
+    }
+
+    /// 定义或覆盖一个用户坐标
+    pub fn define_user_frame(&mut self, name: impl Into<String>, pose: [f64; 6]) {
+        self.user_frames.insert(name.into(), pose);
+    }
+
+    /// 删除一个工具坐标，默认工具坐标不能删除
+    pub fn remove_tool(&mut self, name: &str) -> Option<[f64; 6]> {
+        (name != DEFAULT_TCP_NAME)
+            .then(|| self.tools.remove(name))
+            .flatten()
+    }
+
+    /// 删除一个用户坐标，默认用户坐标不能删除
+    pub fn remove_user_frame(&mut self, name: &str) -> Option<[f64; 6]> {
+        (name != DEFAULT_UCS_NAME)
+            .then(|| self.user_frames.remove(name))
+            .flatten()
+    }
+
+    /// 按名称查找工具坐标
+    pub fn tool(&self, name: &str) -> RobotResult<Frame> {
+        lookup(&self.tools, "tool", name)
+    }
+
+    /// 按名称查找用户坐标
+    pub fn user_frame(&self, name: &str) -> RobotResult<Frame> {
+        lookup(&self.user_frames, "user frame", name)
+    }
+
+    /// 全部工具坐标，按名称排序
+    pub fn tools(&self) -> impl Iterator<Item = (&str, &[f64; 6])> {
+        self.tools.iter().map(|(name, pose)| (name.as_str(), pose))
+    }
+
+    /// 全部用户坐标，按名称排序
+    pub fn user_frames(&self) -> impl Iterator<Item = (&str, &[f64; 6])> {
+        self.user_frames
+            .iter()
+            .map(|(name, pose)| (name.as_str(), pose))
+    }
+}
+
+fn lookup(frames: &BTreeMap<String, [f64; 6]>, kind: &str, name: &str) -> RobotResult<Frame> {
+    frames
+        .get(name)
+        .map(|pose| Frame { name: name.to_string(), pose: *pose })
+        .ok_or_else(|| RobotException::InvalidInstruction(format!("unknown {kind} {name}")))
+}
+
+/// 控制器当前工具坐标与用户坐标的位姿
+///
+/// 每次建立连接后先读取一次控制器上的设置，之后只在位姿变化时写入；
+/// 连接断开重连或控制器重启后会重新读取，不会沿用过期的记录。
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ActiveFrames {
+    /// 读取设置时所在的连接序号
+    session: Option<u64>,
+    tool: [f64; 6],
+    user_frame: [f64; 6],
+}
+
+impl ActiveFrames {
+    /// 与控制器上的位姿不同时，通过 `SetCurTCP` 与 `SetCurUCS` 写入控制器
+    pub fn apply<const N: usize>(
+        &mut self,
+        robot_impl: &mut RobotImpl<N>,
+        tool: &Frame,
+        user_frame: &Frame,
+    ) -> RobotResult<()> {
+        self.apply_tool(robot_impl, tool)?;
+        self.apply_user_frame(robot_impl, user_frame)
+    }
+
+    pub fn apply_tool<const N: usize>(
+        &mut self,
+        robot_impl: &mut RobotImpl<N>,
+        tool: &Frame,
+    ) -> RobotResult<()> {
+        self.sync(robot_impl)?;
+        if self.tool != tool.pose {
+            robot_impl.set_pose_o_to_t((0, tool.pose))?;
+            self.tool = tool.pose;
+        }
+        Ok(())
+    }
+
+    pub fn apply_user_frame<const N: usize>(
+        &mut self,
+        robot_impl: &mut RobotImpl<N>,
+        user_frame: &Frame,
+    ) -> RobotResult<()> {
+        self.sync(robot_impl)?;
+        if self.user_frame != user_frame.pose {
+            robot_impl.set_pose_u_to_t((0, user_frame.pose))?;
+            self.user_frame = user_frame.pose;
+        }
+        Ok(())
+    }
+
+    /// 连接变化后重新读取控制器上的工具坐标与用户坐标
+    fn sync<const N: usize>(&mut self, robot_impl: &mut RobotImpl<N>) -> RobotResult<()> {
+        let session = robot_impl.network.session();
+        if self.session != Some(session) {
+            self.tool = robot_impl.read_pose_o_to_t(0)?;
+            self.user_frame = robot_impl.read_pose_u_to_t(0)?;
+            self.session = Some(robot_impl.network.session());
+        }
+        Ok(())
+    }
+}
+
+/// 将 x、y、z、rx、ry、rz 形式的位姿转换为齐次变换，单位 mm 与 °
+pub(crate) fn pose_to_iso(pose: &[f64; 6]) -> Isometry3<f64> {
+    Isometry3::from_parts(
+        Translation3::new(pose[0], pose[1], pose[2]),
+        UnitQuaternion::from_euler_angles(
+            pose[3].to_radians(),
+            pose[4].to_radians(),
+            pose[5].to_radians(),
+        ),
+    )
+}
+
+/// [`pose_to_iso`] 的逆变换
+pub(crate) fn iso_to_pose(iso: &Isometry3<f64>) -> [f64; 6] {
+    let (rx, ry, rz) = iso.rotation.euler_angles();
+    let t = iso.translation.vector;
+    [
+        t.x,
+        t.y,
+        t.z,
+        rx.to_degrees(),
+        ry.to_degrees(),
+        rz.to_degrees(),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_registry_persistence() {
+        let mut registry = FrameRegistry::default();
+        registry.define_tool("gripper", [0., 0., 120., 0., 0., 90.]);
+        registry.define_user_frame("table", [400., -200., 10., 0., 0., 0.]);
+        assert!(registry.remove_tool(DEFAULT_TCP_NAME).is_none());
+        assert!(registry.user_frame("fixture").is_err());
+
+        let path = std::env::temp_dir().join(format!("libhans_frames_{}.json", std::process::id()));
+        registry.save(&path).unwrap();
+        let loaded = FrameRegistry::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, registry);
+        assert_eq!(loaded.tool("gripper").unwrap().pose[2], 120.);
+        assert_eq!(loaded.tools().count(), 2);
+    }
+}
@@ -1,3 +1,146 @@
+use std::{fmt::Display, marker::PhantomData};
+
+use robot_behavior::{Coord, OverrideOnce, RobotResult, behavior::*};
+
+use crate::{
+    ArcOrientation, DEFAULT_BLEND_RADIUS, DEFAULT_TCP_NAME, DEFAULT_UCS_NAME, DhParameters,
+    FrameRegistry, HANS_DOF, HansRobot, Kinematics, Lifecycle, MotionCommand, PORT_IF, WaitPolicy,
+    Workspace, frame::ActiveFrames, robot::HansType, robot_impl::RobotImpl,
+};
+
+/// 为一个六轴机型生成标记类型、[`HansRobot`] 别名以及关节、末端与 DH 参数的实现
+macro_rules! hans_model {
+    ($model:ident, $marker:ident, $robot:ident, $params:ident) => {
+        pub struct $marker;
+        impl crate::robot::HansType for $marker {
+            const N: usize = crate::HANS_DOF;
+            const MODEL: crate::HansModel = crate::HansModel::$model;
+        }
+
+        pub type $robot = crate::HansRobot<$marker, { crate::HANS_DOF }>;
+
+        impl robot_behavior::behavior::Joints<{ crate::HANS_DOF }> for $robot {
+            const JOINT_MIN: [f64; crate::HANS_DOF] = $params.joint_min;
+            const JOINT_MAX: [f64; crate::HANS_DOF] = $params.joint_max;
+            /// 单位 °/s
+            const JOINT_VEL_BOUND: [f64; crate::HANS_DOF] = $params.joint_vel;
+            /// 单位 rad/s²，下发时换算为控制器使用的 °/s²
+            const JOINT_ACC_BOUND: [f64; crate::HANS_DOF] = $params.joint_acc;
+        }
+
+        impl robot_behavior::behavior::EndPoint for $robot {
+            const CARTESIAN_VEL_BOUND: f64 = $params.cartesian_vel;
+            const CARTESIAN_ACC_BOUND: f64 = $params.cartesian_acc;
+            const CARTESIAN_JERK_BOUND: f64 = 1e-3;
+        }
+
+        impl crate::DhParameters<{ crate::HANS_DOF }> for $robot {
+            const DH: [[f64; 4]; crate::HANS_DOF] = $params.dh;
+        }
+    };
+}
+
 mod hans_s;
 
 pub use hans_s::*;
+
+/// 机型参数
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ModelParams {
+    /// 关节下限，单位 °
+    pub joint_min: [f64; HANS_DOF],
+    /// 关节上限，单位 °
+    pub joint_max: [f64; HANS_DOF],
+    /// 关节最大速度，单位 °/s
+    pub joint_vel: [f64; HANS_DOF],
+    /// 关节最大加速度，单位 rad/s²，与 °/s 的关节速度不同，下发时换算为 °/s²
+    pub joint_acc: [f64; HANS_DOF],
+    /// 末端最大线速度，单位 m/s
+    pub cartesian_vel: f64,
+    /// 末端最大线加速度，单位 m/s²
+    pub cartesian_acc: f64,
+    /// 额定负载，单位 kg
+    pub max_load: f64,
+    /// DH 参数，每行为 `[theta 偏置 rad, d m, a m, alpha rad]`
+    pub dh: [[f64; 4]; HANS_DOF],
+    /// `ReadRobotModel` 返回的机型编号，尚未确认时为 `None`，此时初始化不检查机型
+    pub model_code: Option<u16>,
+}
+
+/// 支持的机型
+///
+/// 目前只收录了 S30 的参数；其它机型需要在取得厂家给出的 DH、关节限位、负载数据与
+/// `ReadRobotModel` 编号后再加入。编号确认之前可以用
+/// [`set_expected_model_code`](crate::HansRobot::set_expected_model_code) 在初始化时检查机型。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HansModel {
+    S30,
+}
+
+impl HansModel {
+    pub const ALL: [HansModel; 1] = [HansModel::S30];
+
+    pub const fn params(&self) -> &'static ModelParams {
+        match self {
+            HansModel::S30 => &HANS_S30,
+        }
+    }
+
+    pub fn kinematics(&self) -> Kinematics<HANS_DOF> {
+        let params = self.params();
+        Kinematics::new(params.dh, params.joint_min, params.joint_max)
+    }
+}
+
+impl Display for HansModel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+impl<T: HansType, const N: usize> HansRobot<T, N>
+where
+    HansRobot<T, N>: Joints<N> + EndPoint + DhParameters<N>,
+{
+    /// 新建一个机器人实例，使用传入的机器人 ip 与默认端口 [`PORT_IF`](crate::network::PORT_IF)
+    pub fn new(ip: &str) -> RobotResult<Self> {
+        Self::new_with_port(ip, PORT_IF)
+    }
+
+    /// 新建一个机器人实例，使用传入的机器人 ip 与指令端口，可用于连接开启 `simulator` 特性后的 `Simulator`
+    ///
+    /// 无法连接到控制器时返回错误
+    pub fn new_with_port(ip: &str, port: u16) -> RobotResult<Self> {
+        let robot_impl = RobotImpl::new_with_port(ip, port)?;
+        let mut robot = HansRobot {
+            marker: PhantomData,
+            robot_impl,
+            datasheet: None,
+            state_stream: None,
+            state_cache: Default::default(),
+            is_moving: false,
+            motion_started: None,
+            stop_requested: false,
+            lifecycle: Lifecycle::default(),
+            wait_policy: WaitPolicy::default(),
+            workspace: Workspace::default(),
+            emergency_stop_output: None,
+            servo: None,
+            expected_model_code: None,
+            coord: OverrideOnce::new(Coord::OCS),
+            max_vel: OverrideOnce::new(Self::JOINT_VEL_BOUND),
+            max_acc: OverrideOnce::new(Self::JOINT_ACC_BOUND),
+            max_cartesian_vel: OverrideOnce::new(Self::CARTESIAN_VEL_BOUND),
+            max_cartesian_acc: OverrideOnce::new(Self::CARTESIAN_ACC_BOUND),
+            motion_command: OverrideOnce::new(MotionCommand::Auto),
+            blend_radius: OverrideOnce::new(DEFAULT_BLEND_RADIUS),
+            arc_orientation: OverrideOnce::new(ArcOrientation::Rotating),
+            frames: FrameRegistry::default(),
+            active_frames: ActiveFrames::default(),
+            tool: OverrideOnce::new(DEFAULT_TCP_NAME.to_string()),
+            user_frame: OverrideOnce::new(DEFAULT_UCS_NAME.to_string()),
+        };
+        let _ = robot.set_scale(0.1);
+        Ok(robot)
+    }
+}
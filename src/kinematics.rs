@@ -0,0 +1,315 @@
+use std::f64::consts::{PI, TAU};
+
+use nalgebra::{Isometry3, SMatrix, Translation3, UnitQuaternion, Vector3};
+use robot_behavior::{RobotException, RobotResult};
+
+use crate::frame::{iso_to_pose, pose_to_iso};
+
+/// 关节角的正弦小于该值时视为奇异，约 1°
+const SINGULAR_SINE: f64 = 0.017;
+/// 腕部中心到肩部轴线的距离小于该值时视为肩部奇异，单位 mm
+const SHOULDER_SINGULAR_DISTANCE: f64 = 10.;
+
+/// 提供 DH 参数的机型，每行为 `[theta 偏置 rad, d m, a m, alpha rad]`
+pub trait DhParameters<const N: usize> {
+    const DH: [[f64; 4]; N];
+}
+
+/// 奇异位形的类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Singularity {
+    /// 腕部中心位于一轴与二轴构成的平面附近，一轴的两组解重合
+    Shoulder,
+    /// 手臂完全伸直或折叠，三轴接近 0° 或 180°
+    Elbow,
+    /// 四轴与六轴共线，五轴接近 0° 或 180°
+    Wrist,
+}
+
+/// 由标准 DH 参数描述的串联机械臂运动学
+///
+/// 关节角单位为 °，位姿为法兰在基坐标系下的 x、y、z、rx、ry、rz，单位 mm 与 °。
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Kinematics<const N: usize> {
+    pub dh: [[f64; 4]; N],
+    pub joint_min: [f64; N],
+    pub joint_max: [f64; N],
+}
+
+impl<const N: usize> Kinematics<N> {
+    pub const fn new(dh: [[f64; 4]; N], joint_min: [f64; N], joint_max: [f64; N]) -> Self {
+        Kinematics { dh, joint_min, joint_max }
+    }
+
+    /// 正运动学，返回法兰位姿
+    pub fn forward(&self, joint: &[f64; N]) -> [f64; 6] {
+        iso_to_pose(&self.forward_iso(joint))
+    }
+
+    /// 正运动学，长度单位为 mm
+    pub(crate) fn forward_iso(&self, joint: &[f64; N]) -> Isometry3<f64> {
+        self.frames(joint)[N]
+    }
+
+    /// 基坐标系与每个连杆坐标系的位姿，第 `i` 个元素为第 `i` 轴转动之前的坐标系
+    fn frames(&self, joint: &[f64; N]) -> Vec<Isometry3<f64>> {
+        let mut frames = Vec::with_capacity(N + 1);
+        frames.push(Isometry3::identity());
+        for (dh, q) in self.dh.iter().zip(joint) {
+            let last = frames[frames.len() - 1];
+            frames.push(last * dh_transform(dh, dh[0] + q.to_radians()));
+        }
+        frames
+    }
+
+    /// 基坐标系下的几何雅可比矩阵
+    ///
+    /// 前三行为法兰线速度（mm/s），后三行为角速度（rad/s），对应关节角速度（rad/s）。
+    pub fn jacobian(&self, joint: &[f64; N]) -> SMatrix<f64, 6, N> {
+        let frames = self.frames(joint);
+        let end = frames[N].translation.vector;
+        let mut jacobian = SMatrix::<f64, 6, N>::zeros();
+        for (i, frame) in frames.iter().take(N).enumerate() {
+            let z = frame.rotation * Vector3::z();
+            let v = z.cross(&(end - frame.translation.vector));
+            jacobian.fixed_view_mut::<3, 1>(0, i).copy_from(&v);
+            jacobian.fixed_view_mut::<3, 1>(3, i).copy_from(&z);
+        }
+        jacobian
+    }
+
+    /// 可操作度 `sqrt(det(J * J^T))`，接近零时位于奇异位形附近
+    pub fn manipulability(&self, joint: &[f64; N]) -> f64 {
+        let jacobian = self.jacobian(joint);
+        (jacobian * jacobian.transpose())
+            .determinant()
+            .max(0.)
+            .sqrt()
+    }
+
+    /// 关节角是否在限位内
+    pub fn within_limits(&self, joint: &[f64; N]) -> bool {
+        (0..N).all(|i| (self.joint_min[i]..=self.joint_max[i]).contains(&joint[i]))
+    }
+
+    /// 轴数为泛型参数时的 [`inverse_nearest`](Kinematics::<6>::inverse_nearest)，
+    /// 只有六轴机型有解析逆解，其它轴数返回错误
+    pub(crate) fn nearest_solution(
+        &self,
+        flange: &[f64; 6],
+        seed: &[f64; N],
+    ) -> RobotResult<[f64; N]> {
+        let (Ok(dh), Ok(joint_min), Ok(joint_max), Ok(seed)) = (
+            self.dh[..].try_into(),
+            self.joint_min[..].try_into(),
+            self.joint_max[..].try_into(),
+            seed[..].try_into(),
+        ) else {
+            return Err(RobotException::UnprocessableInstructionError(format!(
+                "no inverse kinematics for a {N}-axis arm"
+            )));
+        };
+        let solution =
+            Kinematics::<6>::new(dh, joint_min, joint_max).inverse_nearest(flange, &seed)?;
+        Ok(std::array::from_fn(|i| solution[i]))
+    }
+}
+
+impl Kinematics<6> {
+    /// 解析逆运动学，返回法兰到达 `flange` 的全部分支解，关节角范围为 (-180°, 180°]
+    ///
+    /// 适用于二、三、四轴平行且腕部三轴相交于一点的构型，其它构型见
+    /// [`has_analytic_inverse`](Self::has_analytic_inverse)，返回空列表；腕部奇异时六轴取 0°。
+    pub fn inverse(&self, flange: &[f64; 6]) -> Vec<[f64; 6]> {
+        if !self.has_analytic_inverse() {
+            return Vec::new();
+        }
+        let dh = &self.dh;
+        let target = pose_to_iso(flange);
+        let z6 = target.rotation * Vector3::z();
+        // 二、三轴的 d 沿相互平行的轴线方向，可以合并到四轴
+        let d4 = (dh[1][1] + dh[2][1] + dh[3][1]) * 1000.;
+        let d6 = dh[5][1] * 1000.;
+        let (a2, a3) = (dh[1][2] * 1000., dh[2][2] * 1000.);
+        let (s1, s4, s5) = (dh[0][3].sin(), dh[3][3].sin(), dh[4][3].sin());
+
+        let p5 = target.translation.vector - z6 * d6;
+        let r = p5.x.hypot(p5.y);
+        if r < d4.abs() {
+            return Vec::new();
+        }
+        let phi = p5.y.atan2(p5.x);
+        let shoulder = (d4 / s1 / r).asin();
+
+        let mut solutions = Vec::new();
+        for theta1 in [phi + shoulder, phi + PI - shoulder] {
+            let z1 = Vector3::new(s1 * theta1.sin(), -s1 * theta1.cos(), 0.);
+            let cos5 = -z6.dot(&z1) / (s4 * s5);
+            if cos5.abs() > 1. + 1e-9 {
+                continue;
+            }
+            let acos5 = cos5.clamp(-1., 1.).acos();
+            for theta5 in [acos5, -acos5] {
+                let w = target.rotation.inverse() * z1;
+                let sin5 = s4 * theta5.sin();
+                let theta6 = if sin5.abs() < 1e-9 {
+                    0.
+                } else {
+                    (-w.y / sin5).atan2(w.x / sin5)
+                };
+
+                let t01 = dh_transform(&dh[0], theta1);
+                let t46 = dh_transform(&dh[4], theta5) * dh_transform(&dh[5], theta6);
+                let t14 = t01.inverse() * target * t46.inverse();
+                let (px, py) = (t14.translation.x, t14.translation.y);
+                let cos3 = (px * px + py * py - a2 * a2 - a3 * a3) / (2. * a2 * a3);
+                if cos3.abs() > 1. + 1e-9 {
+                    continue;
+                }
+                let acos3 = cos3.clamp(-1., 1.).acos();
+                for theta3 in [acos3, -acos3] {
+                    let theta2 = py.atan2(px) - (a3 * theta3.sin()).atan2(a2 + a3 * theta3.cos());
+                    let x4 = t14.rotation * Vector3::x();
+                    let theta4 = x4.y.atan2(x4.x) - theta2 - theta3;
+                    let theta = [theta1, theta2, theta3, theta4, theta5, theta6];
+                    solutions.push(std::array::from_fn(|i| {
+                        wrap_degrees((theta[i] - dh[i][0]).to_degrees())
+                    }));
+                }
+            }
+        }
+        solutions
+    }
+
+    /// DH 参数是否符合 [`inverse`](Self::inverse) 要求的构型
+    ///
+    /// 一轴与二轴、四轴与五轴、五轴与六轴正交，二、三、四轴平行，且一、四、五轴的连杆长度为零。
+    pub fn has_analytic_inverse(&self) -> bool {
+        let dh = &self.dh;
+        let orthogonal = |i: usize| (dh[i][3].sin().abs() - 1.).abs() < 1e-9;
+        let parallel = |i: usize| dh[i][3].sin().abs() < 1e-9;
+        orthogonal(0)
+            && parallel(1)
+            && parallel(2)
+            && orthogonal(3)
+            && orthogonal(4)
+            && [0, 3, 4].iter().all(|&i| dh[i][2].abs() < 1e-9)
+    }
+
+    /// 在限位内选择与 `seed` 距离最近的逆解，每个关节可以加减 360° 以靠近种子
+    pub fn inverse_nearest(&self, flange: &[f64; 6], seed: &[f64; 6]) -> RobotResult<[f64; 6]> {
+        self.inverse(flange)
+            .iter()
+            .filter_map(|solution| self.nearest_equivalent(solution, seed))
+            .min_by(|a, b| distance(a, seed).total_cmp(&distance(b, seed)))
+            .ok_or_else(|| {
+                RobotException::UnprocessableInstructionError(format!(
+                    "pose {flange:?} is unreachable within the joint limits"
+                ))
+            })
+    }
+
+    fn nearest_equivalent(&self, solution: &[f64; 6], seed: &[f64; 6]) -> Option<[f64; 6]> {
+        let mut joint = [0.; 6];
+        for i in 0..6 {
+            joint[i] = [-360., 0., 360.]
+                .map(|turn| solution[i] + turn)
+                .into_iter()
+                .filter(|q| (self.joint_min[i]..=self.joint_max[i]).contains(q))
+                .min_by(|a, b| (a - seed[i]).abs().total_cmp(&(b - seed[i]).abs()))?;
+        }
+        Some(joint)
+    }
+
+    /// 检查关节角是否位于奇异位形附近
+    pub fn singularity(&self, joint: &[f64; 6]) -> Option<Singularity> {
+        let theta: [f64; 6] = std::array::from_fn(|i| self.dh[i][0] + joint[i].to_radians());
+        if theta[4].sin().abs() < SINGULAR_SINE {
+            return Some(Singularity::Wrist);
+        }
+        if theta[2].sin().abs() < SINGULAR_SINE {
+            return Some(Singularity::Elbow);
+        }
+        let d4 = (self.dh[1][1] + self.dh[2][1] + self.dh[3][1]) * 1000.;
+        let wrist = self.frames(joint)[5].translation.vector;
+        let r = wrist.x.hypot(wrist.y);
+        if (r * r - d4 * d4).max(0.).sqrt() < SHOULDER_SINGULAR_DISTANCE {
+            return Some(Singularity::Shoulder);
+        }
+        None
+    }
+}
+
+/// 单个连杆的 DH 变换，`theta` 已包含偏置，长度单位由 m 转换为 mm
+fn dh_transform([_, d, a, alpha]: &[f64; 4], theta: f64) -> Isometry3<f64> {
+    Isometry3::from_parts(
+        Translation3::new(0., 0., d * 1000.),
+        UnitQuaternion::from_axis_angle(&Vector3::z_axis(), theta),
+    ) * Isometry3::from_parts(
+        Translation3::new(a * 1000., 0., 0.),
+        UnitQuaternion::from_axis_angle(&Vector3::x_axis(), *alpha),
+    )
+}
+
+fn wrap_degrees(angle: f64) -> f64 {
+    let wrapped = angle.to_radians().rem_euclid(TAU);
+    if wrapped > PI {
+        (wrapped - TAU).to_degrees()
+    } else {
+        wrapped.to_degrees()
+    }
+}
+
+fn distance(a: &[f64; 6], b: &[f64; 6]) -> f64 {
+    a.iter().zip(b).map(|(a, b)| (a - b).powi(2)).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hans::{HANS_ROBOT_DH, HANS_ROBOT_MAX_JOINTS, HANS_ROBOT_MIN_JOINTS};
+
+    #[test]
+    fn test_kinematics_round_trip() {
+        let kinematics =
+            Kinematics::new(HANS_ROBOT_DH, HANS_ROBOT_MIN_JOINTS, HANS_ROBOT_MAX_JOINTS);
+        let joint = [30., -60., 90., -30., 60., 45.];
+        let flange = kinematics.forward(&joint);
+        let target = pose_to_iso(&flange);
+
+        let solutions = kinematics.inverse(&flange);
+        assert_eq!(solutions.len(), 8);
+        for solution in &solutions {
+            let pose = kinematics.forward_iso(solution);
+            assert!((pose.translation.vector - target.translation.vector).norm() < 1e-6);
+            assert!(pose.rotation.angle_to(&target.rotation) < 1e-9);
+        }
+        let seed = joint.map(|q| q + 5.);
+        let nearest = kinematics.inverse_nearest(&flange, &seed).unwrap();
+        assert!(distance(&nearest, &joint) < 1e-12);
+
+        // 雅可比与数值差分一致
+        let jacobian = kinematics.jacobian(&joint);
+        let mut moved = joint;
+        moved[1] += 1e-6_f64.to_degrees();
+        let delta = kinematics.forward_iso(&moved).translation.vector - target.translation.vector;
+        assert!((delta / 1e-6 - jacobian.fixed_view::<3, 1>(0, 1)).norm() < 1e-2);
+
+        assert_eq!(kinematics.singularity(&joint), None);
+        let wrist = [30., -60., 90., -30., 0., 45.];
+        assert_eq!(kinematics.singularity(&wrist), Some(Singularity::Wrist));
+        assert!(kinematics.manipulability(&wrist) < 1e-6 * kinematics.manipulability(&joint));
+
+        // 零位时法兰位置可由 DH 表直接写出：x = a2 + a3，y = d6 - (d2 + d3 + d4)，z = d1 - d5
+        let home = kinematics.forward(&[0.; 6]);
+        for (actual, expected) in home.iter().zip([-1641.5, -494.5, 27.2]) {
+            assert!((actual - expected).abs() < 1e-9, "{home:?}");
+        }
+
+        let mut offset = HANS_ROBOT_DH;
+        offset[4][2] = 0.1;
+        let offset = Kinematics::new(offset, HANS_ROBOT_MIN_JOINTS, HANS_ROBOT_MAX_JOINTS);
+        assert!(!offset.has_analytic_inverse());
+        assert!(offset.inverse(&offset.forward(&joint)).is_empty());
+    }
+}
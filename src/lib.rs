@@ -1,6 +1,14 @@
 #![feature(adt_const_params)]
 
+mod calibration;
+mod datasheet;
+mod diagnostics;
+mod frame;
 mod hans;
+mod kinematics;
+mod lifecycle;
+mod modbus;
+mod motion;
 mod network;
 mod robot;
 mod robot_error;
@@ -8,19 +16,59 @@ mod robot_impl;
 mod robot_mode;
 mod robot_param;
 mod robot_state;
+mod servo;
+#[cfg(any(test, feature = "simulator"))]
+mod simulator;
+mod state_cache;
+mod state_stream;
 mod types;
+mod validation;
+mod wait;
+
+#[cfg(feature = "async")]
+mod network_async;
+#[cfg(feature = "async")]
+mod robot_impl_async;
 
 #[cfg(feature = "ffi")]
 mod ffi;
 
+pub use calibration::{
+    TCP_CALIBRATION_MIN_POINTS, TcpCalibration, TcpCalibrationResult, UserFrameTeaching,
+};
+pub use datasheet::*;
+pub use diagnostics::*;
+pub use frame::{Frame, FrameRegistry};
 pub use hans::*;
+pub use kinematics::{DhParameters, Kinematics, Singularity};
+pub use lifecycle::*;
+pub use modbus::*;
+pub use motion::{
+    ArcOrientation, DEFAULT_BLEND_RADIUS, DEFAULT_TCP_NAME, DEFAULT_UCS_NAME, MotionCommand,
+    MoveCircular, MoveMode,
+};
 pub use network::*;
 pub use robot::HansRobot;
-pub use robot_error::RobotError;
+pub use robot_error::{ErrorCategory, ErrorSeverity, RobotError};
 pub use robot_impl::{CommandSubmit, DispatchFn};
-pub use robot_mode::RobotMode;
+pub use robot_mode::{ModeViolation, RobotMode};
 pub use robot_param::*;
+pub use robot_state::*;
+pub use servo::{
+    DEFAULT_SERVO_PERIOD, ServoConfig, ServoSession, ServoStats, ServoTarget, ServoTick,
+};
+#[cfg(any(test, feature = "simulator"))]
+pub use simulator::Simulator;
+pub use state_cache::{CachedState, StateSource};
+pub use state_stream::*;
 pub use types::CommandSerde;
+pub use validation::{ValidationError, WORKSPACE_SAMPLE_STEP, Workspace, WorkspaceBox};
+pub use wait::{DEFAULT_WAIT_TIMEOUT, WaitOutcome, WaitPolicy, WaitResult};
+
+#[cfg(feature = "async")]
+pub use network_async::AsyncNetwork;
+#[cfg(feature = "async")]
+pub use robot_impl_async::AsyncRobotImpl;
 
 #[cfg(feature = "to_py")]
 #[pyo3::pymodule]
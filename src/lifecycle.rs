@@ -0,0 +1,255 @@
+use std::fmt::Display;
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use robot_behavior::{RobotException, RobotResult};
+
+use crate::{RobotMode, robot_impl::RobotImpl};
+
+/// 上电与关机流程中的阶段
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LifecycleStage {
+    /// `ConnectToBox`，连接电箱
+    ConnectToBox,
+    /// `Electrify`，机械臂 48V 上电
+    Electrify,
+    /// `StartMaster`，启动主站
+    StartMaster,
+    /// `GrpEnable`，轴组使能
+    Enable,
+    /// `GrpDisable`，轴组去使能
+    Disable,
+    /// `CloseMaster`，关闭主站
+    CloseMaster,
+    /// `BlackOut`，机械臂 48V 断电
+    BlackOut,
+}
+
+impl LifecycleStage {
+    /// 上电流程，按顺序执行
+    pub const POWER_UP: [LifecycleStage; 4] = [
+        LifecycleStage::ConnectToBox,
+        LifecycleStage::Electrify,
+        LifecycleStage::StartMaster,
+        LifecycleStage::Enable,
+    ];
+
+    /// 关机流程，按顺序执行
+    pub const SHUTDOWN: [LifecycleStage; 3] = [
+        LifecycleStage::Disable,
+        LifecycleStage::CloseMaster,
+        LifecycleStage::BlackOut,
+    ];
+
+    /// 该阶段完成后机器人所处的层级
+    fn target_level(&self) -> u8 {
+        match self {
+            LifecycleStage::ConnectToBox | LifecycleStage::BlackOut => 1,
+            LifecycleStage::Electrify | LifecycleStage::CloseMaster => 2,
+            LifecycleStage::StartMaster | LifecycleStage::Disable => 3,
+            LifecycleStage::Enable => 4,
+        }
+    }
+
+    fn is_power_up(&self) -> bool {
+        matches!(
+            self,
+            LifecycleStage::ConnectToBox
+                | LifecycleStage::Electrify
+                | LifecycleStage::StartMaster
+                | LifecycleStage::Enable
+        )
+    }
+
+    fn execute<const N: usize>(&self, robot_impl: &mut RobotImpl<N>) -> RobotResult<()> {
+        match self {
+            LifecycleStage::ConnectToBox => robot_impl.connect_to_box(()),
+            LifecycleStage::Electrify => robot_impl.robot_power_on(()),
+            LifecycleStage::StartMaster => robot_impl.connect_to_controller(()),
+            LifecycleStage::Enable => robot_impl.robot_enable(0),
+            LifecycleStage::Disable => robot_impl.robot_disable(0),
+            LifecycleStage::CloseMaster => robot_impl.disconnect_from_controller(()),
+            LifecycleStage::BlackOut => robot_impl.robot_power_off(()),
+        }
+    }
+}
+
+/// 上电与关机流程的超时设置
+#[derive(Debug, Clone)]
+pub struct LifecycleConfig {
+    /// 连接电箱的超时
+    pub connect_box_timeout: Duration,
+    /// 上电的超时
+    pub electrify_timeout: Duration,
+    /// 启动主站的超时
+    pub start_master_timeout: Duration,
+    /// 使能的超时
+    pub enable_timeout: Duration,
+    /// 去使能、关闭主站与断电的超时
+    pub shutdown_timeout: Duration,
+    /// 轮询状态机的间隔
+    pub poll_interval: Duration,
+}
+
+impl Default for LifecycleConfig {
+    fn default() -> Self {
+        LifecycleConfig {
+            connect_box_timeout: Duration::from_secs(10),
+            electrify_timeout: Duration::from_secs(30),
+            start_master_timeout: Duration::from_secs(30),
+            enable_timeout: Duration::from_secs(10),
+            shutdown_timeout: Duration::from_secs(10),
+            poll_interval: Duration::from_millis(50),
+        }
+    }
+}
+
+impl LifecycleConfig {
+    fn timeout(&self, stage: LifecycleStage) -> Duration {
+        match stage {
+            LifecycleStage::ConnectToBox => self.connect_box_timeout,
+            LifecycleStage::Electrify => self.electrify_timeout,
+            LifecycleStage::StartMaster => self.start_master_timeout,
+            LifecycleStage::Enable => self.enable_timeout,
+            LifecycleStage::Disable | LifecycleStage::CloseMaster | LifecycleStage::BlackOut => {
+                self.shutdown_timeout
+            }
+        }
+    }
+}
+
+/// 某个阶段失败的原因
+#[derive(Debug)]
+pub enum LifecycleFailure {
+    /// 控制器拒绝了该阶段的指令
+    Rejected(RobotException),
+    /// 机器人进入了故障状态
+    Fault,
+    /// 状态机在超时时间内没有完成该阶段
+    Timeout,
+}
+
+/// 上电或关机流程失败，记录失败的阶段与当时的状态机
+#[derive(Debug)]
+pub struct LifecycleError {
+    pub stage: LifecycleStage,
+    pub mode: RobotMode,
+    pub failure: LifecycleFailure,
+}
+
+impl Display for LifecycleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.failure {
+            LifecycleFailure::Rejected(e) => {
+                write!(f, "{:?} rejected in {:?}: {e}", self.stage, self.mode)
+            }
+            LifecycleFailure::Fault => {
+                write!(f, "{:?} stopped by fault state {:?}", self.stage, self.mode)
+            }
+            LifecycleFailure::Timeout => {
+                write!(f, "{:?} timed out in {:?}", self.stage, self.mode)
+            }
+        }
+    }
+}
+
+impl From<LifecycleError> for RobotException {
+    fn from(e: LifecycleError) -> Self {
+        RobotException::UnprocessableInstructionError(e.to_string())
+    }
+}
+
+/// 上电与关机流程
+///
+/// 上电依次执行 `ConnectToBox`、`Electrify`、`StartMaster`、`GrpEnable`，
+/// 关机依次执行 `GrpDisable`、`CloseMaster`、`BlackOut`。每个阶段发送指令后轮询状态机，
+/// 直到机器人到达该阶段的目标状态；已经满足的阶段会被跳过，因此可以在任意状态下重复调用。
+/// 上电在故障状态下会停止，关机则在急停、碰撞等故障状态下照常执行。
+#[derive(Debug, Clone, Default)]
+pub struct Lifecycle {
+    pub config: LifecycleConfig,
+}
+
+impl Lifecycle {
+    pub fn new(config: LifecycleConfig) -> Self {
+        Lifecycle { config }
+    }
+
+    /// 执行完整的上电流程，到达 [`RobotMode::StandBy`]
+    pub fn power_up<const N: usize>(
+        &self,
+        robot_impl: &mut RobotImpl<N>,
+    ) -> Result<(), LifecycleError> {
+        self.run(robot_impl, &LifecycleStage::POWER_UP)
+    }
+
+    /// 执行完整的关机流程，到达 [`RobotMode::Blackout48V`]
+    pub fn shutdown<const N: usize>(
+        &self,
+        robot_impl: &mut RobotImpl<N>,
+    ) -> Result<(), LifecycleError> {
+        self.run(robot_impl, &LifecycleStage::SHUTDOWN)
+    }
+
+    /// 按顺序执行给定的阶段
+    pub fn run<const N: usize>(
+        &self,
+        robot_impl: &mut RobotImpl<N>,
+        stages: &[LifecycleStage],
+    ) -> Result<(), LifecycleError> {
+        stages
+            .iter()
+            .try_for_each(|stage| self.run_stage(robot_impl, *stage))
+    }
+
+    fn run_stage<const N: usize>(
+        &self,
+        robot_impl: &mut RobotImpl<N>,
+        stage: LifecycleStage,
+    ) -> Result<(), LifecycleError> {
+        let error = |mode, failure| LifecycleError { stage, mode, failure };
+        let read_mode = |robot_impl: &mut RobotImpl<N>, mode| {
+            robot_impl
+                .state_read_cur_fsm(0)
+                .map_err(|e| error(mode, LifecycleFailure::Rejected(e)))
+        };
+        let powering_up = stage.is_power_up();
+        let reached = |mode: RobotMode| {
+            mode.power_level(powering_up).map(|level| {
+                if powering_up {
+                    level >= stage.target_level()
+                } else {
+                    level <= stage.target_level()
+                }
+            })
+        };
+
+        let mut mode = read_mode(robot_impl, RobotMode::default())?;
+        match reached(mode) {
+            Some(true) => return Ok(()),
+            Some(false) => {}
+            None if powering_up => return Err(error(mode, LifecycleFailure::Fault)),
+            // 故障状态下仍然允许去使能与断电
+            None => {}
+        }
+        stage
+            .execute(robot_impl)
+            .map_err(|e| error(mode, LifecycleFailure::Rejected(e)))?;
+
+        let deadline = Instant::now() + self.config.timeout(stage);
+        loop {
+            mode = read_mode(robot_impl, mode)?;
+            match reached(mode) {
+                Some(true) => return Ok(()),
+                Some(false) => {}
+                None if powering_up => return Err(error(mode, LifecycleFailure::Fault)),
+                // 故障状态保持到复位为止，状态机不反映关机进度，指令被接受即视为完成
+                None => return Ok(()),
+            }
+            if Instant::now() >= deadline {
+                return Err(error(mode, LifecycleFailure::Timeout));
+            }
+            sleep(self.config.poll_interval);
+        }
+    }
+}
@@ -0,0 +1,534 @@
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+
+use robot_behavior::{RobotException, RobotResult};
+
+use crate::network::PORT_MODBUSTCP;
+use crate::robot_mode::RobotMode;
+
+/// Modbus 请求的读写超时
+const MODBUS_TIMEOUT: Duration = Duration::from_secs(3);
+/// MBAP 报文头长度
+const MBAP_LEN: usize = 7;
+
+const FC_READ_COILS: u8 = 0x01;
+const FC_READ_DISCRETE_INPUTS: u8 = 0x02;
+const FC_READ_HOLDING_REGISTERS: u8 = 0x03;
+const FC_READ_INPUT_REGISTERS: u8 = 0x04;
+const FC_WRITE_SINGLE_COIL: u8 = 0x05;
+const FC_WRITE_SINGLE_REGISTER: u8 = 0x06;
+const FC_WRITE_MULTIPLE_COILS: u8 = 0x0F;
+const FC_WRITE_MULTIPLE_REGISTERS: u8 = 0x10;
+
+/// 单次读线圈或离散输入的最大数量，见 Modbus 应用协议规范 6.1、6.2 节
+const MAX_READ_BITS: u16 = 2000;
+/// 单次读寄存器的最大数量，见 Modbus 应用协议规范 6.3、6.4 节
+const MAX_READ_REGISTERS: u16 = 125;
+/// 单次写多个线圈的最大数量，见 Modbus 应用协议规范 6.11 节
+const MAX_WRITE_BITS: u16 = 1968;
+/// 单次写多个寄存器的最大数量，见 Modbus 应用协议规范 6.12 节
+const MAX_WRITE_REGISTERS: u16 = 123;
+
+/// 控制器的 Modbus 寄存器地址表
+///
+/// 各信号的地址取决于控制器上的 Modbus 从站配置，这里不提供默认值，需要按现场配置填写
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegisterMap {
+    /// 电箱控制输出 CO0 ~ CO7，线圈
+    pub box_control_output: u16,
+    /// 电箱数字输出 DO0 ~ DO7，线圈
+    pub box_digital_output: u16,
+    /// 末端数字输出 DO0 ~ DO3，线圈
+    pub end_digital_output: u16,
+    /// 电箱控制输入 CI0 ~ CI7，离散输入
+    pub box_control_input: u16,
+    /// 电箱数字输入 DI0 ~ DI7，离散输入
+    pub box_digital_input: u16,
+    /// 末端数字输入 DI0 ~ DI3，离散输入
+    pub end_digital_input: u16,
+    /// 机器人状态寄存器的布局，输入寄存器
+    pub robot_flags: RobotFlagsLayout,
+}
+
+/// 机器人状态标志在输入寄存器中的布局
+///
+/// 状态机、使能、运动中、错误标志与错误代码各占一个寄存器，地址同样取决于控制器的
+/// Modbus 从站配置。各项以相对 `start` 的偏移给出，读取时一次读出覆盖所有偏移的连续寄存器块，
+/// 因此最大偏移不能超过 124。
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RobotFlagsLayout {
+    /// 寄存器块的起始地址
+    pub start: u16,
+    /// 状态机，取值与 [`RobotMode`] 一致
+    pub mode: u16,
+    /// 使能，非零为已使能
+    pub enable: u16,
+    /// 运动中，非零为正在运动
+    pub moving: u16,
+    /// 错误标志，非零为存在错误
+    pub error: u16,
+    /// 错误代码
+    pub error_code: u16,
+}
+
+impl RobotFlagsLayout {
+    /// 从 `start` 开始依次为状态机、使能、运动中、错误标志、错误代码的布局
+    pub fn contiguous(start: u16) -> Self {
+        RobotFlagsLayout {
+            start,
+            mode: 0,
+            enable: 1,
+            moving: 2,
+            error: 3,
+            error_code: 4,
+        }
+    }
+
+    /// 需要读取的寄存器数量
+    fn len(&self) -> u16 {
+        [
+            self.mode,
+            self.enable,
+            self.moving,
+            self.error,
+            self.error_code,
+        ]
+        .into_iter()
+        .max()
+        .unwrap()
+            + 1
+    }
+}
+
+/// 通过 Modbus 读取的机器人状态标志
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+pub struct ModbusRobotFlags {
+    pub mode: RobotMode,
+    pub is_enable: bool,
+    pub is_moving: bool,
+    pub is_error: bool,
+    pub error_code: u16,
+}
+
+/// 接入控制器急停回路的 Modbus 输出
+///
+/// 设置后 [`emergency_stop`](robot_behavior::behavior::Robot::emergency_stop) 优先写该线圈，
+/// 由控制器的硬件急停回路停止运动并断电，不经过文本指令端口
+pub struct EmergencyStopOutput {
+    pub client: ModbusClient,
+    /// 线圈地址
+    pub coil: u16,
+    /// 触发急停时写入的值，常闭回路为 `false`
+    pub trigger: bool,
+}
+
+impl EmergencyStopOutput {
+    /// 写线圈触发急停
+    pub fn trigger(&mut self) -> RobotResult<()> {
+        self.client.write_single_coil(self.coil, self.trigger)
+    }
+}
+
+/// Modbus TCP 客户端
+///
+/// 连接到控制器的 [`PORT_MODBUSTCP`] 端口，提供标准功能码的读写以及电箱、末端 IO 与
+/// 机器人状态的类型化访问，不依赖文本指令协议
+///
+/// 请求超时或读写出错后连接会被丢弃，下一次请求时重新连接，避免残留的半帧数据打乱后续响应
+pub struct ModbusClient {
+    addr: String,
+    stream: Option<TcpStream>,
+    unit_id: u8,
+    transaction_id: u16,
+    map: RegisterMap,
+}
+
+impl ModbusClient {
+    /// 使用默认端口 [`PORT_MODBUSTCP`] 与从站地址 1 连接控制器
+    pub fn connect(host: &str, map: RegisterMap) -> RobotResult<Self> {
+        Self::connect_with(host, PORT_MODBUSTCP, 1, map)
+    }
+
+    /// 使用指定端口与从站地址连接控制器
+    pub fn connect_with(host: &str, port: u16, unit_id: u8, map: RegisterMap) -> RobotResult<Self> {
+        let addr = format!("{host}:{port}");
+        let stream = open_stream(&addr)?;
+        Ok(ModbusClient { addr, stream: Some(stream), unit_id, transaction_id: 0, map })
+    }
+
+    /// 替换寄存器地址表
+    pub fn with_register_map(mut self, map: RegisterMap) -> Self {
+        self.map = map;
+        self
+    }
+
+    /// 当前使用的寄存器地址表
+    pub fn register_map(&self) -> &RegisterMap {
+        &self.map
+    }
+
+    // ! 标准功能码
+
+    /// 读线圈，功能码 0x01
+    pub fn read_coils(&mut self, addr: u16, count: u16) -> RobotResult<Vec<bool>> {
+        check_quantity(count, MAX_READ_BITS)?;
+        let data = self.request(FC_READ_COILS, &read_pdu(addr, count))?;
+        unpack_bits(&data, count)
+    }
+
+    /// 读离散输入，功能码 0x02
+    pub fn read_discrete_inputs(&mut self, addr: u16, count: u16) -> RobotResult<Vec<bool>> {
+        check_quantity(count, MAX_READ_BITS)?;
+        let data = self.request(FC_READ_DISCRETE_INPUTS, &read_pdu(addr, count))?;
+        unpack_bits(&data, count)
+    }
+
+    /// 读保持寄存器，功能码 0x03
+    pub fn read_holding_registers(&mut self, addr: u16, count: u16) -> RobotResult<Vec<u16>> {
+        check_quantity(count, MAX_READ_REGISTERS)?;
+        let data = self.request(FC_READ_HOLDING_REGISTERS, &read_pdu(addr, count))?;
+        unpack_registers(&data, count)
+    }
+
+    /// 读输入寄存器，功能码 0x04
+    pub fn read_input_registers(&mut self, addr: u16, count: u16) -> RobotResult<Vec<u16>> {
+        check_quantity(count, MAX_READ_REGISTERS)?;
+        let data = self.request(FC_READ_INPUT_REGISTERS, &read_pdu(addr, count))?;
+        unpack_registers(&data, count)
+    }
+
+    /// 写单个线圈，功能码 0x05
+    pub fn write_single_coil(&mut self, addr: u16, value: bool) -> RobotResult<()> {
+        let value: u16 = if value { 0xFF00 } else { 0x0000 };
+        self.request(FC_WRITE_SINGLE_COIL, &read_pdu(addr, value))?;
+        Ok(())
+    }
+
+    /// 写单个保持寄存器，功能码 0x06
+    pub fn write_single_register(&mut self, addr: u16, value: u16) -> RobotResult<()> {
+        self.request(FC_WRITE_SINGLE_REGISTER, &read_pdu(addr, value))?;
+        Ok(())
+    }
+
+    /// 写多个线圈，功能码 0x0F
+    pub fn write_multiple_coils(&mut self, addr: u16, values: &[bool]) -> RobotResult<()> {
+        let count = check_quantity(values.len(), MAX_WRITE_BITS)?;
+        let mut bytes = vec![0_u8; values.len().div_ceil(8)];
+        for (i, value) in values.iter().enumerate() {
+            if *value {
+                bytes[i / 8] |= 1 << (i % 8);
+            }
+        }
+        let mut pdu = read_pdu(addr, count);
+        pdu.push(count.div_ceil(8) as u8);
+        pdu.extend_from_slice(&bytes);
+        self.request(FC_WRITE_MULTIPLE_COILS, &pdu)?;
+        Ok(())
+    }
+
+    /// 写多个保持寄存器，功能码 0x10
+    pub fn write_multiple_registers(&mut self, addr: u16, values: &[u16]) -> RobotResult<()> {
+        let count = check_quantity(values.len(), MAX_WRITE_REGISTERS)?;
+        let mut pdu = read_pdu(addr, count);
+        pdu.push((count * 2) as u8);
+        for value in values {
+            pdu.extend_from_slice(&value.to_be_bytes());
+        }
+        self.request(FC_WRITE_MULTIPLE_REGISTERS, &pdu)?;
+        Ok(())
+    }
+
+    // ! 电箱与末端 IO
+
+    /// 读取电箱控制输入 CI0 ~ CI7
+    pub fn read_box_control_inputs(&mut self) -> RobotResult<[bool; 8]> {
+        let bits = self.read_discrete_inputs(self.map.box_control_input, 8)?;
+        Ok(bits.try_into().unwrap())
+    }
+
+    /// 读取电箱数字输入 DI0 ~ DI7
+    pub fn read_box_digital_inputs(&mut self) -> RobotResult<[bool; 8]> {
+        let bits = self.read_discrete_inputs(self.map.box_digital_input, 8)?;
+        Ok(bits.try_into().unwrap())
+    }
+
+    /// 读取电箱控制输出 CO0 ~ CO7
+    pub fn read_box_control_outputs(&mut self) -> RobotResult<[bool; 8]> {
+        let bits = self.read_coils(self.map.box_control_output, 8)?;
+        Ok(bits.try_into().unwrap())
+    }
+
+    /// 读取电箱数字输出 DO0 ~ DO7
+    pub fn read_box_digital_outputs(&mut self) -> RobotResult<[bool; 8]> {
+        let bits = self.read_coils(self.map.box_digital_output, 8)?;
+        Ok(bits.try_into().unwrap())
+    }
+
+    /// 设置电箱控制输出
+    pub fn set_box_control_output(&mut self, id: u8, value: bool) -> RobotResult<()> {
+        check_io_id(id, 8)?;
+        self.write_single_coil(self.map.box_control_output + id as u16, value)
+    }
+
+    /// 设置电箱数字输出
+    pub fn set_box_digital_output(&mut self, id: u8, value: bool) -> RobotResult<()> {
+        check_io_id(id, 8)?;
+        self.write_single_coil(self.map.box_digital_output + id as u16, value)
+    }
+
+    /// 读取末端数字输入 DI0 ~ DI3
+    pub fn read_end_digital_inputs(&mut self) -> RobotResult<[bool; 4]> {
+        let bits = self.read_discrete_inputs(self.map.end_digital_input, 4)?;
+        Ok(bits.try_into().unwrap())
+    }
+
+    /// 读取末端数字输出 DO0 ~ DO3
+    pub fn read_end_digital_outputs(&mut self) -> RobotResult<[bool; 4]> {
+        let bits = self.read_coils(self.map.end_digital_output, 4)?;
+        Ok(bits.try_into().unwrap())
+    }
+
+    /// 设置末端数字输出
+    pub fn set_end_digital_output(&mut self, id: u8, value: bool) -> RobotResult<()> {
+        check_io_id(id, 4)?;
+        self.write_single_coil(self.map.end_digital_output + id as u16, value)
+    }
+
+    // ! 机器人状态
+
+    /// 读取机器人状态标志
+    pub fn read_robot_flags(&mut self) -> RobotResult<ModbusRobotFlags> {
+        let layout = self.map.robot_flags;
+        let regs = self.read_input_registers(layout.start, layout.len())?;
+        let reg = |offset: u16| regs[offset as usize];
+        let mode = u8::try_from(reg(layout.mode)).map_err(|_| {
+            RobotException::DeserializeError(format!("invalid robot mode {}", reg(layout.mode)))
+        })?;
+        Ok(ModbusRobotFlags {
+            mode: RobotMode::from(mode),
+            is_enable: reg(layout.enable) != 0,
+            is_moving: reg(layout.moving) != 0,
+            is_error: reg(layout.error) != 0,
+            error_code: reg(layout.error_code),
+        })
+    }
+
+    /// 发送请求并返回响应中功能码之后的数据，读写失败时丢弃连接
+    fn request(&mut self, function: u8, pdu: &[u8]) -> RobotResult<Vec<u8>> {
+        let stream = match self.stream.take() {
+            Some(stream) => stream,
+            None => open_stream(&self.addr)?,
+        };
+        let result = self.exchange(&stream, function, pdu);
+        if !matches!(result, Err(RobotException::NetworkError(_))) {
+            self.stream = Some(stream);
+        }
+        result
+    }
+
+    fn exchange(
+        &mut self,
+        mut stream: &TcpStream,
+        function: u8,
+        pdu: &[u8],
+    ) -> RobotResult<Vec<u8>> {
+        self.transaction_id = self.transaction_id.wrapping_add(1);
+        let transaction_id = self.transaction_id;
+
+        let mut frame = Vec::with_capacity(MBAP_LEN + 1 + pdu.len());
+        frame.extend_from_slice(&transaction_id.to_be_bytes());
+        frame.extend_from_slice(&0_u16.to_be_bytes());
+        frame.extend_from_slice(&((pdu.len() + 2) as u16).to_be_bytes());
+        frame.push(self.unit_id);
+        frame.push(function);
+        frame.extend_from_slice(pdu);
+        stream.write_all(&frame)?;
+
+        loop {
+            let mut header = [0_u8; MBAP_LEN];
+            stream.read_exact(&mut header)?;
+            let len = u16::from_be_bytes([header[4], header[5]]) as usize;
+            if len < 2 {
+                return Err(RobotException::NetworkError(format!(
+                    "invalid modbus frame length {len}"
+                )));
+            }
+            let mut body = vec![0_u8; len - 1];
+            stream.read_exact(&mut body)?;
+
+            // 丢弃之前超时请求遗留的响应
+            if u16::from_be_bytes([header[0], header[1]]) != transaction_id {
+                continue;
+            }
+
+            return match body[0] {
+                code if code == function => Ok(body.split_off(1)),
+                code if code == function | 0x80 => Err(RobotException::CommandException(format!(
+                    "modbus exception on function 0x{function:02X}: {}",
+                    exception_message(body.get(1).copied().unwrap_or_default())
+                ))),
+                code => Err(RobotException::DeserializeError(format!(
+                    "unexpected modbus function 0x{code:02X}, expect 0x{function:02X}"
+                ))),
+            };
+        }
+    }
+}
+
+fn open_stream(addr: &str) -> RobotResult<TcpStream> {
+    let stream = TcpStream::connect(addr)?;
+    stream.set_read_timeout(Some(MODBUS_TIMEOUT))?;
+    stream.set_write_timeout(Some(MODBUS_TIMEOUT))?;
+    stream.set_nodelay(true)?;
+    Ok(stream)
+}
+
+/// 检查单次请求的数量是否在协议允许的范围内
+fn check_quantity(
+    count: impl TryInto<u16> + Copy + std::fmt::Display,
+    max: u16,
+) -> RobotResult<u16> {
+    match count.try_into() {
+        Ok(quantity) if (1..=max).contains(&quantity) => Ok(quantity),
+        _ => Err(RobotException::InvalidInstruction(format!(
+            "modbus quantity {count} out of range 1..={max}"
+        ))),
+    }
+}
+
+/// 起始地址加数量（或数值）的通用 PDU
+fn read_pdu(addr: u16, value: u16) -> Vec<u8> {
+    let mut pdu = addr.to_be_bytes().to_vec();
+    pdu.extend_from_slice(&value.to_be_bytes());
+    pdu
+}
+
+fn unpack_bits(data: &[u8], count: u16) -> RobotResult<Vec<bool>> {
+    let count = count as usize;
+    if data.is_empty() || data.len() < 1 + count.div_ceil(8) {
+        return Err(RobotException::DeserializeError(
+            "modbus bit response too short".into(),
+        ));
+    }
+    Ok((0..count)
+        .map(|i| data[1 + i / 8] & (1 << (i % 8)) != 0)
+        .collect())
+}
+
+fn unpack_registers(data: &[u8], count: u16) -> RobotResult<Vec<u16>> {
+    let count = count as usize;
+    if data.is_empty() || data.len() < 1 + count * 2 {
+        return Err(RobotException::DeserializeError(
+            "modbus register response too short".into(),
+        ));
+    }
+    Ok(data[1..1 + count * 2]
+        .chunks_exact(2)
+        .map(|b| u16::from_be_bytes([b[0], b[1]]))
+        .collect())
+}
+
+fn check_io_id(id: u8, count: u8) -> RobotResult<()> {
+    if id < count {
+        Ok(())
+    } else {
+        Err(RobotException::InvalidInstruction(format!(
+            "io id {id} out of range 0..{count}"
+        )))
+    }
+}
+
+fn exception_message(code: u8) -> &'static str {
+    match code {
+        0x01 => "illegal function",
+        0x02 => "illegal data address",
+        0x03 => "illegal data value",
+        0x04 => "server device failure",
+        0x05 => "acknowledge",
+        0x06 => "server device busy",
+        0x0A => "gateway path unavailable",
+        0x0B => "gateway target device failed to respond",
+        _ => "unknown exception",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::TcpListener;
+    use std::thread;
+
+    use super::*;
+
+    #[test]
+    fn test_modbus_requests() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = thread::spawn(move || {
+            let (mut socket, _) = listener.accept().unwrap();
+            let reply = |socket: &mut TcpStream, pdu: &[u8]| {
+                let mut request = [0_u8; 12];
+                socket.read_exact(&mut request).unwrap();
+                let mut frame = request[..4].to_vec();
+                frame.extend_from_slice(&((pdu.len() + 1) as u16).to_be_bytes());
+                frame.push(request[6]);
+                frame.extend_from_slice(pdu);
+                socket.write_all(&frame).unwrap();
+                request
+            };
+            let request = reply(&mut socket, &[0x04, 4, 0, 33, 0, 1]);
+            assert_eq!(request[7..], [0x04, 0, 0, 0, 2]);
+            let request = reply(&mut socket, &[0x02, 1, 0b0000_0101]);
+            assert_eq!(request[7..], [0x02, 0, 16, 0, 4]);
+            let request = reply(
+                &mut socket,
+                &[0x04, 14, 0, 25, 0, 1, 0, 0, 0, 1, 0, 1, 0, 0, 0, 7],
+            );
+            assert_eq!(request[7..], [0x04, 0, 100, 0, 7]);
+            let request = reply(&mut socket, &[0x85, 0x02]);
+            assert_eq!(request[7..], [0x05, 0, 17, 0xFF, 0]);
+        });
+
+        let map = RegisterMap {
+            box_control_output: 0,
+            box_digital_output: 8,
+            end_digital_output: 16,
+            box_control_input: 0,
+            box_digital_input: 8,
+            end_digital_input: 16,
+            robot_flags: RobotFlagsLayout {
+                start: 100,
+                mode: 0,
+                enable: 2,
+                moving: 3,
+                error: 5,
+                error_code: 6,
+            },
+        };
+        let mut client = ModbusClient::connect_with("127.0.0.1", port, 1, map).unwrap();
+        assert!(client.read_input_registers(0, 126).is_err());
+        assert!(client.write_multiple_coils(0, &[false; 2000]).is_err());
+        assert_eq!(client.read_input_registers(0, 2).unwrap(), vec![33, 1]);
+        assert_eq!(
+            client.read_end_digital_inputs().unwrap(),
+            [true, false, true, false]
+        );
+        let flags = client.read_robot_flags().unwrap();
+        assert_eq!(
+            flags,
+            ModbusRobotFlags {
+                mode: RobotMode::Moving,
+                is_enable: false,
+                is_moving: true,
+                is_error: false,
+                error_code: 7,
+            }
+        );
+        assert!(matches!(
+            client.set_end_digital_output(1, true),
+            Err(RobotException::CommandException(_))
+        ));
+        assert!(client.set_end_digital_output(4, true).is_err());
+        server.join().unwrap();
+    }
+}
@@ -0,0 +1,490 @@
+use std::f64::consts::TAU;
+
+use nalgebra::Vector3;
+use robot_behavior::{MotionSpace, Robot, RobotException, RobotResult};
+
+use crate::{Frame, robot_impl::RobotImpl, types::*, validation::WORKSPACE_SAMPLE_STEP};
+
+/// 默认的过渡半径，单位 mm
+pub const DEFAULT_BLEND_RADIUS: f64 = 5.;
+/// 默认的工具坐标名称，也是控制器当前工具坐标的名称
+///
+/// 注册表中的名称只在本地使用，运动指令总是引用控制器的当前工具坐标，
+/// 其位姿在下发运动前由 `SetCurTCP` 写入。
+pub const DEFAULT_TCP_NAME: &str = "Tcp";
+/// 默认的用户坐标名称，也是控制器当前用户坐标的名称，其位姿由 `SetCurUCS` 写入
+pub const DEFAULT_UCS_NAME: &str = "Base";
+
+/// 插补方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveMode {
+    /// 关节空间插补
+    Joint,
+    /// 笛卡尔空间直线插补
+    Linear,
+}
+
+impl MoveMode {
+    fn code(&self) -> u8 {
+        match self {
+            MoveMode::Joint => 0,
+            MoveMode::Linear => 1,
+        }
+    }
+}
+
+/// 单点运动下发时使用的指令
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MotionCommand {
+    /// `WayPointEx`，关节目标按关节插补，位姿目标按直线插补
+    #[default]
+    Auto,
+    /// `MoveJ`，关节空间插补，位姿目标由控制器求逆解
+    MoveJ,
+    /// `MoveL`，直线插补，关节目标由控制器求正解
+    MoveL,
+    /// `WayPoint`，使用当前的工具与用户坐标名称
+    WayPoint(MoveMode),
+    /// `WayPoint2`，只支持关节与直线插补，圆弧运动见 `MoveC`
+    WayPoint2(MoveMode),
+    /// `WayPointEx`，工具与用户坐标以数值下发
+    WayPointEx(MoveMode),
+}
+
+impl MotionCommand {
+    /// 实际下发的指令与插补方式
+    fn resolve(&self, joint_target: bool) -> (Command, MoveMode) {
+        let auto = if joint_target {
+            MoveMode::Joint
+        } else {
+            MoveMode::Linear
+        };
+        match *self {
+            MotionCommand::Auto => (Command::WayPointEx, auto),
+            MotionCommand::MoveJ => (Command::MoveJ, MoveMode::Joint),
+            MotionCommand::MoveL => (Command::MoveL, MoveMode::Linear),
+            MotionCommand::WayPoint(mode) => (Command::WayPoint, mode),
+            MotionCommand::WayPoint2(mode) => (Command::WayPoint2, mode),
+            MotionCommand::WayPointEx(mode) => (Command::WayPointEx, mode),
+        }
+    }
+}
+
+/// 圆弧运动中末端姿态的变化方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ArcOrientation {
+    /// 姿态由起点平滑过渡到终点
+    #[default]
+    Rotating,
+    /// 保持起点的姿态不变
+    Fixed,
+}
+
+/// 圆弧与整圆运动，与 [`MoveTo`](robot_behavior::driver::MoveTo) 一样按运动空间区分
+///
+/// 三个点不能共线，否则在下发前返回错误。起点需要与机器人当前的位置一致。
+pub trait MoveCircular<S: MotionSpace<Self>>: Robot {
+    /// 从 `start` 经过 `via` 运动到 `end` 的圆弧
+    fn move_arc(&mut self, start: S::Target, via: S::Target, end: S::Target) -> RobotResult<()>;
+
+    fn move_arc_sync(
+        &mut self,
+        start: S::Target,
+        via: S::Target,
+        end: S::Target,
+    ) -> RobotResult<()> {
+        self.move_arc(start, via, end)?;
+        self.waiting_for_finish()
+    }
+
+    /// 沿 `start`、`via`、`end` 确定的圆运动 `turns` 圈后回到 `start`
+    fn move_circle(
+        &mut self,
+        start: S::Target,
+        via: S::Target,
+        end: S::Target,
+        turns: f64,
+    ) -> RobotResult<()>;
+
+    fn move_circle_sync(
+        &mut self,
+        start: S::Target,
+        via: S::Target,
+        end: S::Target,
+        turns: f64,
+    ) -> RobotResult<()> {
+        self.move_circle(start, via, end, turns)?;
+        self.waiting_for_finish()
+    }
+}
+
+/// 圆弧三点之间的最小距离，单位 mm
+const ARC_MIN_POINT_DISTANCE: f64 = 0.1;
+/// 圆弧的最大半径，单位 mm，远大于机器人的工作范围，更大的圆弧视为三点共线
+const ARC_MAX_RADIUS: f64 = 10_000.;
+
+/// 检查三点能否确定一个圆：任意两点之间不小于 [`ARC_MIN_POINT_DISTANCE`]，
+/// 外接圆半径不超过 [`ARC_MAX_RADIUS`]
+pub(crate) fn check_arc_points(start: [f64; 3], via: [f64; 3], end: [f64; 3]) -> RobotResult<()> {
+    let [a, b, c] = [start, via, end].map(Vector3::from);
+    let (ab, ac, bc) = (b - a, c - a, c - b);
+    let shortest = ab.norm().min(ac.norm()).min(bc.norm());
+    if shortest < ARC_MIN_POINT_DISTANCE {
+        return Err(RobotException::InvalidInstruction(format!(
+            "arc points must be at least {ARC_MIN_POINT_DISTANCE}mm apart"
+        )));
+    }
+    // 外接圆半径 R = |ab||bc||ac| / (2|ab × ac|)
+    let area2 = ab.cross(&ac).norm();
+    if ab.norm() * bc.norm() * ac.norm() > 2. * ARC_MAX_RADIUS * area2 {
+        return Err(RobotException::InvalidInstruction(format!(
+            "arc points are nearly collinear, the circle radius exceeds {ARC_MAX_RADIUS}mm"
+        )));
+    }
+    Ok(())
+}
+
+/// 按 [`WORKSPACE_SAMPLE_STEP`] 采样三点确定的圆弧，`full` 为真时采样整圆
+///
+/// 三点需要先通过 [`check_arc_points`] 的检查。
+pub(crate) fn arc_samples(
+    start: [f64; 3],
+    via: [f64; 3],
+    end: [f64; 3],
+    full: bool,
+) -> Vec<[f64; 3]> {
+    let [a, b, c] = [start, via, end].map(Vector3::from);
+    let (ab, ac) = (b - a, c - a);
+    let normal = ab.cross(&ac);
+    let center = a
+        + (ac.norm_squared() * normal.cross(&ab) + ab.norm_squared() * ac.cross(&normal))
+            / (2. * normal.norm_squared());
+    let radius = (a - center).norm();
+    let u = (a - center) / radius;
+    let v = normal.normalize().cross(&u);
+    // 从 `start` 沿法向的正方向转动时依次经过 `via` 与 `end`
+    let angle = |p: Vector3<f64>| {
+        (p - center)
+            .dot(&v)
+            .atan2((p - center).dot(&u))
+            .rem_euclid(TAU)
+    };
+    let sweep = if full { TAU } else { angle(c) };
+    let steps = (sweep * radius / WORKSPACE_SAMPLE_STEP).ceil().max(1.) as usize;
+    (0..=steps)
+        .map(|k| {
+            let t = sweep * k as f64 / steps as f64;
+            (center + radius * (t.cos() * u + t.sin() * v)).into()
+        })
+        .collect()
+}
+
+/// 单点运动的目标
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum MotionTarget<const N: usize> {
+    /// 关节角，单位 °
+    Joint([f64; N]),
+    /// 位姿，单位 mm 与 °
+    Pose([f64; 6]),
+}
+
+/// 一次运动使用的参数，由 `with_*` 与 `set_*` 设置的速度上限换算为控制器单位
+///
+/// 关节速度上限的单位为 °/s，关节加速度上限的单位为 rad/s²，与 [`Joints`](robot_behavior::behavior::Joints)
+/// 中的 `JOINT_VEL_BOUND` 与 `JOINT_ACC_BOUND` 一致；笛卡尔速度与加速度上限的
+/// 单位为 m/s 与 m/s²。下发时关节插补取各轴上限中的最小值并换算为 °/s 与 °/s²，
+/// 直线插补换算为 mm/s 与 mm/s²。
+#[derive(Debug, Clone)]
+pub(crate) struct MotionParams<const N: usize> {
+    pub command: MotionCommand,
+    pub radius: f64,
+    pub joint_vel: [f64; N],
+    pub joint_acc: [f64; N],
+    pub cartesian_vel: f64,
+    pub cartesian_acc: f64,
+    pub tool: Frame,
+    pub user_frame: Frame,
+}
+
+impl<const N: usize> MotionParams<N> {
+    /// 下发到控制器的速度与加速度
+    pub fn speed(&self, mode: MoveMode) -> (f64, f64) {
+        match mode {
+            MoveMode::Joint => (
+                self.joint_vel.into_iter().fold(f64::INFINITY, f64::min),
+                self.joint_acc
+                    .into_iter()
+                    .fold(f64::INFINITY, f64::min)
+                    .to_degrees(),
+            ),
+            MoveMode::Linear => (self.cartesian_vel * 1e3, self.cartesian_acc * 1e3),
+        }
+    }
+
+    /// 关节速度上限相对于 `bound` 的比例，取各轴中的最小值，用于关节轨迹的速度百分比
+    pub fn speed_ratio(&self, bound: &[f64; N]) -> f64 {
+        (0..N)
+            .map(|i| self.joint_vel[i] / bound[i])
+            .fold(1., f64::min)
+    }
+
+    /// 使用 `WayPointRel` 下发多关节相对运动，`dis` 为各关节的增量，单位 °，为零的关节保持不动
+    pub fn send_relative(&self, robot_impl: &mut RobotImpl<N>, dis: [f64; N]) -> RobotResult<()> {
+        let (vel, acc) = self.speed(MoveMode::Joint);
+        robot_impl.move_way_point_rel((
+            0,
+            WayPointRel {
+                move_mode: MoveMode::Joint.code(),
+                use_point_list: false,
+                pose: [0.; 6],
+                joint: [0.; N],
+                rel_move_mode: 0,
+                is_move: dis.map(|d| d != 0.),
+                dis,
+                tcp_name: DEFAULT_TCP_NAME.into(),
+                ucs_name: DEFAULT_UCS_NAME.into(),
+                vel,
+                acc,
+                radius: self.radius,
+                use_joint: true,
+                is_seek_di: false,
+                di_id: 0,
+                di_value: false,
+                command_id: "0".into(),
+            },
+        ))
+    }
+
+    /// 使用 `MoveRelL` 沿 x、y、z、rx、ry、rz 中的一个方向做相对直线运动，单位 mm 与 °
+    ///
+    /// `RelL.coord` 为 1 时沿工具坐标系运动，为 0 时沿当前用户坐标系运动。
+    pub fn send_relative_line(
+        &self,
+        robot_impl: &mut RobotImpl<N>,
+        axis: usize,
+        dis: f64,
+        tool_frame: bool,
+    ) -> RobotResult<()> {
+        robot_impl.move_line_rel((
+            0,
+            RelL {
+                id: axis as u8,
+                dir: dis > 0.,
+                dis: dis.abs(),
+                coord: tool_frame as u8,
+            },
+        ))
+    }
+
+    /// 使用 `MoveC` 下发圆弧运动，`turns` 不为空时为整圆运动的圈数
+    pub fn send_arc(
+        &self,
+        robot_impl: &mut RobotImpl<N>,
+        [pose_start, pose_pass, pose_end]: [[f64; 6]; 3],
+        orientation: ArcOrientation,
+        turns: Option<f64>,
+    ) -> RobotResult<()> {
+        let (vel, acc) = self.speed(MoveMode::Linear);
+        robot_impl.move_circle((
+            0,
+            MoveC {
+                pose_start,
+                pose_pass,
+                pose_end,
+                is_fixed_pose: orientation == ArcOrientation::Fixed,
+                move_mode: turns.is_some() as u8,
+                rad_len: turns.unwrap_or(0.),
+                vel,
+                acc,
+                radius: self.radius,
+                tcp_name: DEFAULT_TCP_NAME.into(),
+                ucs_name: DEFAULT_UCS_NAME.into(),
+                command_id: "0".into(),
+            },
+        ))
+    }
+
+    /// 运动到 `target` 时的插补方式
+    pub fn mode(&self, target: &MotionTarget<N>) -> MoveMode {
+        self.command
+            .resolve(matches!(target, MotionTarget::Joint(_)))
+            .1
+    }
+
+    /// 运动到 `target` 时下发的指令，用于在下发前校验状态机
+    pub fn command(&self, target: &MotionTarget<N>) -> Command {
+        self.command
+            .resolve(matches!(target, MotionTarget::Joint(_)))
+            .0
+    }
+
+    /// 以关节插补运动到轨迹的起点，与 `MotionCommand` 的设置无关，
+    /// 避免按直线插补接近起点时经过奇异位形或超出工作空间
+    pub fn send_approach(
+        &self,
+        robot_impl: &mut RobotImpl<N>,
+        target: MotionTarget<N>,
+    ) -> RobotResult<()> {
+        let command = MotionCommand::WayPointEx(MoveMode::Joint);
+        MotionParams { command, ..self.clone() }.send(robot_impl, target)
+    }
+
+    /// 下发单点运动指令
+    pub fn send(&self, robot_impl: &mut RobotImpl<N>, target: MotionTarget<N>) -> RobotResult<()> {
+        let (use_joint, joint, pose) = match target {
+            MotionTarget::Joint(joint) => (true, joint, [0.; 6]),
+            MotionTarget::Pose(pose) => (false, [0.; N], pose),
+        };
+        let (command, mode) = self.command.resolve(use_joint);
+        let (vel, acc) = self.speed(mode);
+        let radius = self.radius;
+        let command_id = "0".to_string();
+        let tcp_name = DEFAULT_TCP_NAME.to_string();
+        let ucs_name = DEFAULT_UCS_NAME.to_string();
+        match command {
+            Command::MoveJ => robot_impl.move_joint((
+                0,
+                MoveJ {
+                    pose,
+                    joint,
+                    ucs_name,
+                    tcp_name,
+                    vel,
+                    acc,
+                    radius,
+                    use_joint,
+                    is_seek_di: false,
+                    di_id: 0,
+                    di_value: false,
+                    command_id,
+                },
+            )),
+            Command::MoveL => robot_impl.move_line((
+                0,
+                MoveL {
+                    pose,
+                    joint,
+                    ucs_name,
+                    tcp_name,
+                    vel,
+                    acc,
+                    radius,
+                    use_joint,
+                    is_seek_di: false,
+                    di_id: 0,
+                    di_value: false,
+                    command_id,
+                },
+            )),
+            Command::WayPoint => robot_impl.move_way_point((
+                0,
+                WayPoint {
+                    pose,
+                    joint,
+                    tcp_name,
+                    ucs_name,
+                    vel,
+                    acc,
+                    radius,
+                    move_mode: mode.code(),
+                    use_joint,
+                    is_seek_di: false,
+                    di_id: 0,
+                    di_value: false,
+                    command_id,
+                },
+            )),
+            Command::WayPoint2 => robot_impl.move_way_point2((
+                0,
+                WayPoint2 {
+                    pose1: pose,
+                    joint,
+                    tcp_name,
+                    ucs_name,
+                    vel,
+                    acc,
+                    radius,
+                    move_mode: mode.code(),
+                    use_joint,
+                    is_seek_di: false,
+                    di_id: 0,
+                    di_value: false,
+                    pose2: pose,
+                    command_id,
+                },
+            )),
+            _ => robot_impl.move_way_point_ex((
+                0,
+                WayPointEx {
+                    pose,
+                    joint,
+                    vel,
+                    acc,
+                    radius,
+                    move_mode: mode.code(),
+                    use_joint,
+                    command_id,
+                    ucs: self.user_frame.pose,
+                    tcp: self.tool.pose,
+                    ..WayPointEx::default()
+                },
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FrameRegistry;
+
+    #[test]
+    fn test_motion_params_units() {
+        let params = MotionParams {
+            command: MotionCommand::Auto,
+            radius: 0.,
+            joint_vel: [120., 120., 90.],
+            joint_acc: [2.5, 2., 2.5],
+            cartesian_vel: 0.25,
+            cartesian_acc: 1.5,
+            tool: FrameRegistry::default().tool(DEFAULT_TCP_NAME).unwrap(),
+            user_frame: FrameRegistry::default()
+                .user_frame(DEFAULT_UCS_NAME)
+                .unwrap(),
+        };
+        let (vel, acc) = params.speed(MoveMode::Joint);
+        assert_eq!(vel, 90.);
+        assert!((acc - 2f64.to_degrees()).abs() < 1e-9);
+        assert_eq!(params.speed(MoveMode::Linear), (250., 1500.));
+
+        let joint = MotionTarget::Joint([0.; 3]);
+        let pose = MotionTarget::Pose([0.; 6]);
+        assert_eq!(params.command(&joint), Command::WayPointEx);
+        let params = MotionParams { command: MotionCommand::MoveL, ..params };
+        assert_eq!(params.command(&joint), Command::MoveL);
+        let params = MotionParams { command: MotionCommand::WayPoint2(MoveMode::Joint), ..params };
+        assert_eq!(params.command(&pose), Command::WayPoint2);
+    }
+
+    #[test]
+    fn test_check_arc_points() {
+        assert!(check_arc_points([0., 0., 0.], [30., 30., 0.], [60., 0., 0.]).is_ok());
+        assert!(check_arc_points([0., 0., 0.], [10., 10., 10.], [20., 20., 20.]).is_err());
+        assert!(check_arc_points([0., 0., 0.], [0., 0., 0.], [20., 0., 5.]).is_err());
+        assert!(check_arc_points([0., 0., 0.], [500., 0.01, 0.], [1000., 0., 0.]).is_err());
+        assert!(check_arc_points([0., 0., 0.], [500., 20., 0.], [1000., 0., 0.]).is_ok());
+
+        // 半圆的中点远离起点与终点的连线
+        let samples = arc_samples([100., 0., 0.], [0., 100., 0.], [-100., 0., 0.], false);
+        let last = samples[samples.len() - 1];
+        assert!(Vector3::from(last).metric_distance(&Vector3::new(-100., 0., 0.)) < 1e-9);
+        assert!(
+            samples
+                .iter()
+                .all(|p| (Vector3::from(*p).norm() - 100.).abs() < 1e-9)
+        );
+        assert!(samples.iter().any(|p| p[1] > 99.9));
+        let full = arc_samples([100., 0., 0.], [0., 100., 0.], [-100., 0., 0.], true);
+        assert!(full.iter().any(|p| p[1] < -99.9));
+    }
+}
@@ -1,13 +1,15 @@
 #![allow(unused_imports)]
 
 use std::default;
-use std::io::{Read, Write};
-use std::net::{Shutdown, TcpStream};
-use std::time::Duration;
+use std::io::{self, ErrorKind, Read, Write};
+use std::net::{Shutdown, TcpStream, ToSocketAddrs};
+use std::sync::Mutex;
+use std::thread::sleep;
+use std::time::{Duration, Instant};
 
 use robot_behavior::{RobotException, RobotResult};
 
-use crate::types::CommandSerde;
+use crate::types::{CommandSerde, ReadControllerStateRequest, ReadControllerStateResponse};
 
 pub const PORT_IF: u16 = 10003;
 pub const PORT_DATASHEET_JSON_1: u16 = 10004;
@@ -18,70 +20,254 @@ pub const PORT_DATASHEET_STRUCT_2: u16 = 10015;
 pub const PORT_DATASHEET_STRUCT_3: u16 = 10016;
 pub const PORT_MODBUSTCP: u16 = 10502;
 
+/// 单条应答的长度上限，超过该值说明数据流已经错位
+#[cfg(any(not(feature = "no_robot"), feature = "async"))]
+pub(crate) const MAX_RESPONSE_LEN: usize = 64 * 1024;
+
+/// 指令连接的状态
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectionState {
+    /// 未连接，或已主动断开
+    #[default]
+    Disconnected,
+    /// 已连接
+    Connected,
+    /// 正在重连，附带当前的尝试次数
+    Reconnecting(u32),
+    /// 连接意外断开，开启自动重连时下一条指令会尝试重连
+    Lost,
+}
+
+/// 指令连接的超时、心跳与重连策略
+#[derive(Debug, Clone)]
+pub struct NetworkConfig {
+    /// 建立连接的超时
+    pub connect_timeout: Duration,
+    /// 等待应答的超时
+    pub read_timeout: Duration,
+    /// 发送指令的超时
+    pub write_timeout: Duration,
+    /// 连接空闲超过该时间后，发送指令前先用 `ReadControllerState` 探测连接，`None` 表示不探测
+    pub heartbeat_interval: Option<Duration>,
+    /// 连接断开后是否自动重连
+    pub auto_reconnect: bool,
+    /// 第一次重连失败后的等待时间，此后每次翻倍
+    pub reconnect_initial_delay: Duration,
+    /// 重连等待时间的上限
+    pub reconnect_max_delay: Duration,
+    /// 每轮重连的最大尝试次数
+    pub reconnect_max_attempts: u32,
+    /// 每轮重连允许的总时长，包括建立连接与退避等待，超过后即使未达到最大次数也放弃
+    pub reconnect_timeout: Duration,
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        NetworkConfig {
+            connect_timeout: Duration::from_secs(3),
+            read_timeout: Duration::from_secs(3),
+            write_timeout: Duration::from_secs(3),
+            heartbeat_interval: Some(Duration::from_secs(5)),
+            auto_reconnect: true,
+            reconnect_initial_delay: Duration::from_millis(100),
+            reconnect_max_delay: Duration::from_secs(5),
+            reconnect_max_attempts: 5,
+            reconnect_timeout: Duration::from_secs(5),
+        }
+    }
+}
+
+/// 连接状态变化的回调
+pub type ConnectionCallback = Box<dyn FnMut(ConnectionState) + Send>;
+
+/// 指令端口连接
+///
+/// 发送指令时会监督连接的健康状态：连接空闲过久时先发送心跳探测，
+/// 读写时遇到断开的连接会标记为 [`ConnectionState::Lost`]，
+/// 并在下一条指令前按指数退避自动重连。重连不会重发已经失败的指令。
 #[derive(Default)]
 pub struct Network {
     socket: Option<TcpStream>,
-    connected: bool,
+    host: Option<String>,
+    port: u16,
+    state: ConnectionState,
+    config: NetworkConfig,
+    /// 成功建立连接的次数，用于判断控制器上的设置是否需要重新读取
+    sessions: u64,
+    /// 最近一次收到完整应答的时间
+    last_activity: Option<Instant>,
+    /// 为真时发送指令不探测、不重连，只在当前连接上尝试一次
+    pub(crate) single_attempt: bool,
+    /// 尚未组成完整应答的字节
+    buffer: Vec<u8>,
+    /// 放在 `Mutex` 中使连接可以在线程间共享
+    callbacks: Mutex<Vec<ConnectionCallback>>,
 }
 
 impl Network {
-    pub fn from_ip(host: &str, port: u16) -> Self {
-        let mut network = Network::default();
-        network.connect(host, port).unwrap();
-        network
+    /// 连接到指定 IP 与端口，连接失败时返回错误
+    pub fn from_ip(host: &str, port: u16) -> RobotResult<Self> {
+        Self::from_ip_with(host, port, NetworkConfig::default())
+    }
+
+    /// 使用指定的连接策略连接到指定 IP 与端口
+    pub fn from_ip_with(host: &str, port: u16, config: NetworkConfig) -> RobotResult<Self> {
+        let mut network = Network { config, ..Network::default() };
+        network.connect(host, port)?;
+        Ok(network)
     }
 
-    pub fn from_defult_port(host: &str) -> Self {
+    pub fn from_defult_port(host: &str) -> RobotResult<Self> {
         Network::from_ip(host, PORT_IF)
     }
 
     /// 连接到指定 IP 与端口
     pub fn connect(&mut self, host: &str, port: u16) -> RobotResult<()> {
-        #[cfg(not(feature = "no_robot"))]
-        {
-            let addr = format!("{host}:{port}");
-            let stream = TcpStream::connect(&addr)?;
+        self.close();
+        self.host = Some(host.to_string());
+        self.port = port;
+        self.open(self.config.connect_timeout)
+            .inspect_err(|_| self.set_state(ConnectionState::Lost))
+    }
 
-            stream.set_read_timeout(Some(Duration::from_secs(3)))?;
-            stream.set_write_timeout(Some(Duration::from_secs(3)))?;
-            self.socket = Some(stream);
+    /// 断开 TCP 连接，主动断开后不会自动重连
+    pub fn disconnect(&mut self) -> RobotResult<()> {
+        let result = match &self.socket {
+            Some(stream) => stream.shutdown(Shutdown::Both),
+            None => Ok(()),
+        };
+        self.socket = None;
+        self.buffer.clear();
+        self.set_state(ConnectionState::Disconnected);
+        // 对端已经关闭时 shutdown 会失败，此时连接同样已经断开
+        match result {
+            Err(e) if e.kind() != ErrorKind::NotConnected => Err(e.into()),
+            _ => Ok(()),
         }
+    }
 
-        self.connected = true;
-        Ok(())
+    /// 判断是否已连接
+    pub fn is_connected(&self) -> bool {
+        self.state == ConnectionState::Connected
     }
 
-    /// 断开 TCP 连接
-    pub fn disconnect(&mut self) -> RobotResult<()> {
+    /// 当前的连接状态
+    pub fn state(&self) -> ConnectionState {
+        self.state
+    }
+
+    /// 最近一次连接的机器人 IP
+    pub fn host(&self) -> Option<&str> {
+        self.host.as_deref()
+    }
+
+    /// 最近一次连接的指令端口
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// 当前连接的序号，每次连接或重连成功后加一
+    pub(crate) fn session(&self) -> u64 {
+        self.sessions
+    }
+
+    /// 当前的连接策略
+    pub fn config(&self) -> &NetworkConfig {
+        &self.config
+    }
+
+    /// 修改连接策略，超时设置立即作用于当前连接
+    pub fn set_config(&mut self, config: NetworkConfig) -> RobotResult<()> {
+        self.config = config;
         if let Some(stream) = &self.socket {
-            stream.shutdown(Shutdown::Both)?;
+            stream.set_read_timeout(Some(self.config.read_timeout))?;
+            stream.set_write_timeout(Some(self.config.write_timeout))?;
         }
-        self.socket = None;
-        self.connected = false;
         Ok(())
     }
 
-    /// 判断是否已连接
-    pub fn is_connected(&self) -> bool {
-        self.connected
+    /// 注册连接状态变化的回调，回调在发生变化的线程中同步执行
+    pub fn on_state_change<F>(&mut self, callback: F)
+    where
+        F: FnMut(ConnectionState) + Send + 'static,
+    {
+        self.callbacks.get_mut().unwrap().push(Box::new(callback));
+    }
+
+    /// 发送心跳探测连接，连接已断开且开启自动重连时会先重连
+    pub fn heartbeat(&mut self) -> RobotResult<()> {
+        #[cfg(not(feature = "no_robot"))]
+        {
+            self.ensure_connected(Instant::now() + self.config.reconnect_timeout)?;
+            self.probe()
+        }
+        #[cfg(feature = "no_robot")]
+        Ok(())
+    }
+
+    /// 按指数退避重新建立连接，总时长不超过 [`NetworkConfig::reconnect_timeout`]
+    pub fn reconnect(&mut self) -> RobotResult<()> {
+        self.reconnect_until(Instant::now() + self.config.reconnect_timeout)
+    }
+
+    fn reconnect_until(&mut self, deadline: Instant) -> RobotResult<()> {
+        if self.host.is_none() {
+            return Err(RobotException::NetworkError(
+                "No robot address to reconnect to.".into(),
+            ));
+        }
+        self.close();
+        let attempts = self.config.reconnect_max_attempts.max(1);
+        let mut delay = self.config.reconnect_initial_delay;
+        let mut last_error = None;
+        let mut attempt = 0;
+        while attempt < attempts {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                break;
+            }
+            attempt += 1;
+            self.set_state(ConnectionState::Reconnecting(attempt));
+            match self.open(self.config.connect_timeout.min(remaining)) {
+                Ok(()) => return Ok(()),
+                Err(e) => last_error = Some(e),
+            }
+            if attempt < attempts {
+                sleep(delay.min(deadline.saturating_duration_since(Instant::now())));
+                delay = (delay * 2).min(self.config.reconnect_max_delay);
+            }
+        }
+        self.set_state(ConnectionState::Lost);
+        Err(RobotException::NetworkError(format!(
+            "failed to reconnect after {attempt} attempts: {}",
+            last_error.map(|e| e.to_string()).unwrap_or_default()
+        )))
     }
 
     /// 发送命令并等待返回
+    ///
+    /// 应答以 `;` 结尾，可能被拆分为多个 TCP 包；发送前会丢弃之前超时请求遗留的数据，
+    /// 收到其他指令的应答时同样丢弃并继续等待。
+    ///
+    /// 一条指令内的探测与重连共享 [`NetworkConfig::reconnect_timeout`] 的时长，
+    /// 最长阻塞时间约为该时长加上一次探测与一次收发的读写超时
     pub fn send_and_recv<R, S>(&mut self, cmd: &R) -> RobotResult<S>
     where
         R: CommandSerde,
         S: CommandSerde,
     {
         #[cfg(not(feature = "no_robot"))]
-        if let Some(stream) = &mut self.socket {
-            stream.write_all(cmd.to_string().as_bytes())?;
-            let mut buffer = [0_u8; 1024];
-            let n = stream.read(&mut buffer)?;
-            Ok(S::from_str(&String::from_utf8_lossy(&buffer[..n])).unwrap())
-        } else {
-            Err(RobotException::NetworkError(
-                "No active TCP connection.".into(),
-            ))
+        {
+            if self.single_attempt {
+                return self.exchange(cmd);
+            }
+            let deadline = Instant::now() + self.config.reconnect_timeout;
+            self.ensure_connected(deadline)?;
+            // 空闲连接可能已被对端或中间设备断开，在发送真正的指令前先探测，避免指令丢失
+            if self.is_idle() && self.probe().is_err() {
+                self.ensure_connected(deadline)?;
+            }
+            self.exchange(cmd)
         }
         #[cfg(feature = "no_robot")]
         {
@@ -91,4 +277,304 @@ impl Network {
             Ok(default_ans)
         }
     }
+
+    /// 建立 TCP 连接，`host` 与 `port` 已经记录
+    #[allow(unused_variables)]
+    fn open(&mut self, timeout: Duration) -> RobotResult<()> {
+        #[cfg(not(feature = "no_robot"))]
+        {
+            let host = self.host.as_deref().unwrap_or_default();
+            let mut last_error = None;
+            let stream = (host, self.port).to_socket_addrs()?.find_map(|addr| {
+                TcpStream::connect_timeout(&addr, timeout)
+                    .inspect_err(|e| last_error = Some(e.to_string()))
+                    .ok()
+            });
+            let Some(stream) = stream else {
+                return Err(RobotException::NetworkError(format!(
+                    "failed to connect to {host}:{}: {}",
+                    self.port,
+                    last_error.unwrap_or_else(|| "no address resolved".into())
+                )));
+            };
+            stream.set_read_timeout(Some(self.config.read_timeout))?;
+            stream.set_write_timeout(Some(self.config.write_timeout))?;
+            stream.set_nodelay(true)?;
+            self.socket = Some(stream);
+        }
+
+        self.buffer.clear();
+        self.last_activity = Some(Instant::now());
+        self.sessions += 1;
+        self.set_state(ConnectionState::Connected);
+        Ok(())
+    }
+
+    /// 关闭当前连接但不改变连接状态
+    fn close(&mut self) {
+        if let Some(stream) = self.socket.take() {
+            let _ = stream.shutdown(Shutdown::Both);
+        }
+        self.buffer.clear();
+    }
+
+    fn set_state(&mut self, state: ConnectionState) {
+        if self.state != state {
+            self.state = state;
+            for callback in self.callbacks.get_mut().unwrap().iter_mut() {
+                callback(state);
+            }
+        }
+    }
+
+    /// 确保连接可用，连接已断开且开启自动重连时重连
+    #[cfg(not(feature = "no_robot"))]
+    fn ensure_connected(&mut self, deadline: Instant) -> RobotResult<()> {
+        match self.state {
+            ConnectionState::Connected if self.socket.is_some() => Ok(()),
+            ConnectionState::Lost if self.config.auto_reconnect => self.reconnect_until(deadline),
+            _ => Err(RobotException::NetworkError(
+                "No active TCP connection.".into(),
+            )),
+        }
+    }
+
+    #[cfg(not(feature = "no_robot"))]
+    fn is_idle(&self) -> bool {
+        match (self.config.heartbeat_interval, self.last_activity) {
+            (Some(interval), Some(last)) => last.elapsed() >= interval,
+            _ => false,
+        }
+    }
+
+    /// 用 `ReadControllerState` 探测连接，控制器返回 Fail 同样说明连接正常
+    #[cfg(not(feature = "no_robot"))]
+    fn probe(&mut self) -> RobotResult<()> {
+        let _: ReadControllerStateResponse =
+            self.exchange(&ReadControllerStateRequest::from(()))?;
+        Ok(())
+    }
+
+    #[cfg(not(feature = "no_robot"))]
+    fn exchange<R, S>(&mut self, cmd: &R) -> RobotResult<S>
+    where
+        R: CommandSerde,
+        S: CommandSerde,
+    {
+        let request = cmd.to_string();
+        let command = request.split(',').next().unwrap_or_default();
+        self.discard_stale()?;
+        let Some(stream) = &mut self.socket else {
+            return Err(RobotException::NetworkError(
+                "No active TCP connection.".into(),
+            ));
+        };
+        let written = stream.write_all(request.as_bytes());
+        self.check_io(written)?;
+        loop {
+            let frame = self.read_frame()?;
+            if frame.split(',').next() == Some(command) {
+                self.last_activity = Some(Instant::now());
+                return S::from_str(&frame);
+            }
+        }
+    }
+
+    /// 读取下一条以 `;` 结尾的应答
+    #[cfg(not(feature = "no_robot"))]
+    fn read_frame(&mut self) -> RobotResult<String> {
+        loop {
+            if let Some(frame) = take_frame(&mut self.buffer) {
+                return Ok(frame);
+            }
+            if self.buffer.len() > MAX_RESPONSE_LEN {
+                self.buffer.clear();
+                return Err(RobotException::DeserializeError(format!(
+                    "response exceeds {MAX_RESPONSE_LEN} bytes without terminator"
+                )));
+            }
+            let Some(stream) = &mut self.socket else {
+                return Err(RobotException::NetworkError(
+                    "No active TCP connection.".into(),
+                ));
+            };
+            let mut chunk = [0_u8; 1024];
+            let read = stream.read(&mut chunk);
+            let n = self.check_io(read)?;
+            if n == 0 {
+                return Err(self.closed_by_peer());
+            }
+            self.buffer.extend_from_slice(&chunk[..n]);
+        }
+    }
+
+    /// 丢弃缓冲区与连接中已经到达但不属于本次请求的数据
+    #[cfg(not(feature = "no_robot"))]
+    fn discard_stale(&mut self) -> RobotResult<()> {
+        self.buffer.clear();
+        let Some(stream) = &mut self.socket else {
+            return Ok(());
+        };
+        stream.set_nonblocking(true)?;
+        let mut chunk = [0_u8; 1024];
+        let drained = loop {
+            match stream.read(&mut chunk) {
+                Ok(0) => break Ok(false),
+                Ok(_) => continue,
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break Ok(true),
+                Err(e) => break Err(e),
+            }
+        };
+        stream.set_nonblocking(false)?;
+        if !self.check_io(drained)? {
+            return Err(self.closed_by_peer());
+        }
+        Ok(())
+    }
+
+    /// 检查读写结果，连接已断开时标记为 [`ConnectionState::Lost`]
+    ///
+    /// 超时不视为断开，残留的应答会在下一次发送前丢弃
+    #[cfg(not(feature = "no_robot"))]
+    fn check_io<T>(&mut self, result: io::Result<T>) -> RobotResult<T> {
+        result.map_err(|e| {
+            if is_broken(&e) {
+                self.lose_connection();
+            }
+            e.into()
+        })
+    }
+
+    /// 对端已关闭连接，读到了 0 字节
+    #[cfg(not(feature = "no_robot"))]
+    fn closed_by_peer(&mut self) -> RobotException {
+        self.lose_connection();
+        RobotException::NetworkError("connection closed by controller".into())
+    }
+
+    #[cfg(not(feature = "no_robot"))]
+    fn lose_connection(&mut self) {
+        self.close();
+        self.set_state(ConnectionState::Lost);
+    }
+}
+
+/// 从缓冲区中取出一条以 `;` 结尾的应答
+#[cfg(any(not(feature = "no_robot"), feature = "async"))]
+pub(crate) fn take_frame(buffer: &mut Vec<u8>) -> Option<String> {
+    let end = buffer.iter().position(|b| *b == b';')?;
+    let frame: Vec<u8> = buffer.drain(..=end).collect();
+    Some(String::from_utf8_lossy(&frame).trim().to_string())
+}
+
+/// 判断错误是否说明连接已经断开
+#[cfg(any(not(feature = "no_robot"), feature = "async"))]
+pub(crate) fn is_broken(error: &io::Error) -> bool {
+    matches!(
+        error.kind(),
+        ErrorKind::BrokenPipe
+            | ErrorKind::ConnectionReset
+            | ErrorKind::ConnectionAborted
+            | ErrorKind::NotConnected
+            | ErrorKind::UnexpectedEof
+    )
+}
+
+#[cfg(all(test, not(feature = "no_robot")))]
+mod tests {
+    use std::net::TcpListener;
+    use std::sync::Arc;
+    use std::thread::{self, sleep};
+
+    use super::*;
+    use crate::types::{ReadBoxAIRequest, ReadBoxAIResponse};
+
+    fn read_request(socket: &mut TcpStream) {
+        let mut byte = [0_u8; 1];
+        while socket.read(&mut byte).unwrap() == 1 && byte[0] != b';' {}
+    }
+
+    #[test]
+    fn test_send_and_recv_framing() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let values = ["0.123456789"; 200].join(",");
+        let server = thread::spawn(move || {
+            let (mut socket, _) = listener.accept().unwrap();
+            // 上一次超时请求遗留的应答
+            socket.write_all(b"ReadBoxAI,OK,1,;").unwrap();
+
+            read_request(&mut socket);
+            let response = format!("ReadBoxAI,OK,{values},;");
+            for chunk in response.as_bytes().chunks(300) {
+                socket.write_all(chunk).unwrap();
+                sleep(Duration::from_millis(5));
+            }
+
+            read_request(&mut socket);
+            socket
+                .write_all(b"GrpEnable,OK,;ReadBoxAI,OK,abc,;")
+                .unwrap();
+        });
+
+        let mut network = Network::from_ip("127.0.0.1", port).unwrap();
+        sleep(Duration::from_millis(50));
+        let request = ReadBoxAIRequest::<200>::from([0.; 200]);
+        let response: ReadBoxAIResponse<200> = network.send_and_recv(&request).unwrap();
+        assert_eq!(response.status.unwrap(), [0.123456789; 200]);
+        let response: RobotResult<ReadBoxAIResponse<200>> = network.send_and_recv(&request);
+        assert!(matches!(response, Err(RobotException::DeserializeError(_))));
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_reconnect_after_connection_lost() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = thread::spawn(move || {
+            for _ in 0..2 {
+                let (mut socket, _) = listener.accept().unwrap();
+                read_request(&mut socket);
+                socket.write_all(b"ReadControllerState,OK,1,;").unwrap();
+            }
+        });
+
+        let mut network = Network::from_ip("127.0.0.1", port).unwrap();
+        let states = Arc::new(Mutex::new(Vec::new()));
+        let recorder = states.clone();
+        network.on_state_change(move |state| recorder.lock().unwrap().push(state));
+
+        let request = ReadControllerStateRequest::from(());
+        let response: ReadControllerStateResponse = network.send_and_recv(&request).unwrap();
+        assert!(response.status.unwrap());
+
+        // 控制器关闭了第一条连接
+        sleep(Duration::from_millis(50));
+        let response: RobotResult<ReadControllerStateResponse> = network.send_and_recv(&request);
+        assert!(matches!(response, Err(RobotException::NetworkError(_))));
+        assert_eq!(network.state(), ConnectionState::Lost);
+
+        let response: ReadControllerStateResponse = network.send_and_recv(&request).unwrap();
+        assert!(response.status.unwrap());
+        assert_eq!(
+            *states.lock().unwrap(),
+            [
+                ConnectionState::Lost,
+                ConnectionState::Reconnecting(1),
+                ConnectionState::Connected
+            ]
+        );
+        server.join().unwrap();
+
+        network.disconnect().unwrap();
+        assert_eq!(network.state(), ConnectionState::Disconnected);
+        assert!(
+            network
+                .send_and_recv::<_, ReadControllerStateResponse>(&request)
+                .is_err()
+        );
+
+        // 连接失败时返回错误而不是得到一个未连接的实例
+        assert!(Network::from_ip("127.0.0.1", port).is_err());
+    }
 }
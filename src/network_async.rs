@@ -0,0 +1,179 @@
+use std::io::{self, ErrorKind};
+
+use robot_behavior::{RobotException, RobotResult};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+use tokio::time::timeout;
+
+use crate::network::{MAX_RESPONSE_LEN, NetworkConfig, is_broken, take_frame};
+use crate::types::CommandSerde;
+
+/// 等待发送的请求数量上限
+const REQUEST_QUEUE_LEN: usize = 32;
+
+struct Job {
+    request: String,
+    reply: oneshot::Sender<RobotResult<String>>,
+}
+
+/// 异步指令端口连接
+///
+/// 连接由独立的 I/O 任务持有，请求按提交顺序逐条发送并等待应答。
+/// 调用方的 future 在请求发出前被取消时，该请求不会再发送；请求发出后被取消时，
+/// I/O 任务仍会读完对应的应答再丢弃，因此后续请求不会拿到错位的应答。
+pub struct AsyncNetwork {
+    sender: Option<mpsc::Sender<Job>>,
+    host: String,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl AsyncNetwork {
+    /// 连接到指定 IP 与端口
+    pub async fn connect(host: &str, port: u16) -> RobotResult<Self> {
+        Self::connect_with(host, port, NetworkConfig::default()).await
+    }
+
+    /// 使用指定的超时设置连接到指定 IP 与端口，心跳与重连策略不适用于异步连接
+    pub async fn connect_with(host: &str, port: u16, config: NetworkConfig) -> RobotResult<Self> {
+        let stream = timeout(config.connect_timeout, TcpStream::connect((host, port)))
+            .await
+            .map_err(|_| {
+                RobotException::NetworkError(format!("connect to {host}:{port} timed out"))
+            })??;
+        stream.set_nodelay(true)?;
+
+        let (sender, receiver) = mpsc::channel(REQUEST_QUEUE_LEN);
+        let handle = tokio::spawn(io_task(stream, receiver, config));
+        Ok(AsyncNetwork {
+            sender: Some(sender),
+            host: host.to_string(),
+            handle: Some(handle),
+        })
+    }
+
+    /// 断开连接，已经提交的请求会先处理完
+    pub async fn disconnect(&mut self) {
+        self.sender = None;
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.await;
+        }
+    }
+
+    /// 判断连接是否仍然可用
+    pub fn is_connected(&self) -> bool {
+        self.sender
+            .as_ref()
+            .is_some_and(|sender| !sender.is_closed())
+    }
+
+    /// 连接的机器人 IP
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+
+    /// 发送命令并等待返回，可以在多个任务中并发调用
+    pub async fn send_and_recv<R, S>(&self, cmd: &R) -> RobotResult<S>
+    where
+        R: CommandSerde,
+        S: CommandSerde,
+    {
+        let closed = || RobotException::NetworkError("No active TCP connection.".into());
+        let sender = self.sender.as_ref().ok_or_else(closed)?;
+        let (reply, response) = oneshot::channel();
+        let job = Job { request: cmd.to_string(), reply };
+        sender.send(job).await.map_err(|_| closed())?;
+        let frame = response.await.map_err(|_| closed())??;
+        S::from_str(&frame)
+    }
+}
+
+async fn io_task(mut stream: TcpStream, mut receiver: mpsc::Receiver<Job>, config: NetworkConfig) {
+    let mut buffer = Vec::new();
+    while let Some(job) = receiver.recv().await {
+        // 调用方在请求发出前已经取消
+        if job.reply.is_closed() {
+            continue;
+        }
+        match exchange(&mut stream, &mut buffer, &job.request, &config).await {
+            Ok(frame) => {
+                let _ = job.reply.send(Ok(frame));
+            }
+            Err(e) if e.kind() == ErrorKind::InvalidData => {
+                let _ = job
+                    .reply
+                    .send(Err(RobotException::DeserializeError(e.to_string())));
+            }
+            Err(e) => {
+                let broken = is_broken(&e);
+                let _ = job.reply.send(Err(e.into()));
+                if broken {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// 发送一条请求并读取对应的应答，其他指令的应答直接丢弃
+async fn exchange(
+    stream: &mut TcpStream,
+    buffer: &mut Vec<u8>,
+    request: &str,
+    config: &NetworkConfig,
+) -> io::Result<String> {
+    discard_stale(stream, buffer)?;
+    timeout(config.write_timeout, stream.write_all(request.as_bytes()))
+        .await
+        .map_err(|_| ErrorKind::TimedOut)??;
+
+    let command = request.split(',').next().unwrap_or_default();
+    loop {
+        let frame = read_frame(stream, buffer, config).await?;
+        if frame.split(',').next() == Some(command) {
+            return Ok(frame);
+        }
+    }
+}
+
+async fn read_frame(
+    stream: &mut TcpStream,
+    buffer: &mut Vec<u8>,
+    config: &NetworkConfig,
+) -> io::Result<String> {
+    loop {
+        if let Some(frame) = take_frame(buffer) {
+            return Ok(frame);
+        }
+        if buffer.len() > MAX_RESPONSE_LEN {
+            buffer.clear();
+            return Err(io::Error::new(
+                ErrorKind::InvalidData,
+                format!("response exceeds {MAX_RESPONSE_LEN} bytes without terminator"),
+            ));
+        }
+        let mut chunk = [0_u8; 1024];
+        let n = timeout(config.read_timeout, stream.read(&mut chunk))
+            .await
+            .map_err(|_| ErrorKind::TimedOut)??;
+        if n == 0 {
+            return Err(ErrorKind::UnexpectedEof.into());
+        }
+        buffer.extend_from_slice(&chunk[..n]);
+    }
+}
+
+/// 丢弃之前超时请求遗留的数据
+fn discard_stale(stream: &TcpStream, buffer: &mut Vec<u8>) -> io::Result<()> {
+    buffer.clear();
+    let mut chunk = [0_u8; 1024];
+    loop {
+        match stream.try_read(&mut chunk) {
+            Ok(0) => return Err(ErrorKind::UnexpectedEof.into()),
+            Ok(_) => continue,
+            Err(e) if e.kind() == ErrorKind::WouldBlock => return Ok(()),
+            Err(e) => return Err(e),
+        }
+    }
+}
@@ -1,36 +1,391 @@
-﻿use std::{marker::PhantomData, thread::sleep, time::Duration};
+﻿use std::{
+    marker::PhantomData,
+    path::Path,
+    sync::Arc,
+    thread::sleep,
+    time::{Duration, Instant},
+};
 
+use nalgebra::{Isometry3, Point3};
 use robot_behavior::{
     ArmState, Coord, JointSample, LoadState, OverrideOnce, Pose, Robot, RobotException,
     RobotResult, SpatialSample, StateView, driver::*,
 };
 
-use crate::{RobotMode, robot_impl::RobotImpl, robot_param::*, robot_state::RobotState, types::*};
+use crate::{
+    ArcOrientation, ConnectionState, DEFAULT_TCP_NAME, DEFAULT_UCS_NAME, DatasheetReader,
+    DhParameters, EmergencyStopOutput, FaultReport, Frame, FrameRegistry, HansModel, Kinematics,
+    Lifecycle, LifecycleConfig, LifecycleError, LifecycleStage, MotionCommand, MoveCircular,
+    MoveMode, NetworkConfig, RobotError, RobotMode, ServoConfig, ServoSession, ServoTarget,
+    ServoTick, StateStream, TcpCalibration, TcpCalibrationResult, UserFrameTeaching, WaitOutcome,
+    WaitPolicy, WaitResult,
+    frame::{ActiveFrames, iso_to_pose, pose_to_iso},
+    motion::{MotionParams, MotionTarget, arc_samples, check_arc_points},
+    robot_impl::RobotImpl,
+    robot_param::*,
+    robot_state::RobotState,
+    servo::{ServoGuard, ServoHandle},
+    state_cache::{CachedState, StateCache, StateSource},
+    types::*,
+    validation::{
+        Workspace, check_cartesian_acc, check_cartesian_vel, check_joint_acc, check_joint_limits,
+        check_joint_vel, check_payload,
+    },
+    wait::MotionWatch,
+};
 
 pub trait HansType {
     const N: usize;
+    /// 该类型对应的机型，决定额定负载等参数
+    const MODEL: HansModel;
 }
 
 pub struct HansRobot<T: HansType, const N: usize> {
     pub(crate) marker: PhantomData<T>,
     pub robot_impl: RobotImpl<N>,
+    pub(crate) datasheet: Option<DatasheetReader>,
+    pub(crate) state_stream: Option<StateStream>,
+    pub(crate) state_cache: StateCache<N>,
     pub(crate) is_moving: bool,
+    /// 最近一次下发运动的时间
+    pub(crate) motion_started: Option<Instant>,
+    pub(crate) stop_requested: bool,
+    pub(crate) lifecycle: Lifecycle,
+    pub(crate) wait_policy: WaitPolicy,
+    pub(crate) workspace: Workspace,
+    pub(crate) emergency_stop_output: Option<EmergencyStopOutput>,
+    pub(crate) servo: Option<ServoHandle>,
+    /// 初始化时要求控制器返回的机型编号
+    pub(crate) expected_model_code: Option<u16>,
 
     pub(crate) coord: OverrideOnce<Coord>,
     pub(crate) max_vel: OverrideOnce<[f64; N]>,
     pub(crate) max_acc: OverrideOnce<[f64; N]>,
     pub(crate) max_cartesian_vel: OverrideOnce<f64>,
     pub(crate) max_cartesian_acc: OverrideOnce<f64>,
+    pub(crate) motion_command: OverrideOnce<MotionCommand>,
+    pub(crate) blend_radius: OverrideOnce<f64>,
+    pub(crate) arc_orientation: OverrideOnce<ArcOrientation>,
+    pub(crate) frames: FrameRegistry,
+    pub(crate) active_frames: ActiveFrames,
+    pub(crate) tool: OverrideOnce<String>,
+    pub(crate) user_frame: OverrideOnce<String>,
 }
 
 impl<T: HansType, const N: usize> HansRobot<T, N> {
-    pub fn connect(&mut self, ip: &str, port: u16) {
-        self.robot_impl.connect(ip, port);
+    pub fn connect(&mut self, ip: &str, port: u16) -> RobotResult<()> {
+        self.robot_impl.connect(ip, port)
     }
 
     /// Disconnects from the robot controller.
-    pub fn disconnect(&mut self) {
-        self.robot_impl.disconnect();
+    pub fn disconnect(&mut self) -> RobotResult<()> {
+        self.state_stream = None;
+        self.datasheet = None;
+        self.robot_impl.disconnect()
+    }
+
+    /// 该类型对应的机型
+    pub fn model(&self) -> HansModel {
+        T::MODEL
+    }
+
+    /// 读取控制器 `ReadRobotModel` 返回的原始机型编号
+    pub fn read_model_code(&mut self) -> RobotResult<u16> {
+        self.robot_impl.robot_model(0)
+    }
+
+    /// 设置控制器应当返回的机型编号，[`init`](Robot::init) 时不一致则拒绝初始化
+    ///
+    /// 未设置时使用 [`ModelParams::model_code`](crate::ModelParams::model_code)；两者都没有时不做检查。
+    pub fn set_expected_model_code(&mut self, code: Option<u16>) {
+        self.expected_model_code = code;
+    }
+
+    /// 读取机型编号并与期望的编号比较，不一致时返回错误
+    pub fn check_model(&mut self) -> RobotResult<()> {
+        let Some(expected) = self.expected_model_code.or(T::MODEL.params().model_code) else {
+            return Ok(());
+        };
+        let code = self.read_model_code()?;
+        if code != expected {
+            return Err(RobotException::InvalidInstruction(format!(
+                "controller reports model code {code}, but {} expects {expected}",
+                T::MODEL
+            )));
+        }
+        Ok(())
+    }
+
+    /// 指令连接的当前状态
+    pub fn connection_state(&self) -> ConnectionState {
+        self.robot_impl.network.state()
+    }
+
+    /// 注册指令连接状态变化的回调，例如在连接断开与重连成功时通知应用
+    pub fn on_connection_state<F>(&mut self, callback: F)
+    where
+        F: FnMut(ConnectionState) + Send + 'static,
+    {
+        self.robot_impl.network.on_state_change(callback);
+    }
+
+    /// 修改指令连接的超时、心跳与重连策略
+    pub fn set_network_config(&mut self, config: NetworkConfig) -> RobotResult<()> {
+        self.robot_impl.network.set_config(config)
+    }
+
+    /// 主动发送一次心跳，连接断开且开启自动重连时会先重连
+    pub fn heartbeat(&mut self) -> RobotResult<()> {
+        self.robot_impl.network.heartbeat()
+    }
+
+    /// 执行完整的上电流程直到 [`RobotMode::StandBy`]，失败时报告所在的阶段
+    pub fn power_up(&mut self) -> Result<(), LifecycleError> {
+        self.lifecycle.power_up(&mut self.robot_impl)
+    }
+
+    /// 执行完整的关机流程直到 [`RobotMode::Blackout48V`]，失败时报告所在的阶段
+    pub fn power_down(&mut self) -> Result<(), LifecycleError> {
+        self.is_moving = false;
+        self.lifecycle.shutdown(&mut self.robot_impl)
+    }
+
+    /// 修改上电与关机流程的超时设置
+    pub fn set_lifecycle_config(&mut self, config: LifecycleConfig) {
+        self.lifecycle.config = config;
+    }
+
+    /// 读取故障诊断报告，汇总状态机、状态标志、急停信息与关节故障
+    ///
+    /// 控制器报警码通过 [`read_state`](Robot::read_state) 从 JSON 推送数据中读取，
+    /// 推送端口不可用时返回错误。
+    pub fn diagnose(&mut self) -> RobotResult<FaultReport> {
+        let mut report = self.robot_impl.diagnose()?;
+        let error = self.read_state()?.state_and_error.error_code;
+        report.controller_error = (error != RobotError::NoError).then_some(error);
+        Ok(report)
+    }
+
+    /// 读取当前状态机并校验 `command` 能否执行，返回指令完成后机器人应处的状态
+    ///
+    /// 不能执行时返回 [`RobotException::InvalidInstruction`]，说明被拒绝的原因，
+    /// 例如 `cannot MoveJ in FreeDriver: free drive is active, close it first`。
+    pub fn check_command(&mut self, command: Command) -> RobotResult<RobotMode> {
+        let mode = self.robot_impl.state_read_cur_fsm(0)?;
+        let next = mode.transition(command)?;
+        if next == RobotMode::Moving {
+            self.stop_requested = false;
+        }
+        Ok(next)
+    }
+
+    /// 取出本次运动的参数，同时清除所有只生效一次的设置
+    pub(crate) fn take_motion_params(&mut self) -> RobotResult<MotionParams<N>> {
+        let tool = self.frames.tool(&self.tool.get())?;
+        let user_frame = self.frames.user_frame(&self.user_frame.get())?;
+        Ok(MotionParams {
+            command: self.motion_command.get(),
+            radius: self.blend_radius.get(),
+            joint_vel: self.max_vel.get(),
+            joint_acc: self.max_acc.get(),
+            cartesian_vel: self.max_cartesian_vel.get(),
+            cartesian_acc: self.max_cartesian_acc.get(),
+            tool,
+            user_frame,
+        })
+    }
+
+    /// 将本次运动选中的工具坐标与用户坐标写入控制器
+    fn apply_frames(&mut self, params: &MotionParams<N>) -> RobotResult<()> {
+        (self.active_frames).apply(&mut self.robot_impl, &params.tool, &params.user_frame)
+    }
+
+    /// 工具坐标与用户坐标的注册表
+    pub fn frames(&self) -> &FrameRegistry {
+        &self.frames
+    }
+
+    /// 修改注册表，修改后的坐标系在下一次运动时写入控制器
+    pub fn frames_mut(&mut self) -> &mut FrameRegistry {
+        &mut self.frames
+    }
+
+    /// 从 JSON 文件读取工具坐标与用户坐标
+    pub fn load_frames(&mut self, path: impl AsRef<Path>) -> RobotResult<()> {
+        self.frames = FrameRegistry::load(path)?;
+        Ok(())
+    }
+
+    /// 将工具坐标与用户坐标保存为 JSON 文件
+    pub fn save_frames(&self, path: impl AsRef<Path>) -> RobotResult<()> {
+        self.frames.save(path)
+    }
+
+    /// 选择默认的工具坐标并立即写入控制器
+    pub fn set_tool(&mut self, name: &str) -> RobotResult<()> {
+        let tool = self.frames.tool(name)?;
+        (self.active_frames).apply_tool(&mut self.robot_impl, &tool)?;
+        self.tool.set(tool.name);
+        Ok(())
+    }
+
+    /// 选择默认的用户坐标并立即写入控制器
+    pub fn set_user_frame(&mut self, name: &str) -> RobotResult<()> {
+        let user_frame = self.frames.user_frame(name)?;
+        (self.active_frames).apply_user_frame(&mut self.robot_impl, &user_frame)?;
+        self.user_frame.set(user_frame.name);
+        Ok(())
+    }
+
+    /// 下一次运动使用的工具坐标
+    pub fn with_tool(&mut self, name: &str) -> &mut Self {
+        self.tool.once(name.to_string());
+        self
+    }
+
+    /// 下一次运动使用的用户坐标
+    pub fn with_user_frame(&mut self, name: &str) -> &mut Self {
+        self.user_frame.once(name.to_string());
+        self
+    }
+
+    /// 读取法兰在基坐标系下的实际位姿，与当前选择的工具坐标无关
+    pub fn read_flange_pose(&mut self) -> RobotResult<[f64; 6]> {
+        let pose = self.robot_impl.state_read_act_pos(0)?;
+        let flange = pose_to_iso(&pose.pose_o_to_ee) * pose_to_iso(&pose.pose_f_to_ee).inverse();
+        Ok(iso_to_pose(&flange))
+    }
+
+    /// 求解 TCP 标定，将结果以 `name` 存入注册表并设为默认工具坐标
+    ///
+    /// 标定结果会立即写入控制器，残差见返回值。
+    pub fn calibrate_tcp(
+        &mut self,
+        name: &str,
+        calibration: &TcpCalibration,
+    ) -> RobotResult<TcpCalibrationResult> {
+        let result = calibration.solve()?;
+        self.frames.define_tool(name, result.tcp);
+        self.set_tool(name)?;
+        Ok(result)
+    }
+
+    /// 读取当前工具末端在基坐标系下的实际位姿
+    pub fn read_tcp_pose(&mut self) -> RobotResult<[f64; 6]> {
+        Ok(self.robot_impl.state_read_act_pos(0)?.pose_o_to_ee)
+    }
+
+    /// 由三点示教求解用户坐标，以 `name` 存入注册表并设为默认用户坐标
+    ///
+    /// 返回的坐标系可以通过 [`with_user_frame`](Self::with_user_frame) 在之后的运动中按名称选择。
+    pub fn teach_user_frame(
+        &mut self,
+        name: &str,
+        teaching: &UserFrameTeaching,
+    ) -> RobotResult<Frame> {
+        let pose = teaching.solve()?;
+        self.frames.define_user_frame(name, pose);
+        self.set_user_frame(name)?;
+        self.frames.user_frame(name)
+    }
+
+    /// 设置 [`waiting_for_finish`](Robot::waiting_for_finish) 使用的等待策略
+    pub fn set_wait_policy(&mut self, policy: WaitPolicy) {
+        self.wait_policy = policy;
+    }
+
+    /// 按照给定的策略等待当前运动结束，返回运动是完成、被停止、故障还是超时
+    ///
+    /// 策略允许且状态流已开启时，从推送的状态中读取状态机；开始等待后的前两帧可能仍是
+    /// 下发指令之前的状态，因此在此之前仍然通过指令端口查询。状态流中断期间同样改为查询指令端口。
+    pub fn wait_for_motion(&mut self, policy: &WaitPolicy) -> RobotResult<WaitResult> {
+        let stream = (self.state_stream.as_ref())
+            .filter(|stream| policy.use_state_stream && stream.is_running());
+        let fresh_frame = stream.map_or(0, |stream| stream.frame_count() + 2);
+        let mut watch = MotionWatch::new(policy, self.stop_requested);
+        loop {
+            let streamed = stream
+                .filter(|stream| stream.frame_count() >= fresh_frame)
+                .and_then(|stream| stream.latest_within(STREAM_STATE_MAX_AGE))
+                .map(|state| state.state_and_error.state);
+            let mode = match streamed {
+                Some(mode) => mode,
+                None => self.robot_impl.state_read_cur_fsm(0)?,
+            };
+            if let Some(result) = watch.observe(mode) {
+                self.is_moving = result.outcome == WaitOutcome::TimedOut;
+                return Ok(result);
+            }
+            sleep(watch.poll_interval());
+        }
+    }
+
+    /// 启动后台状态流，此后 [`read_state`](Robot::read_state) 与 [`Arm::state`]
+    /// 直接读取本地缓存的最新状态，不再占用指令端口；最新一帧超过 200 ms 未更新时
+    /// 视为状态流中断，改为主动读取
+    pub fn start_state_stream(&mut self) -> RobotResult<&StateStream> {
+        if self.state_stream.is_none() {
+            let host = self.robot_impl.network.host().ok_or_else(|| {
+                RobotException::NetworkError("Robot is not connected".to_string())
+            })?;
+            let stream = StateStream::start(host, crate::PORT_DATASHEET_JSON_1)?;
+            let cache = self.state_cache.clone();
+            stream.on_state_timed(1, move |state, received| {
+                cache.update_from_datasheet(state, received)
+            });
+            self.state_stream = Some(stream);
+        }
+        Ok(self.state_stream.as_ref().unwrap())
+    }
+
+    /// 停止后台状态流
+    pub fn stop_state_stream(&mut self) {
+        self.state_stream = None;
+    }
+
+    /// 当前运行中的后台状态流，可用于订阅状态
+    pub fn state_stream(&self) -> Option<&StateStream> {
+        self.state_stream.as_ref()
+    }
+
+    /// 状态流中未过期的最新一帧，状态流断开或正在重连时返回 `None`
+    fn streamed_state(&self) -> Option<Arc<RobotState>> {
+        (self.state_stream.as_ref())?.latest_within(STREAM_STATE_MAX_AGE)
+    }
+
+    /// 最近一次获得的关节与末端状态，由 [`Arm::state`]、[`read_state`](Robot::read_state)
+    /// 与后台状态流更新，尚未获得过状态时返回 `None`
+    ///
+    /// [`Arm::get_joint`] 与 [`Arm::get_endpoint`] 返回同一份缓存，可以通过时间戳判断是否过期。
+    pub fn cached_state(&self) -> Option<CachedState<N>> {
+        self.state_cache.latest()
+    }
+
+    /// 通过指令端口读取当前位置并写入缓存
+    fn refresh_state_cache(&mut self) -> RobotResult<CachedState<N>> {
+        let pose = self.robot_impl.state_read_act_pos(0)?;
+        let received = Instant::now();
+        let cache = &self.state_cache;
+        Ok(cache.update(
+            pose.joint,
+            pose.pose_o_to_ee,
+            StateSource::Command,
+            received,
+        ))
+    }
+
+    /// 设置工具末端允许到达的空间，每次运动下发前都会在本地检查
+    pub fn set_workspace(&mut self, workspace: Workspace) {
+        self.workspace = workspace;
+    }
+
+    pub fn workspace(&self) -> &Workspace {
+        &self.workspace
+    }
+
+    /// 设置接入急停回路的 Modbus 输出，此后 [`emergency_stop`](Robot::emergency_stop) 优先使用该输出
+    pub fn set_emergency_stop_output(&mut self, output: Option<EmergencyStopOutput>) {
+        self.emergency_stop_output = output;
     }
 }
 
@@ -42,19 +397,40 @@ impl<T: HansType, const N: usize> Robot for HansRobot<T, N> {
         format!("HansRobot v{HANS_VERSION}")
     }
 
+    /// 连接电箱、上电并启动主站，此后调用 [`enable`](Robot::enable) 使能
     fn init(&mut self) -> RobotResult<()> {
-        if self.robot_impl.is_connected() {
-            self.robot_impl.robot_power_on(())?;
-            Ok(())
-        } else {
-            Err(RobotException::NetworkError(
+        if !self.robot_impl.is_connected() {
+            return Err(RobotException::NetworkError(
                 "Robot is not connected".to_string(),
-            ))
+            ));
         }
+        self.check_model()?;
+        self.lifecycle.run(
+            &mut self.robot_impl,
+            &LifecycleStage::POWER_UP[..LifecycleStage::POWER_UP.len() - 1],
+        )?;
+        // 先填充一次状态缓存，此后 get_joint 与 get_endpoint 不再返回零
+        self.refresh_state_cache()?;
+        Ok(())
     }
 
+    /// 按照去使能、关闭主站、断电的顺序关机
     fn shutdown(&mut self) -> RobotResult<()> {
-        self.disable()?;
+        self.lifecycle.shutdown(&mut self.robot_impl)?;
+        self.is_moving = false;
+        Ok(())
+    }
+
+    fn enable(&mut self) -> RobotResult<()> {
+        self.lifecycle
+            .run(&mut self.robot_impl, &[LifecycleStage::Enable])?;
+        Ok(())
+    }
+
+    fn disable(&mut self) -> RobotResult<()> {
+        self.lifecycle
+            .run(&mut self.robot_impl, &[LifecycleStage::Disable])?;
+        self.is_moving = false;
         Ok(())
     }
 
@@ -64,27 +440,28 @@ impl<T: HansType, const N: usize> Robot for HansRobot<T, N> {
     }
 
     fn is_moving(&mut self) -> RobotResult<bool> {
+        // 伺服会话结束时已经退出伺服模式
+        if self.servo.as_ref().is_some_and(|servo| !servo.is_running()) {
+            self.servo = None;
+            self.is_moving = false;
+        }
         if !self.is_moving {
             return Ok(false);
         }
-        self.is_moving = self.robot_impl.state_read_cur_fsm(0).unwrap() != RobotMode::StandBy;
+        self.is_moving = self.robot_impl.state_read_cur_fsm(0)? != RobotMode::StandBy;
         Ok(self.is_moving)
     }
 
+    /// 按照 [`set_wait_policy`](HansRobot::set_wait_policy) 设置的策略等待运动结束，
+    /// 故障或超时时返回错误
     fn waiting_for_finish(&mut self) -> RobotResult<()> {
-        loop {
-            let state = self.robot_impl.state_read_cur_fsm(0)?;
-            if state == RobotMode::StandBy {
-                break;
-            }
-        }
-
-        self.is_moving = false;
-        Ok(())
+        let policy = self.wait_policy.clone();
+        self.wait_for_motion(&policy)?.into_result()
     }
 
     fn stop(&mut self) -> RobotResult<()> {
         self.robot_impl.robot_move_stop(0)?;
+        self.stop_requested = true;
         Ok(())
     }
 
@@ -98,16 +475,101 @@ impl<T: HansType, const N: usize> Robot for HansRobot<T, N> {
         Ok(())
     }
 
+    /// 急停
+    ///
+    /// 设置了 [`EmergencyStopOutput`] 时只写该输出，由硬件急停回路停止运动并断电。
+    /// 否则通过指令端口先停止运动，再断开机械臂 48V 电源使抱闸动作；这些指令都只在当前连接上
+    /// 尝试一次，不探测也不重连，每条指令最多阻塞一个读写超时。
+    /// 断电成功，或断电失败但机器人已经处于断电或急停状态时视为成功，否则返回包含停止与断电
+    /// 两次失败原因的错误。
     fn emergency_stop(&mut self) -> RobotResult<()> {
-        unimplemented!("hans robot does not support emergency stop")
+        self.is_moving = false;
+        self.stop_requested = true;
+        if let Some(output) = &mut self.emergency_stop_output {
+            return output.trigger();
+        }
+        self.robot_impl.single_attempt(|robot_impl| {
+            let stopped = robot_impl.robot_move_stop(0);
+            let Err(power_off) = robot_impl.robot_power_off(()) else {
+                return Ok(());
+            };
+            match robot_impl.state_read_cur_fsm(0) {
+                Ok(
+                    RobotMode::EmergencyStop | RobotMode::Blackouting48V | RobotMode::Blackout48V,
+                ) => Ok(()),
+                _ => Err(RobotException::CommandException(format!(
+                    "emergency stop failed, stop: {}, power off: {power_off}",
+                    stopped.err().map_or("ok".to_string(), |e| e.to_string())
+                ))),
+            }
+        })
     }
 
+    /// 解除急停并恢复到 [`RobotMode::StandBy`]
+    ///
+    /// 硬件急停或安全光幕仍处于触发状态时直接返回错误。此后按照状态机逐步推进，
+    /// 每进入一个状态只执行一次对应的指令：
+    ///
+    /// | 当前状态 | 动作 |
+    /// | --- | --- |
+    /// | `EmergencyStop`、`Error`、`SaftyGuard`、`SaftyGuardError`、`RobotCollisionStop`、`RobotOutofSafeSpace` | `GrpReset` |
+    /// | `ElectricBoxDisconnect` | `ConnectToBox` |
+    /// | `Blackout48V` | `Electrify` |
+    /// | `ControllerDisconnect` | `StartMaster` |
+    /// | `Disable` | `GrpEnable` |
+    /// | `StandBy` | 恢复完成 |
+    /// | 其他过渡状态 | 等待 |
+    ///
+    /// 指令被控制器拒绝，或状态机在 30 秒内没有到达 `StandBy` 时返回错误。
     fn clear_emergency_stop(&mut self) -> RobotResult<()> {
-        unimplemented!("hans robot does not support clear emergency stop")
+        let info = self.robot_impl.state_read_emergency_info(0)?;
+        if info.is_estop {
+            return Err(RobotException::UnprocessableInstructionError(format!(
+                "hardware emergency stop is still engaged (code {}), release it first",
+                info.esto_code
+            )));
+        }
+        if info.is_safety_guard {
+            return Err(RobotException::UnprocessableInstructionError(format!(
+                "safety guard is still triggered (code {}), clear it first",
+                info.safety_guard_code
+            )));
+        }
+        recover_to_standby(&mut self.robot_impl, RECOVERY_TIMEOUT)
     }
 
     fn read_state(&mut self) -> RobotResult<Self::State> {
-        unimplemented!()
+        if let Some(state) = self.streamed_state() {
+            return Ok((*state).clone());
+        }
+
+        #[cfg(feature = "no_robot")]
+        return Ok(RobotState::default());
+
+        #[cfg(not(feature = "no_robot"))]
+        {
+            let datasheet = match &mut self.datasheet {
+                Some(datasheet) => datasheet,
+                None => {
+                    let host = self.robot_impl.network.host().ok_or_else(|| {
+                        RobotException::NetworkError("Robot is not connected".to_string())
+                    })?;
+                    self.datasheet.insert(DatasheetReader::connect(
+                        host,
+                        crate::PORT_DATASHEET_JSON_1,
+                    )?)
+                }
+            };
+            let state = datasheet.latest_state();
+            let received = Instant::now();
+            match &state {
+                Ok(state) => self.state_cache.update_from_datasheet(state, received),
+                // 连接异常时丢弃读取器，下次调用时重新连接
+                Err(RobotException::NetworkError(_)) => self.datasheet = None,
+                Err(_) => {}
+            }
+            state
+        }
     }
 }
 
@@ -116,9 +578,16 @@ where
     HansRobot<T, N>: Joints<N> + EndPoint + MoveTo<JointSpace<N>> + MoveTo<FlangeSpace>,
 {
     fn state(&mut self) -> RobotResult<ArmState<N>> {
+        if let Some(state) = self.streamed_state() {
+            return Ok(arm_state_from_stream(&state));
+        }
+
         let act_pose = self.robot_impl.state_read_act_pos(0)?;
+        let received = Instant::now();
         let joint_vel = self.robot_impl.state_read_act_joint_vel(0)?;
         let pose_vel = self.robot_impl.state_read_act_tcp_vel(0)?;
+        let (joint, pose) = (act_pose.joint, act_pose.pose_o_to_ee);
+        (self.state_cache).update(joint, pose, StateSource::Command, received);
 
         let state = ArmState {
             joint: StateView::from_meas(JointSample {
@@ -143,22 +612,38 @@ where
         Ok(state)
     }
     fn set_load(&mut self, load: LoadState) -> RobotResult<()> {
+        check_payload(load.m, T::MODEL.params().max_load)?;
         self.robot_impl
             .state_set_payload((0, Load { mass: load.m, centroid: load.x }))
     }
+    /// 缓存的关节角，见 [`cached_state`](HansRobot::cached_state)
+    ///
+    /// [`init`](Robot::init) 成功后缓存中总有状态；在此之前且没有调用过 [`Arm::state`]、
+    /// [`read_state`](Robot::read_state) 或启动状态流时返回零，需要区分时使用 `cached_state`
     fn get_joint(&self) -> [f64; N] {
-        [0.; N]
+        self.state_cache
+            .latest()
+            .map_or([0.; N], |state| state.joint)
     }
 
+    /// 缓存的末端位姿，见 [`cached_state`](HansRobot::cached_state)，尚未获得过状态时为默认值，
+    /// 与 [`get_joint`](Arm::get_joint) 相同
     fn get_endpoint(&self) -> Pose {
-        Pose::default()
+        self.state_cache
+            .latest()
+            .map_or_else(Pose::default, |state| {
+                let [x, y, z, rx, ry, rz] = state.pose;
+                Pose::Euler([x, y, z], [rx, ry, rz])
+            })
     }
 
+    /// 本次运动的关节速度上限，单位 °/s
     fn with_joint_vel(mut self, vel_bound: [f64; N]) -> Self {
         self.max_vel.once(vel_bound);
         self
     }
 
+    /// 本次运动的关节加速度上限，单位 rad/s²
     fn with_joint_acc(mut self, acc_bound: [f64; N]) -> Self {
         self.max_acc.once(acc_bound);
         self
@@ -205,8 +690,17 @@ where
 
 impl<T: HansType, const N: usize> HansRobot<T, N>
 where
-    HansRobot<T, N>: Joints<N> + EndPoint,
+    HansRobot<T, N>: Joints<N> + EndPoint + DhParameters<N>,
 {
+    /// 设置运动目标所在的坐标系
+    ///
+    /// - [`Coord::OCS`]：绝对目标
+    /// - [`Coord::Inertial`]：相对当前位置的增量，笛卡尔增量沿当前用户坐标系，默认与基坐标系重合
+    /// - [`Coord::Relative`]：相对当前位置的增量，笛卡尔增量沿工具坐标系
+    /// - [`Coord::Other`]：位姿目标在给定坐标系中表示，关节目标视为绝对目标
+    ///
+    /// 关节增量通过 `WayPointRel` 一次下发所有非零轴；笛卡尔增量只有一个方向时使用 `MoveRelL`，
+    /// 其 `coord` 字段选择工具坐标系或用户坐标系，否则在本地换算为绝对目标后直线运动。
     pub fn set_coord(&mut self, coord: Coord) -> RobotResult<()> {
         self.coord.set(coord);
         Ok(())
@@ -238,100 +732,361 @@ where
             .once(Self::CARTESIAN_ACC_BOUND * scale);
         self
     }
+
+    /// 设置单点运动下发的指令，默认为 [`MotionCommand::Auto`]
+    pub fn set_motion_command(&mut self, command: MotionCommand) {
+        self.motion_command.set(command);
+    }
+
+    /// 下一次单点运动使用的指令
+    pub fn with_motion_command(&mut self, command: MotionCommand) -> &mut Self {
+        self.motion_command.once(command);
+        self
+    }
+
+    /// 设置过渡半径，单位 mm，默认为 [`DEFAULT_BLEND_RADIUS`](crate::DEFAULT_BLEND_RADIUS)
+    pub fn set_blend_radius(&mut self, radius: f64) {
+        self.blend_radius.set(radius);
+    }
+
+    /// 下一次单点运动使用的过渡半径，单位 mm
+    pub fn with_blend_radius(&mut self, radius: f64) -> &mut Self {
+        self.blend_radius.once(radius);
+        self
+    }
+
+    /// 设置圆弧运动中末端姿态的变化方式，默认为 [`ArcOrientation::Rotating`]
+    pub fn set_arc_orientation(&mut self, orientation: ArcOrientation) {
+        self.arc_orientation.set(orientation);
+    }
+
+    /// 下一次圆弧运动中末端姿态的变化方式
+    pub fn with_arc_orientation(&mut self, orientation: ArcOrientation) -> &mut Self {
+        self.arc_orientation.once(orientation);
+        self
+    }
+
+    /// 校验三点后下发 `MoveC`，三点为绝对位姿，`with_coord` 设置为 [`Coord::Other`] 时先换算到基坐标系，
+    /// 相对坐标会被拒绝；无论成功与否都会消耗只生效一次的设置
+    fn move_c(&mut self, points: [Pose; 3], turns: Option<f64>) -> RobotResult<()> {
+        let orientation = self.arc_orientation.get();
+        let coord = self.coord.get();
+        let params = self.take_motion_params()?;
+        let points = match coord {
+            Coord::Other(frame) => points.map(|pose| frame * pose),
+            Coord::Inertial | Coord::Relative => {
+                return Err(RobotException::InvalidInstruction(
+                    "arc points must be absolute poses, relative coordinates are not supported"
+                        .into(),
+                ));
+            }
+            _ => points,
+        };
+        let [start, via, end] = points.map(|pose| pose.position());
+        check_arc_points(start, via, end)?;
+        self.validate_speed(&params)?;
+        // 每个点都要有逆解；圆弧可能远离三个点，工作空间沿圆弧采样检查
+        let mut joint = self.current_state()?.joint;
+        for point in points {
+            let target = MotionTarget::Pose(point.into());
+            joint = self.validate_target(&params, &joint, &target, MoveMode::Linear)?;
+        }
+        if !self.workspace.is_empty() {
+            let user_frame = pose_to_iso(&params.user_frame.pose);
+            let [start, via, end] =
+                [start, via, end].map(|p| (user_frame * Point3::from(p)).coords.into());
+            for point in arc_samples(start, via, end, turns.is_some()) {
+                self.workspace.check(point)?;
+            }
+        }
+        if turns.is_some_and(|turns| turns <= 0. || !turns.is_finite()) {
+            return Err(RobotException::InvalidInstruction(
+                "full circle needs a positive number of turns".into(),
+            ));
+        }
+        self.check_command(Command::MoveC)?;
+        self.apply_frames(&params)?;
+        self.begin_motion();
+        params.send_arc(
+            &mut self.robot_impl,
+            points.map(Into::into),
+            orientation,
+            turns,
+        )
+    }
 }
 
-impl<T: HansType, const N: usize> MoveTo<JointSpace<N>> for HansRobot<T, N>
+impl<T: HansType, const N: usize> HansRobot<T, N>
 where
-    HansRobot<T, N>: Joints<N>,
+    HansRobot<T, N>: Joints<N> + EndPoint + DhParameters<N>,
 {
-    fn move_to(&mut self, target: [f64; N]) -> RobotResult<()> {
-        if self.is_moving()? {
-            return Err(RobotException::UnprocessableInstructionError(
-                "Robot is moving, you can not push new move command".into(),
-            ));
+    /// 由机型的 DH 参数与关节限位构造的运动学模型，可以离线规划与校验位姿
+    pub fn kinematics(&self) -> Kinematics<N> {
+        Kinematics::new(Self::DH, Self::JOINT_MIN, Self::JOINT_MAX)
+    }
+
+    /// 运动的起点，缓存的状态不超过 [`RELATIVE_STATE_MAX_AGE`] 且晚于上一次下发运动时直接使用，
+    /// 否则重新读取
+    fn current_state(&mut self) -> RobotResult<CachedState<N>> {
+        let after_motion =
+            |state: &CachedState<N>| self.motion_started.is_none_or(|t| state.timestamp > t);
+        match self.cached_state() {
+            Some(state) if state.is_fresh(RELATIVE_STATE_MAX_AGE) && after_motion(&state) => {
+                Ok(state)
+            }
+            _ => self.refresh_state_cache(),
         }
+    }
+
+    /// 标记运动已下发，此前缓存的状态不再作为下一次运动的起点
+    fn begin_motion(&mut self) {
         self.is_moving = true;
-        match self.coord.get() {
-            Coord::OCS => {
-                let move_config = WayPointEx {
-                    joint: target,
-                    vel: 25.,
-                    acc: 100.,
-                    radius: 5.,
-                    move_mode: 0,
-                    use_joint: true,
-                    command_id: "0".into(),
-                    ..WayPointEx::default()
-                };
-                self.robot_impl.move_way_point_ex((0, move_config))?;
+        self.motion_started = Some(Instant::now());
+    }
+
+    fn validate_speed(&self, params: &MotionParams<N>) -> RobotResult<()> {
+        check_joint_vel(&params.joint_vel, &Self::JOINT_VEL_BOUND)?;
+        check_joint_acc(&params.joint_acc, &Self::JOINT_ACC_BOUND)?;
+        check_cartesian_vel(params.cartesian_vel, Self::CARTESIAN_VEL_BOUND)?;
+        check_cartesian_acc(params.cartesian_acc, Self::CARTESIAN_ACC_BOUND)?;
+        Ok(())
+    }
+
+    /// 工具末端在基坐标系中的位置，单位 mm
+    fn tcp_point(&self, params: &MotionParams<N>, joint: &[f64; N]) -> [f64; 3] {
+        let tcp = self.kinematics().forward_iso(joint) * pose_to_iso(&params.tool.pose);
+        tcp.translation.vector.into()
+    }
+
+    /// 检查从关节角 `from` 按 `mode` 插补运动到 `target` 的路径，返回目标的关节角
+    ///
+    /// 位姿目标在限位内求与 `from` 最近的逆解，无解时返回错误。关节插补在关节空间中
+    /// 每 [`JOINT_SAMPLE_STEP`] 采样一次，直线插补按直线采样，检查工具末端是否始终位于工作空间内；
+    /// 直线插补中间点的可达性由控制器检查。
+    fn validate_target(
+        &self,
+        params: &MotionParams<N>,
+        from: &[f64; N],
+        target: &MotionTarget<N>,
+        mode: MoveMode,
+    ) -> RobotResult<[f64; N]> {
+        let to = match target {
+            MotionTarget::Joint(joint) => {
+                check_joint_limits(joint, &Self::JOINT_MIN, &Self::JOINT_MAX)?;
+                *joint
             }
-            Coord::Inertial | Coord::Relative => {
-                for (id, joint) in target.iter().enumerate().take(N) {
-                    if *joint == 0. {
-                        continue;
-                    }
-                    let dir = *joint > 0.;
-                    let move_config = RelJ { id: id as u8, dir, dis: joint.abs() };
-                    self.robot_impl.move_joint_rel((0, move_config))?;
-                    return Ok(());
+            MotionTarget::Pose(pose) => {
+                let flange = pose_to_iso(&params.user_frame.pose)
+                    * pose_to_iso(pose)
+                    * pose_to_iso(&params.tool.pose).inverse();
+                self.kinematics()
+                    .nearest_solution(&iso_to_pose(&flange), from)?
+            }
+        };
+        if self.workspace.is_empty() {
+            return Ok(to);
+        }
+        match mode {
+            MoveMode::Joint => {
+                let span = (0..N).map(|i| (to[i] - from[i]).abs()).fold(0., f64::max);
+                let steps = (span / JOINT_SAMPLE_STEP).ceil().max(1.) as usize;
+                for k in 0..=steps {
+                    let t = k as f64 / steps as f64;
+                    let joint = std::array::from_fn(|i| from[i] + (to[i] - from[i]) * t);
+                    self.workspace.check(self.tcp_point(params, &joint))?;
                 }
             }
-            Coord::Other(_) => {
-                println!("undefined coord, use OCS as default");
+            MoveMode::Linear => (self.workspace)
+                .check_line(self.tcp_point(params, from), self.tcp_point(params, &to))?,
+        }
+        Ok(to)
+    }
+
+    /// 进入伺服模式，并在独立线程中按 `config.period` 推送回调给出的设定点
+    ///
+    /// 回调在伺服线程中执行，返回 `None` 时会话正常结束并退出伺服模式。每个设定点推送前
+    /// 都会检查关节限位、工作空间以及相对上一个设定点的关节速度与末端线速度。
+    /// 控制器的伺服指令只接受 servo_time 与 lookahead_time 两个参数，见 [`ServoConfig`]。
+    pub fn start_servo<F>(
+        &mut self,
+        config: ServoConfig,
+        callback: F,
+    ) -> RobotResult<ServoSession<N>>
+    where
+        F: FnMut(ServoTick) -> Option<ServoTarget<N>> + Send + 'static,
+    {
+        self.open_servo(config, Some(Box::new(callback)))
+    }
+
+    /// 进入伺服模式，设定点由 [`ServoSession::push`] 写入队列，伺服线程每个周期取出一个
+    ///
+    /// 队列为空的周期不推送，机器人停在上一个设定点。
+    pub fn start_servo_queue(&mut self, config: ServoConfig) -> RobotResult<ServoSession<N>> {
+        self.open_servo(config, None)
+    }
+
+    fn open_servo(
+        &mut self,
+        config: ServoConfig,
+        callback: Option<Box<dyn FnMut(ServoTick) -> Option<ServoTarget<N>> + Send>>,
+    ) -> RobotResult<ServoSession<N>> {
+        config.check()?;
+        let params = self.take_motion_params()?;
+        let host = (self.robot_impl.network.host())
+            .ok_or_else(|| RobotException::NetworkError("Robot is not connected".to_string()))?
+            .to_string();
+        self.check_command(Command::StartServo)?;
+        self.apply_frames(&params)?;
+        let last_joint = self.current_state()?.joint;
+        let guard = ServoGuard {
+            kinematics: self.kinematics(),
+            workspace: self.workspace.clone(),
+            joint_vel: Self::JOINT_VEL_BOUND,
+            cartesian_vel: Self::CARTESIAN_VEL_BOUND,
+            tool: params.tool.pose,
+            user_frame: params.user_frame.pose,
+            last_joint,
+            last_tcp: self.tcp_point(&params, &last_joint),
+        };
+
+        // 伺服线程使用独立的连接，断开后不重连，避免在重连期间积压设定点
+        let network_config = NetworkConfig {
+            heartbeat_interval: None,
+            auto_reconnect: false,
+            ..self.robot_impl.network.config().clone()
+        };
+        let port = self.robot_impl.network.port();
+        let connection = RobotImpl::new_with_config(&host, port, network_config)?;
+
+        self.robot_impl.start_servo((
+            0,
+            config.servo_time.as_secs_f64(),
+            config.lookahead_time.as_secs_f64(),
+        ))?;
+        let session = ServoSession::start(connection, guard, config, callback)?;
+        self.begin_motion();
+        self.servo = Some(session.handle());
+        Ok(session)
+    }
+}
+
+impl<T: HansType, const N: usize> MoveTo<JointSpace<N>> for HansRobot<T, N>
+where
+    HansRobot<T, N>: Joints<N> + EndPoint + DhParameters<N>,
+{
+    fn move_to(&mut self, target: [f64; N]) -> RobotResult<()> {
+        let coord = self.coord.get();
+        let params = self.take_motion_params()?;
+        self.validate_speed(&params)?;
+        let current = self.current_state()?.joint;
+        let motion_target = MotionTarget::Joint(target);
+        match coord {
+            Coord::Inertial | Coord::Relative => {
+                let absolute = MotionTarget::Joint(std::array::from_fn(|i| current[i] + target[i]));
+                self.validate_target(&params, &current, &absolute, MoveMode::Joint)?;
+            }
+            Coord::OCS | Coord::Other(_) => {
+                let mode = params.mode(&motion_target);
+                self.validate_target(&params, &current, &motion_target, mode)?;
             }
         }
+        self.check_command(match coord {
+            Coord::Inertial | Coord::Relative => Command::WayPointRel,
+            _ => params.command(&motion_target),
+        })?;
+        self.apply_frames(&params)?;
+        self.begin_motion();
+        match coord {
+            Coord::Inertial | Coord::Relative => {
+                params.send_relative(&mut self.robot_impl, target)?
+            }
+            Coord::OCS | Coord::Other(_) => params.send(&mut self.robot_impl, motion_target)?,
+        }
         Ok(())
     }
 }
 
 impl<T: HansType, const N: usize> MoveTo<FlangeSpace> for HansRobot<T, N>
 where
-    HansRobot<T, N>: EndPoint,
+    HansRobot<T, N>: Joints<N> + EndPoint + DhParameters<N>,
 {
     fn move_to(&mut self, target: Pose) -> RobotResult<()> {
-        if self.is_moving()? {
-            return Err(RobotException::UnprocessableInstructionError(
-                "Robot is moving, you can not push new move command".into(),
-            ));
-        }
-        self.is_moving = true;
-        match self.coord.get() {
-            Coord::OCS => {
-                let move_config = WayPointEx {
-                    pose: target.into(),
-                    vel: 25.,
-                    acc: 100.,
-                    radius: 5.,
-                    move_mode: 1,
-                    use_joint: false,
-                    command_id: "0".into(),
-                    ..WayPointEx::default()
-                };
-                self.robot_impl.move_way_point_ex((0, move_config))?;
-            }
+        let coord = self.coord.get();
+        let params = self.take_motion_params()?;
+        let pose: [f64; 6] = match coord {
+            Coord::Other(frame) => (frame * target).into(),
+            _ => target.into(),
+        };
+        self.validate_speed(&params)?;
+        let current = self.current_state()?.joint;
+        let (params, motion_target) = match coord {
             Coord::Inertial | Coord::Relative => {
-                let pose: [f64; 6] = target.into();
-                for (id, pose) in pose.iter().enumerate().take(N) {
-                    if *pose == 0. {
-                        continue;
-                    }
-                    let dir = *pose >= 0.;
-                    let move_config = RelL { id: id as u8, dir, dis: pose.abs(), coord: 0 };
-                    self.robot_impl.move_line_rel((0, move_config))?;
-                    return Ok(());
-                }
-            }
-            Coord::Other(_) => {
-                println!("undefined coord, use OCS as default");
+                // 增量为工具末端在工具坐标系或用户坐标系中的平移与旋转，换算为用户坐标系中的目标；
+                // 起点取控制器实测的法兰位姿，不受本地 DH 参数误差的影响
+                let flange = pose_to_iso(&self.read_flange_pose()?);
+                let tcp = pose_to_iso(&params.user_frame.pose).inverse()
+                    * flange
+                    * pose_to_iso(&params.tool.pose);
+                let delta = pose_to_iso(&pose);
+                let user_target = if coord == Coord::Relative {
+                    tcp * delta
+                } else {
+                    Isometry3::from_parts(
+                        tcp.translation * delta.translation,
+                        delta.rotation * tcp.rotation,
+                    )
+                };
+                let command = MotionCommand::WayPointEx(MoveMode::Linear);
+                (
+                    MotionParams { command, ..params },
+                    MotionTarget::Pose(iso_to_pose(&user_target)),
+                )
             }
+            Coord::OCS | Coord::Other(_) => (params, MotionTarget::Pose(pose)),
+        };
+        let mode = params.mode(&motion_target);
+        self.validate_target(&params, &current, &motion_target, mode)?;
+
+        // 只有一个方向的增量时使用 `MoveRelL`，否则下发换算后的绝对目标
+        let mut axes = (0..6).filter(|&i| pose[i] != 0.);
+        let single_axis = match (coord, axes.next(), axes.next()) {
+            (Coord::Inertial | Coord::Relative, Some(axis), None) => Some(axis),
+            _ => None,
+        };
+        self.check_command(match single_axis {
+            Some(_) => Command::MoveRelL,
+            None => params.command(&motion_target),
+        })?;
+        self.apply_frames(&params)?;
+        self.begin_motion();
+        match single_axis {
+            Some(axis) => params.send_relative_line(
+                &mut self.robot_impl,
+                axis,
+                pose[axis],
+                coord == Coord::Relative,
+            ),
+            None => params.send(&mut self.robot_impl, motion_target),
         }
-        Ok(())
+    }
+}
+
+impl<T: HansType, const N: usize> MoveCircular<FlangeSpace> for HansRobot<T, N>
+where
+    HansRobot<T, N>: Joints<N> + EndPoint + DhParameters<N>,
+{
+    fn move_arc(&mut self, start: Pose, via: Pose, end: Pose) -> RobotResult<()> {
+        self.move_c([start, via, end], None)
+    }
+
+    fn move_circle(&mut self, start: Pose, via: Pose, end: Pose, turns: f64) -> RobotResult<()> {
+        self.move_c([start, via, end], Some(turns))
     }
 }
 
 impl<T: HansType, const N: usize> MoveTraj<JointSpace<N>> for HansRobot<T, N>
 where
-    HansRobot<T, N>: Joints<N>,
+    HansRobot<T, N>: Joints<N> + EndPoint + DhParameters<N>,
 {
     fn move_traj(&mut self, path: Vec<[f64; N]>) -> RobotResult<()> {
         <Self as MoveTraj<JointSpace<N>>>::move_waypoints(self, path)
@@ -350,19 +1105,24 @@ where
         if path.is_empty() {
             return Ok(());
         }
-        if self.is_moving()? {
-            return Err(RobotException::UnprocessableInstructionError(
-                "Robot is moving, you can not push new move command".into(),
-            ));
+        self.check_command(Command::MovePath)?;
+        let params = self.take_motion_params()?;
+        self.validate_speed(&params)?;
+        let approach = MotionTarget::Joint(path[0]);
+        let mut joint = self.current_state()?.joint;
+        joint = self.validate_target(&params, &joint, &approach, MoveMode::Joint)?;
+        for target in &path[1..] {
+            let target = MotionTarget::Joint(*target);
+            joint = self.validate_target(&params, &joint, &target, MoveMode::Joint)?;
         }
-        self.is_moving = true;
-
-        <Self as MoveTo<JointSpace<N>>>::move_to(self, path[0])?;
+        self.apply_frames(&params)?;
+        params.send_approach(&mut self.robot_impl, approach)?;
+        self.begin_motion();
 
         let path_name = "my_path";
         let path_config = StartPushMovePathJ {
             path_name: path_name.into(),
-            speed: self.max_vel.get()[0] / Self::JOINT_VEL_BOUND[0],
+            speed: params.speed_ratio(&Self::JOINT_VEL_BOUND),
             radius: 2.,
         };
         self.robot_impl.start_push_move_path_j((0, path_config))?;
@@ -371,7 +1131,11 @@ where
                 .push_move_path_j((0, path_name.into(), joint))?;
         }
         self.robot_impl.end_push_move_path((0, path_name.into()))?;
-        wait_move_path_ready(&mut self.robot_impl, path_name)?;
+        wait_move_path_ready(&mut self.robot_impl, path_name, &self.wait_policy)?;
+        // 控制器在运动中拒绝 MovePath，先等待接近起点的运动结束
+        let policy = self.wait_policy.clone();
+        self.wait_for_motion(&policy)?.into_result()?;
+        self.begin_motion();
         self.robot_impl.move_path_j((0, path_name.into()))?;
         Ok(())
     }
@@ -379,7 +1143,7 @@ where
 
 impl<T: HansType, const N: usize> MoveTraj<FlangeSpace> for HansRobot<T, N>
 where
-    HansRobot<T, N>: EndPoint,
+    HansRobot<T, N>: Joints<N> + EndPoint + DhParameters<N>,
 {
     fn move_traj(&mut self, path: Vec<Pose>) -> RobotResult<()> {
         <Self as MoveTraj<FlangeSpace>>::move_waypoints(self, path)
@@ -398,23 +1162,29 @@ where
         if path.is_empty() {
             return Ok(());
         }
-        if self.is_moving()? {
-            return Err(RobotException::UnprocessableInstructionError(
-                "Robot is moving, you can not push new move command".into(),
-            ));
+        self.check_command(Command::MovePathL)?;
+        let params = self.take_motion_params()?;
+        self.validate_speed(&params)?;
+        let approach = MotionTarget::Pose(path[0].into());
+        let mut joint = self.current_state()?.joint;
+        joint = self.validate_target(&params, &joint, &approach, MoveMode::Joint)?;
+        for point in &path[1..] {
+            let target = MotionTarget::Pose((*point).into());
+            joint = self.validate_target(&params, &joint, &target, MoveMode::Linear)?;
         }
-        self.is_moving = true;
-
-        <Self as MoveTo<FlangeSpace>>::move_to(self, path[0])?;
+        self.apply_frames(&params)?;
+        params.send_approach(&mut self.robot_impl, approach)?;
+        self.begin_motion();
 
         let path_name = "my_path";
+        let (vel, acc) = params.speed(MoveMode::Linear);
         let path_config = StartPushMovePathL {
             path_name: path_name.into(),
-            vel: 100.,
-            acc: 2500.,
+            vel,
+            acc,
             jeck: 1_000_000.,
-            ucs_name: "Base".into(),
-            tcp_name: "Tcp".into(),
+            ucs_name: DEFAULT_UCS_NAME.into(),
+            tcp_name: DEFAULT_TCP_NAME.into(),
         };
         self.robot_impl.start_push_move_path_l((0, path_config))?;
         for point in path {
@@ -423,27 +1193,123 @@ where
             self.robot_impl.push_move_path_l((0, pose))?;
         }
         self.robot_impl.end_push_move_path((0, path_name.into()))?;
-        wait_move_path_ready(&mut self.robot_impl, path_name)?;
+        wait_move_path_ready(&mut self.robot_impl, path_name, &self.wait_policy)?;
+        // 控制器在运动中拒绝 MovePath，先等待接近起点的运动结束
+        let policy = self.wait_policy.clone();
+        self.wait_for_motion(&policy)?.into_result()?;
+        self.begin_motion();
         self.robot_impl.move_path_l((0, path_name.into()))?;
         Ok(())
     }
 }
 
+/// 由推送的状态构造 [`ArmState`]，推送数据中不包含末端速度
+fn arm_state_from_stream<const N: usize>(state: &RobotState) -> ArmState<N> {
+    let pos_and_vel = &state.pos_and_vel;
+    let pose = pos_and_vel.pose_o_to_ee;
+    ArmState {
+        joint: StateView::from_meas(JointSample {
+            q: Some(std::array::from_fn(|i| pos_and_vel.position[i])),
+            dq: Some(std::array::from_fn(|i| pos_and_vel.joint_velocity[i])),
+            ddq: Some(std::array::from_fn(|i| pos_and_vel.joint_acceleration[i])),
+            tau: None,
+            dtau: None,
+        }),
+        flange: StateView::from_meas(SpatialSample {
+            pose: Some(Pose::Euler(
+                [pose[0], pose[1], pose[2]],
+                [pose[3], pose[4], pose[5]],
+            )),
+            vel: None,
+            acc: None,
+            wrench: None,
+        }),
+        load: None,
+        ..Default::default()
+    }
+}
+
+/// 状态流中的最新一帧超过该时间时视为过期，改为主动读取
+const STREAM_STATE_MAX_AGE: Duration = Duration::from_millis(200);
+/// 增量运动校验时可以直接使用的缓存状态的最长时间
+const RELATIVE_STATE_MAX_AGE: Duration = Duration::from_millis(100);
+/// 检查关节插补路径时相邻采样点的最大关节角间距，单位 °
+const JOINT_SAMPLE_STEP: f64 = 0.1;
+/// 急停恢复时轮询状态机的间隔
+const RECOVERY_POLL_INTERVAL: Duration = Duration::from_millis(50);
+/// 急停恢复的总超时
+const RECOVERY_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// 恢复过程中的一步：指令名与对应的操作
+type RecoveryStep<const N: usize> = (&'static str, fn(&mut RobotImpl<N>) -> RobotResult<()>);
+
+/// 按照状态机逐步恢复到 [`RobotMode::StandBy`]，状态与动作的对应关系见
+/// [`clear_emergency_stop`](Robot::clear_emergency_stop)
+fn recover_to_standby<const N: usize>(
+    robot_impl: &mut RobotImpl<N>,
+    timeout: Duration,
+) -> RobotResult<()> {
+    let deadline = Instant::now() + timeout;
+    let mut handled = None;
+    loop {
+        let mode = robot_impl.state_read_cur_fsm(0)?;
+        let step: Option<RecoveryStep<N>> = match mode {
+            RobotMode::StandBy => return Ok(()),
+            mode if mode.requires_reset() => Some(("GrpReset", |r| r.robot_reset(0))),
+            RobotMode::ElectricBoxDisconnect => Some(("ConnectToBox", |r| r.connect_to_box(()))),
+            RobotMode::Blackout48V => Some(("Electrify", |r| r.robot_power_on(()))),
+            RobotMode::ControllerDisconnect => {
+                Some(("StartMaster", |r| r.connect_to_controller(())))
+            }
+            RobotMode::Disable => Some(("GrpEnable", |r| r.robot_enable(0))),
+            _ => None,
+        };
+        if let Some((name, action)) = step
+            && handled != Some(mode)
+        {
+            handled = Some(mode);
+            action(robot_impl).map_err(|e| {
+                RobotException::UnprocessableInstructionError(format!(
+                    "emergency stop recovery failed: {name} in {mode:?}: {e}"
+                ))
+            })?;
+            continue;
+        }
+        if Instant::now() >= deadline {
+            return Err(RobotException::UnprocessableInstructionError(format!(
+                "emergency stop recovery timed out in {mode:?}"
+            )));
+        }
+        sleep(RECOVERY_POLL_INTERVAL);
+    }
+}
+
+/// 等待推送的路径计算完成，轮询间隔与超时取自等待策略
 fn wait_move_path_ready<const N: usize>(
     robot_impl: &mut RobotImpl<N>,
     path_name: &str,
+    policy: &WaitPolicy,
 ) -> RobotResult<()> {
+    let started = Instant::now();
     loop {
         let state = robot_impl.read_move_path_state((0, path_name.into()))?;
         match state {
-            3 => break,
+            3 => return Ok(()),
             5 => {
                 return Err(RobotException::UnprocessableInstructionError(
                     "Connot calculate path, Check whether the points are appropriate".into(),
                 ));
             }
-            _ => sleep(Duration::from_millis(100)),
+            _ if policy
+                .timeout
+                .is_some_and(|timeout| started.elapsed() >= timeout) =>
+            {
+                return Err(RobotException::UnprocessableInstructionError(format!(
+                    "path {path_name} was not ready after {:.3}s",
+                    started.elapsed().as_secs_f64()
+                )));
+            }
+            _ => sleep(policy.poll_interval),
         }
     }
-    Ok(())
 }
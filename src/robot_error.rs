@@ -1,35 +1,174 @@
 use std::fmt::Display;
 
 use robot_behavior::RobotException;
-use serde_repr::{Deserialize_repr, Serialize_repr};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
-#[derive(Default, Debug, Serialize_repr, Deserialize_repr, PartialEq)]
-#[repr(u16)]
-pub enum RobotError {
+/// 错误类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorCategory {
+    /// 没有错误
+    None,
+    /// 运动执行相关，例如在运动中下发了新的运动指令
+    Motion,
+    /// 指令参数不合法
+    Parameter,
+    /// 指令格式或指令名无法识别
+    Command,
+    /// 控制器状态相关，例如控制器尚未初始化
+    Controller,
+    /// 客户端与控制器之间的通信
+    Communication,
+    /// 目录中没有收录的错误码
+    Unknown,
+}
+
+/// 错误的严重程度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ErrorSeverity {
+    /// 仅提示，不影响后续指令
+    Info,
+    /// 当前指令被拒绝，修正后可以重试
+    Warning,
+    /// 机器人停止运行，需要复位后才能继续
+    Error,
+    /// 需要人工检查硬件或重启控制器
+    Fatal,
+}
+
+/// 错误码目录
+///
+/// 每一行依次为：变体、错误码、类别、严重程度、中文描述、英文描述、处理建议。
+/// 目录只收录 SDK 中已经确认含义的错误码，新增条目必须注明出处。
+/// 伺服驱动器的错误码见 [`AxisFault`](crate::AxisFault)。
+macro_rules! error_table {
+    ($(
+        $(#[$attr:meta])*
+        $variant:ident = $code:literal, $category:ident, $severity:ident,
+        $zh:literal, $en:literal, $remedy:literal;
+    )*) => {
+        /// 控制器返回的错误码
+        ///
+        /// 这还不是完整的汉斯控制器错误码表：运动、参数、控制器、安全与伺服报警的完整编码表
+        /// 尚未取得，目前只收录了已经确认含义的几个错误码。其余错误码保存在
+        /// [`RobotError::Unknown`] 中，序列化时原样写回，不会丢失。
+        #[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum RobotError {
+            $($(#[$attr])* $variant,)*
+            /// 目录中没有收录的错误码
+            Unknown(u16),
+        }
+
+        impl RobotError {
+            /// 目录中收录的全部错误
+            pub const CATALOGUE: &'static [RobotError] = &[$(RobotError::$variant),*];
+
+            /// 错误码
+            pub fn code(&self) -> u16 {
+                match self {
+                    $(RobotError::$variant => $code,)*
+                    RobotError::Unknown(code) => *code,
+                }
+            }
+
+            /// 错误类别
+            pub fn category(&self) -> ErrorCategory {
+                match self {
+                    $(RobotError::$variant => ErrorCategory::$category,)*
+                    RobotError::Unknown(_) => ErrorCategory::Unknown,
+                }
+            }
+
+            /// 严重程度
+            pub fn severity(&self) -> ErrorSeverity {
+                match self {
+                    $(RobotError::$variant => ErrorSeverity::$severity,)*
+                    RobotError::Unknown(_) => ErrorSeverity::Error,
+                }
+            }
+
+            /// 中文描述
+            pub fn description_zh(&self) -> &'static str {
+                match self {
+                    $(RobotError::$variant => $zh,)*
+                    RobotError::Unknown(_) => "未收录的控制器错误",
+                }
+            }
+
+            /// 英文描述
+            pub fn description_en(&self) -> &'static str {
+                match self {
+                    $(RobotError::$variant => $en,)*
+                    RobotError::Unknown(_) => "Unlisted controller error",
+                }
+            }
+
+            /// 处理建议
+            pub fn remediation(&self) -> &'static str {
+                match self {
+                    $(RobotError::$variant => $remedy,)*
+                    RobotError::Unknown(_) => "查阅控制器手册中对应的错误码，或在示教器上查看报警详情",
+                }
+            }
+        }
+
+        impl From<u16> for RobotError {
+            fn from(code: u16) -> Self {
+                match code {
+                    $($code => RobotError::$variant,)*
+                    code => RobotError::Unknown(code),
+                }
+            }
+        }
+    };
+}
+
+error_table! {
     #[default]
-    NoError,
-    NoNameError,
+    NoError = 0, None, Info,
+        "没有错误", "No error", "无需处理";
+    NoNameError = 1, Unknown, Error,
+        "未命名的错误", "Unnamed error", "查看示教器上的报警详情";
 
-    RECOnMoving = 20004,
+    RECOnMoving = 20004, Motion, Warning,
+        "机器人正在运动，拒绝新的运动指令", "Robot is moving",
+        "等待当前运动结束，或先停止运动再下发指令";
 
-    ControllerNotInit = 40000,
-    RECParametersError = 40034,
-    RECCmdFormatError = 40056,
+    ControllerNotInit = 40000, Controller, Warning,
+        "控制器未初始化", "Controller not initialized",
+        "依次执行连接电箱、上电、启动主站与使能后再下发指令";
+    RECParametersError = 40034, Parameter, Warning,
+        "指令参数错误", "Invalid command parameters",
+        "检查参数的数量、取值范围以及目标点是否可达";
+    RECCmdFormatError = 40056, Command, Warning,
+        "指令格式错误", "Invalid command format",
+        "检查指令名称与参数分隔符，确认控制器版本支持该指令";
 
-    IoError = 65535,
+    IoError = 65535, Communication, Error,
+        "通信错误", "Io error",
+        "检查网络连接，必要时重新连接控制器";
+}
+
+impl Serialize for RobotError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u16(self.code())
+    }
+}
+
+impl<'de> Deserialize<'de> for RobotError {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        u16::deserialize(deserializer).map(RobotError::from)
+    }
+}
+
+impl From<RobotError> for u16 {
+    fn from(error: RobotError) -> Self {
+        error.code()
+    }
 }
 
 impl Display for RobotError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            RobotError::NoError => write!(f, "No error"),
-            RobotError::NoNameError => write!(f, "No name error"),
-            RobotError::RECOnMoving => write!(f, "REC on moving"),
-            RobotError::ControllerNotInit => write!(f, "Controller not init"),
-            RobotError::RECParametersError => write!(f, "REC parameters error"),
-            RobotError::RECCmdFormatError => write!(f, "REC cmd format error"),
-            RobotError::IoError => write!(f, "Io error"),
-        }
+        write!(f, "{} ({})", self.description_en(), self.code())
     }
 }
 
@@ -38,3 +177,23 @@ impl From<RobotError> for RobotException {
         RobotException::UnprocessableInstructionError(e.to_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_robot_error_catalogue() {
+        for error in RobotError::CATALOGUE {
+            assert_eq!(RobotError::from(error.code()), *error);
+        }
+        assert_eq!(RobotError::from(40034), RobotError::RECParametersError);
+        assert_eq!(RobotError::RECOnMoving.category(), ErrorCategory::Motion);
+
+        let unknown: RobotError = serde_json::from_str("41234").unwrap();
+        assert_eq!(unknown, RobotError::Unknown(41234));
+        assert_eq!(unknown.category(), ErrorCategory::Unknown);
+        assert_eq!(serde_json::to_string(&unknown).unwrap(), "41234");
+        assert_eq!(unknown.to_string(), "Unlisted controller error (41234)");
+    }
+}
@@ -1,6 +1,6 @@
 use robot_behavior::RobotResult;
 
-use crate::{Network, RobotMode, types::*};
+use crate::{Network, NetworkConfig, RobotMode, types::*};
 
 #[derive(Default)]
 pub struct RobotImpl<const N: usize> {
@@ -38,13 +38,13 @@ macro_rules! cmd_fn {
     ($fn_name:ident, $req_type:ty, $res_type:ty;; $ret_type:ty) => {
         pub fn $fn_name(&mut self, _:()) -> RobotResult<$ret_type> {
             let response: $res_type = self.network.send_and_recv(&<$req_type>::from(()))?;
-            Ok(response.status.unwrap())
+            response.status.map_err(Into::into)
         }
     };
     ($fn_name:ident, $req_type:ty, $res_type:ty; $($arg_name:ident: $arg_type:ty),*) => {
         pub fn $fn_name(&mut self, $($arg_name: $arg_type),*) -> RobotResult<()> {
-            let _: $res_type = self.network.send_and_recv(&<$req_type>::from($($arg_name),*))?;
-            Ok(())
+            let response: $res_type = self.network.send_and_recv(&<$req_type>::from($($arg_name),*))?;
+            response.status.map_err(Into::into)
         }
     };
     ($fn_name:ident, $req_type:ty, $res_type:ty; $($arg_name:ident: $arg_type:ty),* ; $ret_type:ty) => {
@@ -62,13 +62,13 @@ macro_rules! cmd_fn {
     ($fn_name:ident<$const_ty:ty; $const_name:ident>, $req_type:ty, $res_type:ty;;  $ret_type:ty) => {
         pub fn $fn_name<const $const_name: usize>(&mut self, argc: [$const_ty; $const_name]) -> RobotResult<$ret_type> {
             let response: $res_type = self.network.send_and_recv(&<$req_type>::from(argc))?;
-            Ok(response.status.unwrap())
+            response.status.map_err(Into::into)
         }
     };
     ($fn_name:ident<$const_name:ident>, $req_type:ty, $res_type:ty; $($arg_name:ident: $arg_type:ty),*) => {
         pub fn $fn_name<const $const_name: usize>(&mut self, $($arg_name: $arg_type),*) -> RobotResult<()> {
-            let _: $res_type = self.network.send_and_recv(&<$req_type>::from($($arg_name),*))?;
-            Ok(())
+            let response: $res_type = self.network.send_and_recv(&<$req_type>::from($($arg_name),*))?;
+            response.status.map_err(Into::into)
         }
     };
     ($fn_name:ident<$const_name:ident>, $req_type:ty, $res_type:ty; $($arg_name:ident: $arg_type:ty),* ; $ret_type:ty) => {
@@ -79,21 +79,170 @@ macro_rules! cmd_fn {
     };
 }
 
+/// 指令表，对每一条指令调用 `$m!`，同步与异步客户端都由这张表生成
+macro_rules! for_each_command {
+    ($m:ident) => {
+        $m!(connect_to_box, ConnectToBoxRequest, ConnectToBoxResponse);
+        $m!(robot_power_on, ElectrifyRequest, ElectrifyResponse);
+        $m!(robot_power_off, BlackOutRequest, BlackOutResponse);
+        $m!(
+            connect_to_controller,
+            StartMasterRequest,
+            StartMasterResponse
+        );
+        $m!(
+            disconnect_from_controller,
+            CloseMasterRequest,
+            CloseMasterResponse
+        );
+        $m!(is_simulation, IsSimulationRequest, IsSimulationResponse;; bool);
+        $m!(is_controller_started, ReadControllerStateRequest, ReadControllerStateResponse;; bool);
+
+        // ! 机器人轴组控制指令
+
+        $m!(robot_model, ReadRobotModelRequest, ReadRobotModelResponse; id: u8; u16);
+        $m!(robot_enable, GrpEnableRequest, GrpEnableResponse; id: u8);
+        $m!(robot_disable, GrpDisableRequest, GrpDisableResponse; id: u8);
+        $m!(robot_reset, GrpResetRequest, GrpResetResponse; id: u8);
+        $m!(robot_move_stop, GrpStopRequest, GrpStopResponse; id: u8);
+        $m!(robot_move_pause, GrpInterruptRequest, GrpInterruptResponse; id: u8);
+        $m!(robot_move_continue, GrpContinueRequest, GrpContinueResponse; id: u8);
+        $m!(robot_free_driver_open, GrpOpenFreeDriverRequest, GrpOpenFreeDriverResponse; id: u8);
+        $m!(robot_free_driver_close, GrpCloseFreeDriverRequest, GrpCloseFreeDriverResponse; id: u8);
+
+        // ! 电箱控制指令
+        $m!(box_info, ReadBoxInfoRequest, ReadBoxInfoResponse; id: u8; BoxInfo);
+        $m!(box_control_input<bool; M>, ReadBoxCIRequest<M>, ReadBoxCIResponse<M>);
+        $m!(box_control_output<bool; M>, ReadBoxCORequest<M>, ReadBoxCOResponse<M>);
+        $m!(box_digital_input<bool; M>, ReadBoxDIRequest<M>, ReadBoxDIResponse<M>);
+        $m!(box_digital_output<bool; M>, ReadBoxDORequest<M>, ReadBoxDOResponse<M>);
+        $m!(box_analog_input<f64; M>, ReadBoxAIRequest<M>, ReadBoxAIResponse<M>);
+        $m!(box_analog_output<f64; M>, ReadBoxAORequest<M>, ReadBoxAOResponse<M>);
+        $m!(box_end_digital_input<M>, ReadEIRequest<M>, ReadEIResponse<M>; id_port: (u8,[u8;M]); [bool;M]);
+        $m!(box_end_digital_output<M>, ReadEORequest<M>, ReadEOResponse<M>; id_port: (u8,[u8;M]); [bool;M]);
+        $m!(box_end_analog_input, ReadEAIRequest, ReadEAIResponse; id: (u8,u8); f64);
+        $m!(box_set_control_output, SetBoxCORequest, SetBoxCOResponse; id_out: (u8,bool));
+        $m!(box_set_digital_output, SetBoxDORequest, SetBoxDOResponse; id_out: (u8,bool));
+        $m!(box_set_analog_output_mode, SetBoxAOModeRequest, SetBoxAOModeResponse; id_mode: (u8,u8));
+        $m!(box_set_analog_output, SetBoxAORequest, SetBoxAOResponse; id_out_mode: (u8,f64,u8));
+        $m!(box_set_end_digital_output, SetEndDORequest, SetEndDOResponse; id_out: (u8,u8,bool));
+
+        // ! 机器人状态指令
+        $m!(state_set_override, SetOverrideRequest, SetOverrideResponse; id_value: (u8,f64));
+        $m!(state_set_tool_motion, SetToolMotionRequest, SetToolMotionResponse; id_value: (u8,bool));
+        $m!(state_set_payload, SetPayloadRequest, SetPayloadResponse; id_value: (u8,Load));
+        $m!(state_set_joint_max_vel, SetJointMaxVelRequest::<N>, SetJointMaxVelResponse; id_value: (u8,[f64;N]));
+        $m!(state_set_joint_max_acc, SetJointMaxAccRequest::<N>, SetJointMaxAccResponse; id_value: (u8,[f64;N]));
+        $m!(state_set_linear_max_vel, SetLinearMaxVelRequest, SetLinearMaxVelResponse; id_value: (u8,f64));
+        $m!(state_set_linear_max_acc, SetLinearMaxAccRequest, SetLinearMaxAccResponse; id_value: (u8,f64));
+        $m!(state_read_joint_max_vel, ReadJointMaxVelRequest, ReadJointMaxVelResponse::<N>; id: u8; [f64;N]);
+        $m!(state_read_joint_max_acc, ReadJointMaxAccRequest, ReadJointMaxAccResponse::<N>; id: u8; [f64;N]);
+        $m!(state_read_joint_max_jerk, ReadJointMaxJerkRequest, ReadJointMaxJerkResponse::<N>; id: u8; [f64;N]);
+        $m!(state_read_linear_max_vel, ReadLinearMaxVelRequest, ReadLinearMaxVelResponse::<N>; id: u8; [f64;N]);
+        $m!(state_read_emergency_info, ReadEmergencyInfoRequest, ReadEmergencyInfoResponse::<N>; id: u8; EmergencyInfo);
+        $m!(state_read_robot_state, ReadRobotStateRequest, ReadRobotStateResponse; id: u8; RobotFlag);
+        $m!(state_read_axis_error_code, ReadAxisErrorCodeRequest, ReadAxisErrorCodeResponse::<N>; id: u8; [u16;N]);
+        $m!(state_read_cur_fsm, ReadCurFSMRequest, ReadCurFSMResponse; id: u8; RobotMode);
+        $m!(state_read_cmd_pos, ReadCmdPosRequest, ReadCmdPosResponse::<N>; id: u8; CmdPose::<N>);
+        $m!(state_read_act_pos, ReadActPosRequest, ReadActPosResponse::<N>; id: u8; ActPose::<N>);
+        $m!(state_read_cmd_joint_vel, ReadCmdJointVelRequest, ReadCmdJointVelResponse::<N>; id: u8; [f64;N]);
+        $m!(state_read_act_joint_vel, ReadActJointVelRequest, ReadActJointVelResponse::<N>; id: u8; [f64;N]);
+        $m!(state_read_cmd_tcp_vel, ReadCmdTcpVelRequest, ReadCmdTcpVelResponse; id: u8; [f64; 6]);
+        $m!(state_read_act_tcp_vel, ReadActTcpVelRequest, ReadActTcpVelResponse; id: u8; [f64; 6]);
+        $m!(state_read_cmd_joint_cur, ReadCmdJointCurRequest, ReadCmdJointCurResponse::<N>; id: u8; [f64;N]);
+        $m!(state_read_act_joint_cur, ReadActJointCurRequest, ReadActJointCurResponse::<N>; id: u8; [f64;N]);
+        $m!(state_read_tcp_vel, ReadTcpVelocityRequest, ReadTcpVelocityResponse; id: u8; (f64,f64));
+
+        // ! 坐标系读写指令
+        $m!(set_pose_o_to_t, SetCurTCPRequest, SetCurTCPResponse; id_pose: (u8,[f64;6]));
+        $m!(set_pose_u_to_t, SetCurUCSRequest, SetCurUCSResponse; id_pose: (u8,[f64;6]));
+        $m!(read_pose_o_to_t, ReadCurTCPRequest, ReadCurTCPResponse; id_pose: u8; [f64;6]);
+        $m!(read_pose_u_to_t, ReadCurUCSRequest, ReadCurUCSResponse; id_pose: u8; [f64;6]);
+
+        // ! 力控指令
+        $m!(force_control, SetForceControlStateRequest, SetForceControlStateResponse; id_state: (u8,bool));
+        $m!(force_control_mode, ReadFTControlStateRequest, ReadFTControlStateResponse; id: u8; bool);
+        $m!(force_tool_coord, SetForceToolCoordinateMotionRequest, SetForceToolCoordinateMotionResponse; id_mode: (u8,bool));
+        $m!(force_interrupt, GrpFCInterruptRequest, GrpFCInterruptResponse; id: u8);
+        $m!(force_continue, GrpFCContinueRequest, GrpFCContinueResponse; id: u8);
+        $m!(force_zero, SetForceZeroRequest, SetForceZeroResponse; id: u8);
+        $m!(force_max_search_vel, HRSetMaxSearchVelocitiesRequest, HRSetMaxSearchVelocitiesResponse; id_v_w: (u8,f64,f64));
+        $m!(force_control_strategy, HRSetForceControlStrategyRequest, HRSetForceControlStrategyResponse; id_mode: (u8,u8));
+        $m!(force_set_senor_pose_f_to, SetFTPositionRequest, SetFTPositionResponse; id_pos: (u8,[f64;6]));
+        $m!(force_pid_control_params, HRSetPIDControlParamsRequest::<N>, HRSetPIDControlParamsResponse; id_params: (u8,[f64; N]));
+        $m!(force_mass_params, HRSetMassParamsRequest, HRSetMassParamsResponse; id_params: (u8,[f64;6]));
+        $m!(force_damp_params, HRSetDampParamsRequest, HRSetDampParamsResponse; id_params: (u8,[f64;6]));
+        $m!(force_stiff_params, HRSetStiffParamsRequest, HRSetStiffParamsResponse; id_params: (u8,[f64;6]));
+        $m!(force_control_goal, HRSetControlGoalRequest, HRSetControlGoalResponse; id_goal: (u8,[f64;6],f64));
+        $m!(force_free_drive, SetForceFreeDriveModeRequest, SetForceFreeDriveModeResponse; id_mode: (u8,bool));
+        $m!(force_senor_data, ReadFTCabDataRequest, ReadFTCabDataResponse; id: u8; [f64;6]);
+
+        // ! 运动生成指令
+        $m!(move_joint_rel, MoveRelJRequest, MoveRelJResponse; id_dir_dis: (u8, RelJ));
+        $m!(move_line_rel, MoveRelLRequest, MoveRelLResponse; id_dir_dis_coord: (u8, RelL));
+        $m!(move_way_point_rel, WayPointRelRequest::<N>, WayPointRelResponse; id_way_point_rel: (u8, WayPointRel::<N>));
+        $m!(move_way_point_ex, WayPointExRequest::<N>, WayPointExResponse; id_way_point_ex: (u8, WayPointEx::<N>));
+        $m!(move_way_point, WayPointRequest::<N>, WayPointResponse; id_way_point: (u8, WayPoint::<N>));
+        $m!(move_way_point2, WayPoint2Request::<N>, WayPoint2Response; id_way_point2: (u8, WayPoint2::<N>));
+        $m!(move_joint, MoveJRequest::<N>, MoveJResponse; id_joint: (u8, MoveJ::<N>));
+        $m!(move_line, MoveLRequest::<N>, MoveLResponse; id_line: (u8, MoveL::<N>));
+        $m!(move_circle, MoveCRequest, MoveCResponse; id_circle: (u8, MoveC));
+        $m!(start_push_move_path_j, StartPushMovePathRequest, StartPushMovePathResponse; id_config: (u8, StartPushMovePathJ));
+        $m!(push_move_path_j, PushMovePathJRequest::<N>, PushMovePathJResponse; id_path: (u8, String, [f64;N]));
+        $m!(end_push_move_path, EndPushMovePathRequest, EndPushMovePathResponse; id_path: (u8, String));
+        $m!(move_path_j, MovePathRequest, MovePathResponse; id_path: (u8, String));
+        $m!(read_move_path_state, ReadMovePathStateRequest, ReadMovePathStateResponse; id_path: (u8, String); u8);
+        $m!(update_move_path_name, UpdateMovePathNameRequest, UpdateMovePathNameResponse; id_path_new: (u8, String, String));
+        $m!(del_move_path, DelMovePathRequest, DelMovePathResponse; id_path: (u8, String));
+        $m!(read_soft_motion_process, ReadSoftMotionProcessRequest, ReadSoftMotionProcessResponse; id:u8; (f64, u16));
+        $m!(start_push_move_path_l, InitMovePathLRequest, InitMovePathLResponse; id_config: (u8, StartPushMovePathL));
+        $m!(push_move_path_l, PushMovePathLRequest, PushMovePathLResponse; id_path: (u8, [f64;6]));
+        $m!(push_move_paths, PushMovePathsRequest<N>, PushMovePathsResponse; id_paths: (u8, MovePaths<N>));
+        $m!(move_path_l, MovePathLRequest, MovePathLResponse; id_path: (u8, String));
+        $m!(set_move_path_override, SetMovePathOverrideRequest, SetMovePathOverrideResponse; id_value: (u8, f64));
+        $m!(start_servo, StartServoRequest, StartServoResponse; id_v_a: (u8, f64, f64));
+        $m!(push_servo_j, PushServoJRequest::<N>, PushServoJResponse; id_joint: (u8, [f64;N]));
+        $m!(push_servo_p, PushServoPRequest, PushServoPResponse; id_pose_tcp_ucs: (u8, [[f64;6];3]));
+    };
+}
+#[cfg(feature = "async")]
+pub(crate) use for_each_command;
+
 impl<const N: usize> RobotImpl<N> {
     /// 新建一个机器人实例，使用传入的机器人 ip 与默认端口 [`PORT_IF`](crate::network::PORT_IF)
-    pub fn new(ip: &str) -> Self {
-        let network = Network::from_defult_port(ip);
-        RobotImpl { network }
+    pub fn new(ip: &str) -> RobotResult<Self> {
+        let network = Network::from_defult_port(ip)?;
+        Ok(RobotImpl { network })
+    }
+
+    /// 新建一个机器人实例，使用传入的机器人 ip 与指令端口
+    pub fn new_with_port(ip: &str, port: u16) -> RobotResult<Self> {
+        let network = Network::from_ip(ip, port)?;
+        Ok(RobotImpl { network })
+    }
+
+    /// 新建一个机器人实例，使用传入的机器人 ip、指令端口与连接策略
+    pub fn new_with_config(ip: &str, port: u16, config: NetworkConfig) -> RobotResult<Self> {
+        let network = Network::from_ip_with(ip, port, config)?;
+        Ok(RobotImpl { network })
+    }
+
+    /// 在 `f` 中发送的指令不探测、不重连，只在当前连接上尝试一次，用于急停等不能等待重连的场合
+    pub(crate) fn single_attempt<T>(&mut self, f: impl FnOnce(&mut Self) -> T) -> T {
+        let previous = std::mem::replace(&mut self.network.single_attempt, true);
+        let result = f(self);
+        self.network.single_attempt = previous;
+        result
     }
 
     /// 连接网络，使用指定的 ip 与端口
-    pub fn connect(&mut self, ip: &str, port: u16) {
-        self.network.connect(ip, port).unwrap();
+    pub fn connect(&mut self, ip: &str, port: u16) -> RobotResult<()> {
+        self.network.connect(ip, port)
     }
 
     /// 断开网络连接
-    pub fn disconnect(&mut self) {
-        self.network.disconnect().unwrap();
+    pub fn disconnect(&mut self) -> RobotResult<()> {
+        self.network.disconnect()
     }
 
     pub fn is_connected(&self) -> bool {
@@ -113,127 +262,7 @@ impl<const N: usize> RobotImpl<N> {
         Ok(())
     }
 
-    cmd_fn!(connect_to_box, ConnectToBoxRequest, ConnectToBoxResponse);
-    cmd_fn!(robot_power_on, ElectrifyRequest, ElectrifyResponse);
-    cmd_fn!(robot_power_off, BlackOutRequest, BlackOutResponse);
-    cmd_fn!(
-        connect_to_controller,
-        StartMasterRequest,
-        StartMasterResponse
-    );
-    cmd_fn!(
-        disconnect_from_controller,
-        CloseMasterRequest,
-        CloseMasterResponse
-    );
-    cmd_fn!(is_simulation, IsSimulationRequest, IsSimulationResponse;; bool);
-    cmd_fn!(is_controller_started, ReadControllerStateRequest, ReadControllerStateResponse;; bool);
-
-    // ! 机器人轴组控制指令
-
-    cmd_fn!(robot_model, ReadRobotModelRequest, ReadRobotModelResponse; id: u8; u16);
-    cmd_fn!(robot_enable, GrpEnableRequest, GrpEnableResponse; id: u8);
-    cmd_fn!(robot_disable, GrpDisableRequest, GrpDisableResponse; id: u8);
-    cmd_fn!(robot_reset, GrpResetRequest, GrpResetResponse; id: u8);
-    cmd_fn!(robot_move_stop, GrpStopRequest, GrpStopResponse; id: u8);
-    cmd_fn!(robot_move_pause, GrpInterruptRequest, GrpInterruptResponse; id: u8);
-    cmd_fn!(robot_move_continue, GrpContinueRequest, GrpContinueResponse; id: u8);
-    cmd_fn!(robot_free_driver_open, GrpOpenFreeDriverRequest, GrpOpenFreeDriverResponse; id: u8);
-    cmd_fn!(robot_free_driver_close, GrpCloseFreeDriverRequest, GrpCloseFreeDriverResponse; id: u8);
-
-    // ! 电箱控制指令
-    cmd_fn!(box_info, ReadBoxInfoRequest, ReadBoxInfoResponse; id: u8; BoxInfo);
-    cmd_fn!(box_control_input<bool; M>, ReadBoxCIRequest<M>, ReadBoxCIResponse<M>);
-    cmd_fn!(box_control_output<bool; M>, ReadBoxCORequest<M>, ReadBoxCOResponse<M>);
-    cmd_fn!(box_digital_input<bool; M>, ReadBoxDIRequest<M>, ReadBoxDIResponse<M>);
-    cmd_fn!(box_digital_output<bool; M>, ReadBoxDORequest<M>, ReadBoxDOResponse<M>);
-    cmd_fn!(box_analog_input<f64; M>, ReadBoxAIRequest<M>, ReadBoxAIResponse<M>);
-    cmd_fn!(box_analog_output<f64; M>, ReadBoxAORequest<M>, ReadBoxAOResponse<M>);
-    cmd_fn!(box_end_digital_input<M>, ReadEIRequest<M>, ReadEIResponse<M>; id_port: (u8,[u8;M]); [bool;M]);
-    cmd_fn!(box_end_digital_output<M>, ReadEORequest<M>, ReadEOResponse<M>; id_port: (u8,[u8;M]); [bool;M]);
-    cmd_fn!(box_end_analog_input, ReadEAIRequest, ReadEAIResponse; id: (u8,u8); f64);
-    cmd_fn!(box_set_control_output, SetBoxCORequest, SetBoxCOResponse; id_out: (u8,bool));
-    cmd_fn!(box_set_digital_output, SetBoxDORequest, SetBoxDOResponse; id_out: (u8,bool));
-    cmd_fn!(box_set_analog_output_mode, SetBoxAOModeRequest, SetBoxAOModeResponse; id_mode: (u8,u8));
-    cmd_fn!(box_set_analog_output, SetBoxAORequest, SetBoxAOResponse; id_out_mode: (u8,f64,u8));
-    cmd_fn!(box_set_end_digital_output, SetEndDORequest, SetEndDOResponse; id_out: (u8,u8,bool));
-
-    // ! 机器人状态指令
-    cmd_fn!(state_set_override, SetOverrideRequest, SetOverrideResponse; id_value: (u8,f64));
-    cmd_fn!(state_set_tool_motion, SetToolMotionRequest, SetToolMotionResponse; id_value: (u8,bool));
-    cmd_fn!(state_set_payload, SetPayloadRequest, SetPayloadResponse; id_value: (u8,Load));
-    cmd_fn!(state_set_joint_max_vel, SetJointMaxVelRequest::<N>, SetJointMaxVelResponse; id_value: (u8,[f64;N]));
-    cmd_fn!(state_set_joint_max_acc, SetJointMaxAccRequest::<N>, SetJointMaxAccResponse; id_value: (u8,[f64;N]));
-    cmd_fn!(state_set_linear_max_vel, SetLinearMaxVelRequest, SetLinearMaxVelResponse; id_value: (u8,f64));
-    cmd_fn!(state_set_linear_max_acc, SetLinearMaxAccRequest, SetLinearMaxAccResponse; id_value: (u8,f64));
-    cmd_fn!(state_read_joint_max_vel, ReadJointMaxVelRequest, ReadJointMaxVelResponse::<N>; id: u8; [f64;N]);
-    cmd_fn!(state_read_joint_max_acc, ReadJointMaxAccRequest, ReadJointMaxAccResponse::<N>; id: u8; [f64;N]);
-    cmd_fn!(state_read_joint_max_jerk, ReadJointMaxJerkRequest, ReadJointMaxJerkResponse::<N>; id: u8; [f64;N]);
-    cmd_fn!(state_read_linear_max_vel, ReadLinearMaxVelRequest, ReadLinearMaxVelResponse::<N>; id: u8; [f64;N]);
-    cmd_fn!(state_read_emergency_info, ReadEmergencyInfoRequest, ReadEmergencyInfoResponse::<N>; id: u8; EmergencyInfo);
-    cmd_fn!(state_read_robot_state, ReadRobotStateRequest, ReadRobotStateResponse; id: u8; RobotFlag);
-    cmd_fn!(state_read_axis_error_code, ReadAxisErrorCodeRequest, ReadAxisErrorCodeResponse::<N>; id: u8; [u16;N]);
-    cmd_fn!(state_read_cur_fsm, ReadCurFSMRequest, ReadCurFSMResponse; id: u8; RobotMode);
-    cmd_fn!(state_read_cmd_pos, ReadCmdPosRequest, ReadCmdPosResponse::<N>; id: u8; CmdPose::<N>);
-    cmd_fn!(state_read_act_pos, ReadActPosRequest, ReadActPosResponse::<N>; id: u8; ActPose::<N>);
-    cmd_fn!(state_read_cmd_joint_vel, ReadCmdJointVelRequest, ReadCmdJointVelResponse::<N>; id: u8; [f64;N]);
-    cmd_fn!(state_read_act_joint_vel, ReadActJointVelRequest, ReadActJointVelResponse::<N>; id: u8; [f64;N]);
-    cmd_fn!(state_read_cmd_tcp_vel, ReadCmdTcpVelRequest, ReadCmdTcpVelResponse; id: u8; [f64; 6]);
-    cmd_fn!(state_read_act_tcp_vel, ReadActTcpVelRequest, ReadActTcpVelResponse; id: u8; [f64; 6]);
-    cmd_fn!(state_read_cmd_joint_cur, ReadCmdJointCurRequest, ReadCmdJointCurResponse::<N>; id: u8; [f64;N]);
-    cmd_fn!(state_read_act_joint_cur, ReadActJointCurRequest, ReadActJointCurResponse::<N>; id: u8; [f64;N]);
-    cmd_fn!(state_read_tcp_vel, ReadTcpVelocityRequest, ReadTcpVelocityResponse; id: u8; (f64,f64));
-
-    // ! 坐标系读写指令
-    cmd_fn!(set_pose_o_to_t, SetCurTCPRequest, SetCurTCPResponse; id_pose: (u8,[f64;6]));
-    cmd_fn!(set_pose_u_to_t, SetCurUCSRequest, SetCurUCSResponse; id_pose: (u8,[f64;6]));
-    cmd_fn!(read_pose_o_to_t, ReadCurTCPRequest, ReadCurTCPResponse; id_pose: u8; [f64;6]);
-    cmd_fn!(read_pose_u_to_t, ReadCurUCSRequest, ReadCurUCSResponse; id_pose: u8; [f64;6]);
-
-    // ! 力控指令
-    cmd_fn!(force_control, SetForceControlStateRequest, SetForceControlStateResponse; id_state: (u8,bool));
-    cmd_fn!(force_control_mode, ReadFTControlStateRequest, ReadFTControlStateResponse; id: u8; bool);
-    cmd_fn!(force_tool_coord, SetForceToolCoordinateMotionRequest, SetForceToolCoordinateMotionResponse; id_mode: (u8,bool));
-    cmd_fn!(force_interrupt, GrpFCInterruptRequest, GrpFCInterruptResponse; id: u8);
-    cmd_fn!(force_continue, GrpFCContinueRequest, GrpFCContinueResponse; id: u8);
-    cmd_fn!(force_zero, SetForceZeroRequest, SetForceZeroResponse; id: u8);
-    cmd_fn!(force_max_search_vel, HRSetMaxSearchVelocitiesRequest, HRSetMaxSearchVelocitiesResponse; id_v_w: (u8,f64,f64));
-    cmd_fn!(force_control_strategy, HRSetForceControlStrategyRequest, HRSetForceControlStrategyResponse; id_mode: (u8,u8));
-    cmd_fn!(force_set_senor_pose_f_to, SetFTPositionRequest, SetFTPositionResponse; id_pos: (u8,[f64;6]));
-    cmd_fn!(force_pid_control_params, HRSetPIDControlParamsRequest::<N>, HRSetPIDControlParamsResponse; id_params: (u8,[f64; N]));
-    cmd_fn!(force_mass_params, HRSetMassParamsRequest, HRSetMassParamsResponse; id_params: (u8,[f64;6]));
-    cmd_fn!(force_damp_params, HRSetDampParamsRequest, HRSetDampParamsResponse; id_params: (u8,[f64;6]));
-    cmd_fn!(force_stiff_params, HRSetStiffParamsRequest, HRSetStiffParamsResponse; id_params: (u8,[f64;6]));
-    cmd_fn!(force_control_goal, HRSetControlGoalRequest, HRSetControlGoalResponse; id_goal: (u8,[f64;6],f64));
-    cmd_fn!(force_free_drive, SetForceFreeDriveModeRequest, SetForceFreeDriveModeResponse; id_mode: (u8,bool));
-    cmd_fn!(force_senor_data, ReadFTCabDataRequest, ReadFTCabDataResponse; id: u8; [f64;6]);
-
-    // ! 运动生成指令
-    cmd_fn!(move_joint_rel, MoveRelJRequest, MoveRelJResponse; id_dir_dis: (u8, RelJ));
-    cmd_fn!(move_line_rel, MoveRelLRequest, MoveRelLResponse; id_dir_dis_coord: (u8, RelL));
-    cmd_fn!(move_way_point_rel, WayPointRelRequest::<N>, WayPointRelResponse; id_way_point_rel: (u8, WayPointRel::<N>));
-    cmd_fn!(move_way_point_ex, WayPointExRequest::<N>, WayPointExResponse; id_way_point_ex: (u8, WayPointEx::<N>));
-    cmd_fn!(move_way_point, WayPointRequest::<N>, WayPointResponse; id_way_point: (u8, WayPoint::<N>));
-    cmd_fn!(move_way_point2, WayPoint2Request::<N>, WayPoint2Response; id_way_point2: (u8, WayPoint2::<N>));
-    cmd_fn!(move_joint, MoveJRequest::<N>, MoveJResponse; id_joint: (u8, MoveJ::<N>));
-    cmd_fn!(move_line, MoveLRequest::<N>, MoveLResponse; id_line: (u8, MoveL::<N>));
-    cmd_fn!(move_circle, MoveCRequest, MoveCResponse; id_circle: (u8, MoveC));
-    cmd_fn!(start_push_move_path_j, StartPushMovePathRequest, StartPushMovePathResponse; id_config: (u8, StartPushMovePathJ));
-    cmd_fn!(push_move_path_j, PushMovePathJRequest::<N>, PushMovePathJResponse; id_path: (u8, String, [f64;N]));
-    cmd_fn!(end_push_move_path, EndPushMovePathRequest, EndPushMovePathResponse; id_path: (u8, String));
-    cmd_fn!(move_path_j, MovePathRequest, MovePathResponse; id_path: (u8, String));
-    cmd_fn!(read_move_path_state, ReadMovePathStateRequest, ReadMovePathStateResponse; id_path: (u8, String); u8);
-    cmd_fn!(update_move_path_name, UpdateMovePathNameRequest, UpdateMovePathNameResponse; id_path_new: (u8, String, String));
-    cmd_fn!(del_move_path, DelMovePathRequest, DelMovePathResponse; id_path: (u8, String));
-    cmd_fn!(read_soft_motion_process, ReadSoftMotionProcessRequest, ReadSoftMotionProcessResponse; id:u8; (f64, u16));
-    cmd_fn!(start_push_move_path_l, InitMovePathLRequest, InitMovePathLResponse; id_config: (u8, StartPushMovePathL));
-    cmd_fn!(push_move_path_l, PushMovePathLRequest, PushMovePathLResponse; id_path: (u8, [f64;6]));
-    cmd_fn!(push_move_paths, PushMovePathsRequest<N>, PushMovePathsResponse; id_paths: (u8, MovePaths<N>));
-    cmd_fn!(move_path_l, MovePathLRequest, MovePathLResponse; id_path: (u8, String));
-    cmd_fn!(set_move_path_override, SetMovePathOverrideRequest, SetMovePathOverrideResponse; id_value: (u8, f64));
-    cmd_fn!(start_servo, StartServoRequest, StartServoResponse; id_v_a: (u8, f64, f64));
-    cmd_fn!(push_servo_j, PushServoJRequest::<N>, PushServoJResponse; id_joint: (u8, [f64;N]));
-    cmd_fn!(push_servo_p, PushServoPRequest, PushServoPResponse; id_pose_tcp_ucs: (u8, [[f64;6];3]));
+    for_each_command!(cmd_fn);
 }
 
 submit!(
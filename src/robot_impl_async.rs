@@ -0,0 +1,232 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use robot_behavior::RobotResult;
+use tokio::time::sleep;
+
+use crate::robot_impl::for_each_command;
+use crate::wait::MotionWatch;
+use crate::{AsyncNetwork, NetworkConfig, PORT_IF, RobotMode, WaitPolicy, WaitResult, types::*};
+
+macro_rules! async_cmd_fn {
+    ($fn_name:ident, $req_type:ty, $res_type:ty) => {
+        pub async fn $fn_name(&self, _:()) -> RobotResult<()> {
+            let response: $res_type = self.network.send_and_recv(&<$req_type>::from(())).await?;
+            response.status.map_err(Into::into)
+        }
+    };
+    ($fn_name:ident, $req_type:ty, $res_type:ty;; $ret_type:ty) => {
+        pub async fn $fn_name(&self, _:()) -> RobotResult<$ret_type> {
+            let response: $res_type = self.network.send_and_recv(&<$req_type>::from(())).await?;
+            response.status.map_err(Into::into)
+        }
+    };
+    ($fn_name:ident, $req_type:ty, $res_type:ty; $($arg_name:ident: $arg_type:ty),*) => {
+        pub async fn $fn_name(&self, $($arg_name: $arg_type),*) -> RobotResult<()> {
+            let response: $res_type = self.network.send_and_recv(&<$req_type>::from($($arg_name),*)).await?;
+            response.status.map_err(Into::into)
+        }
+    };
+    ($fn_name:ident, $req_type:ty, $res_type:ty; $($arg_name:ident: $arg_type:ty),* ; $ret_type:ty) => {
+        pub async fn $fn_name(&self, $($arg_name: $arg_type),*) -> RobotResult<$ret_type> {
+            let response: $res_type = self.network.send_and_recv(&<$req_type>::from($($arg_name),*)).await?;
+            response.status.map_err(Into::into)
+        }
+    };
+    ($fn_name:ident<$const_ty:ty; $const_name:ident>, $req_type:ty, $res_type:ty) => {
+        pub async fn $fn_name<const $const_name: usize>(&self, argc: [$const_ty; $const_name]) -> RobotResult<[$const_ty; $const_name]> {
+            let response: $res_type = self.network.send_and_recv(&<$req_type>::from(argc)).await?;
+            response.status.map_err(Into::into)
+        }
+    };
+    ($fn_name:ident<$const_ty:ty; $const_name:ident>, $req_type:ty, $res_type:ty;;  $ret_type:ty) => {
+        pub async fn $fn_name<const $const_name: usize>(&self, argc: [$const_ty; $const_name]) -> RobotResult<$ret_type> {
+            let response: $res_type = self.network.send_and_recv(&<$req_type>::from(argc)).await?;
+            response.status.map_err(Into::into)
+        }
+    };
+    ($fn_name:ident<$const_name:ident>, $req_type:ty, $res_type:ty; $($arg_name:ident: $arg_type:ty),*) => {
+        pub async fn $fn_name<const $const_name: usize>(&self, $($arg_name: $arg_type),*) -> RobotResult<()> {
+            let response: $res_type = self.network.send_and_recv(&<$req_type>::from($($arg_name),*)).await?;
+            response.status.map_err(Into::into)
+        }
+    };
+    ($fn_name:ident<$const_name:ident>, $req_type:ty, $res_type:ty; $($arg_name:ident: $arg_type:ty),* ; $ret_type:ty) => {
+        pub async fn $fn_name<const $const_name: usize>(&self, $($arg_name: $arg_type),*) -> RobotResult<$ret_type> {
+            let response: $res_type = self.network.send_and_recv(&<$req_type>::from($($arg_name),*)).await?;
+            response.status.map_err(Into::into)
+        }
+    };
+}
+
+/// [`RobotImpl`](crate::robot_impl::RobotImpl) 的异步版本
+///
+/// 指令与同步版本由同一张指令表生成，名称与参数一致。所有指令只需要 `&self`，
+/// 可以在多个任务中并发调用，请求会按提交顺序依次发送。
+pub struct AsyncRobotImpl<const N: usize> {
+    pub network: AsyncNetwork,
+    /// 通过 [`stop`](Self::stop) 下发过停止指令，由下一次等待运动结束时取走
+    stop_requested: AtomicBool,
+}
+
+impl<const N: usize> AsyncRobotImpl<N> {
+    /// 连接机器人，使用传入的机器人 ip 与默认端口 [`PORT_IF`](crate::network::PORT_IF)
+    pub async fn connect(ip: &str) -> RobotResult<Self> {
+        Self::connect_with_port(ip, PORT_IF).await
+    }
+
+    /// 连接机器人，使用传入的机器人 ip 与指令端口
+    pub async fn connect_with_port(ip: &str, port: u16) -> RobotResult<Self> {
+        Self::connect_with(ip, port, NetworkConfig::default()).await
+    }
+
+    /// 连接机器人，使用传入的机器人 ip、指令端口与超时设置
+    pub async fn connect_with(ip: &str, port: u16, config: NetworkConfig) -> RobotResult<Self> {
+        let network = AsyncNetwork::connect_with(ip, port, config).await?;
+        Ok(AsyncRobotImpl { network, stop_requested: AtomicBool::new(false) })
+    }
+
+    /// 断开网络连接
+    pub async fn disconnect(&mut self) {
+        self.network.disconnect().await;
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.network.is_connected()
+    }
+
+    /// 等待当前运动结束，每隔 `interval` 查询一次状态机
+    ///
+    /// 机器人进入错误或急停状态时返回错误，而不是一直等待
+    pub async fn waiting_for_finish(&self, interval: Duration) -> RobotResult<()> {
+        let policy = WaitPolicy::default().with_poll_interval(interval);
+        self.wait_for_motion(&policy).await?.into_result()
+    }
+
+    /// 按照给定的策略等待当前运动结束，返回运动是完成、被停止、故障还是超时
+    ///
+    /// 异步接口没有状态流，`use_state_stream` 会被忽略。等待期间或之前通过 [`stop`](Self::stop)
+    /// 停止的运动回到待机时返回 `Stopped`。
+    pub async fn wait_for_motion(&self, policy: &WaitPolicy) -> RobotResult<WaitResult> {
+        let mut watch = MotionWatch::new(policy, false);
+        loop {
+            if self.stop_requested.swap(false, Ordering::AcqRel) {
+                watch.stop_requested();
+            }
+            let mode = self.state_read_cur_fsm(0).await?;
+            if let Some(result) = watch.observe(mode) {
+                return Ok(result);
+            }
+            sleep(watch.poll_interval()).await;
+        }
+    }
+
+    /// 停止当前运动，正在等待同一运动结束的任务会返回 `Stopped`
+    pub async fn stop(&self) -> RobotResult<()> {
+        self.stop_requested.store(true, Ordering::Release);
+        let result = self.robot_move_stop(0).await;
+        if result.is_err() {
+            self.stop_requested.store(false, Ordering::Release);
+        }
+        result
+    }
+
+    // ! 以下为机器人控制接口
+    // ! 初始化指令
+
+    pub async fn power_off(&self) -> RobotResult<()> {
+        let _: OSCmdResponse = self.network.send_and_recv(&OSCmdRequest::from(1)).await?;
+        Ok(())
+    }
+
+    pub async fn restart(&self) -> RobotResult<()> {
+        let _: OSCmdResponse = self.network.send_and_recv(&OSCmdRequest::from(2)).await?;
+        Ok(())
+    }
+
+    for_each_command!(async_cmd_fn);
+}
+
+#[cfg(all(test, not(feature = "no_robot")))]
+mod tests {
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::sync::Arc;
+    use std::thread::{self, sleep as thread_sleep};
+
+    use tokio::runtime::{Builder, Runtime};
+    use tokio::time::timeout;
+
+    use super::*;
+    use crate::Simulator;
+
+    fn runtime() -> Runtime {
+        Builder::new_current_thread().enable_all().build().unwrap()
+    }
+
+    #[test]
+    fn test_async_robot_with_simulator() {
+        let simulator = Simulator::start_cold().unwrap();
+        simulator.set_time_scale(10.);
+        runtime().block_on(async {
+            let robot = AsyncRobotImpl::<6>::connect_with_port(&simulator.host(), simulator.port())
+                .await
+                .unwrap();
+            robot.connect_to_box(()).await.unwrap();
+            robot.robot_power_on(()).await.unwrap();
+            robot.connect_to_controller(()).await.unwrap();
+            robot.robot_enable(0).await.unwrap();
+            assert!(robot.is_controller_started(()).await.unwrap());
+
+            let start = simulator.joint();
+            let rel = RelJ { id: 2, dir: true, dis: 10. };
+            robot.move_joint_rel((0, rel)).await.unwrap();
+            robot
+                .waiting_for_finish(Duration::from_millis(5))
+                .await
+                .unwrap();
+            assert!((simulator.joint()[2] - start[2] - 10.).abs() < 1e-6);
+
+            let rel = RelJ { id: 2, dir: true, dis: 90. };
+            robot.move_joint_rel((0, rel)).await.unwrap();
+            let policy = WaitPolicy::default().with_poll_interval(Duration::from_millis(5));
+            let robot = Arc::new(robot);
+            let stopper = tokio::spawn({
+                let robot = robot.clone();
+                async move {
+                    tokio::time::sleep(Duration::from_millis(20)).await;
+                    robot.stop().await
+                }
+            });
+            let result = robot.wait_for_motion(&policy).await.unwrap();
+            stopper.await.unwrap().unwrap();
+            assert_eq!(result.outcome, crate::WaitOutcome::Stopped);
+        });
+    }
+
+    #[test]
+    fn test_async_cancellation_keeps_pairing() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = thread::spawn(move || {
+            let (mut socket, _) = listener.accept().unwrap();
+            for model in 1..=2 {
+                let mut byte = [0_u8; 1];
+                while socket.read(&mut byte).unwrap() == 1 && byte[0] != b';' {}
+                // 第一条应答在调用方取消之后才到达
+                thread_sleep(Duration::from_millis(if model == 1 { 100 } else { 0 }));
+                write!(socket, "ReadRobotModel,OK,{model},;").unwrap();
+            }
+        });
+
+        runtime().block_on(async {
+            let robot = AsyncRobotImpl::<6>::connect_with_port("127.0.0.1", port)
+                .await
+                .unwrap();
+            let cancelled = timeout(Duration::from_millis(10), robot.robot_model(0)).await;
+            assert!(cancelled.is_err());
+            assert_eq!(robot.robot_model(0).await.unwrap(), 2);
+        });
+        server.join().unwrap();
+    }
+}
@@ -1,9 +1,11 @@
+use std::fmt::Display;
+
 use serde_repr::{Deserialize_repr, Serialize_repr};
 
-use crate::types::CommandSerde;
-use robot_behavior::{RobotResult, deserialize_error};
+use crate::types::{Command, CommandSerde};
+use robot_behavior::{RobotException, RobotResult, deserialize_error};
 
-#[derive(Default, Debug, Serialize_repr, Deserialize_repr, PartialEq, Copy, Clone)]
+#[derive(Default, Debug, Serialize_repr, Deserialize_repr, PartialEq, Eq, Hash, Copy, Clone)]
 #[repr(u8)]
 pub enum RobotMode {
     #[default]
@@ -118,3 +120,328 @@ impl CommandSerde for RobotMode {
         RobotMode::StandBy
     }
 }
+
+impl RobotMode {
+    /// 故障状态，机器人已停止运行
+    pub fn is_error(&self) -> bool {
+        matches!(
+            self,
+            RobotMode::SaftyGuardError
+                | RobotMode::ControllerVersionError
+                | RobotMode::EtherCATError
+                | RobotMode::RobotOutofSafeSpace
+                | RobotMode::RobotCollisionStop
+                | RobotMode::Error
+                | RobotMode::HRAppError
+        )
+    }
+
+    /// 急停、安全光幕、碰撞或超出安全空间引起的安全停止，包括正在处理中的状态
+    pub fn is_safety_stop(&self) -> bool {
+        matches!(
+            self,
+            RobotMode::EmergencyStopHandling
+                | RobotMode::EmergencyStop
+                | RobotMode::SaftyGuardErrorHandling
+                | RobotMode::SaftyGuardError
+                | RobotMode::SafetyGuardHandling
+                | RobotMode::SaftyGuard
+                | RobotMode::RobotOutofSafeSpace
+                | RobotMode::RobotCollisionStop
+        )
+    }
+
+    /// 过渡状态，控制器会在一段时间后自行离开该状态
+    pub fn is_transitional(&self) -> bool {
+        matches!(
+            self,
+            RobotMode::ElectricBoxConnecting
+                | RobotMode::EmergencyStopHandling
+                | RobotMode::Blackouting48V
+                | RobotMode::Electrifying48V
+                | RobotMode::SaftyGuardErrorHandling
+                | RobotMode::SafetyGuardHandling
+                | RobotMode::ControllerDisconnecting
+                | RobotMode::ControllerConnecting
+                | RobotMode::ControllerChecking
+                | RobotMode::Reseting
+                | RobotMode::RobotEnabling
+                | RobotMode::RobotStopping
+                | RobotMode::RobotDisabling
+                | RobotMode::RobotOpeningFreeDriver
+                | RobotMode::RobotClosingFreeDriver
+                | RobotMode::ScriptHoldHandling
+                | RobotMode::ScriptStopping
+                | RobotMode::Braking
+        )
+    }
+
+    /// 可以接受新的运动指令
+    pub fn can_accept_motion(&self) -> bool {
+        *self == RobotMode::StandBy
+    }
+
+    /// 需要 `GrpReset` 复位后才能继续
+    pub fn requires_reset(&self) -> bool {
+        matches!(
+            self,
+            RobotMode::EmergencyStop
+                | RobotMode::SaftyGuard
+                | RobotMode::SaftyGuardError
+                | RobotMode::RobotOutofSafeSpace
+                | RobotMode::RobotCollisionStop
+                | RobotMode::Error
+        )
+    }
+
+    /// 脚本运行相关的状态
+    pub fn is_script_state(&self) -> bool {
+        matches!(
+            self,
+            RobotMode::ScriptRunning
+                | RobotMode::ScriptHoldHandling
+                | RobotMode::ScriptHolding
+                | RobotMode::ScriptStopping
+                | RobotMode::ScriptStopped
+        )
+    }
+
+    /// 机器人所处的上电层级，故障状态返回 `None`
+    ///
+    /// 0 电箱未连接，1 已连接电箱未上电，2 已上电主站未启动，3 主站已启动未使能，4 已使能。
+    /// 过渡状态按流程方向归入尚未完成的一侧：上电时 `Electrifying48V` 属于层级 1，
+    /// 关机时 `Blackouting48V` 属于层级 2。
+    pub(crate) fn power_level(&self, powering_up: bool) -> Option<u8> {
+        // 过渡状态位于相邻两个层级之间，取尚未完成的一侧
+        let between = |lower: u8| Some(if powering_up { lower } else { lower + 1 });
+        match self {
+            RobotMode::UnInitialized
+            | RobotMode::Initialized
+            | RobotMode::ElectricBoxDisconnect => Some(0),
+            RobotMode::ElectricBoxConnecting => between(0),
+            RobotMode::Blackout48V => Some(1),
+            RobotMode::Blackouting48V | RobotMode::Electrifying48V => between(1),
+            RobotMode::ControllerDisconnect => Some(2),
+            RobotMode::ControllerDisconnecting
+            | RobotMode::ControllerConnecting
+            | RobotMode::ControllerChecking => between(2),
+            RobotMode::Disable => Some(3),
+            RobotMode::RobotEnabling | RobotMode::RobotDisabling => between(3),
+            RobotMode::StandBy
+            | RobotMode::Moving
+            | RobotMode::LongJogMoving
+            | RobotMode::RobotStopping
+            | RobotMode::RobotOpeningFreeDriver
+            | RobotMode::RobotClosingFreeDriver
+            | RobotMode::FreeDriver
+            | RobotMode::RobotHolding
+            | RobotMode::ScriptRunning
+            | RobotMode::ScriptHoldHandling
+            | RobotMode::ScriptHolding
+            | RobotMode::ScriptStopping
+            | RobotMode::ScriptStopped
+            | RobotMode::RobotLoadIdentify
+            | RobotMode::Braking => Some(4),
+            _ => None,
+        }
+    }
+
+    /// 当前状态下不能下发运动指令的原因
+    fn motion_blocker(&self) -> &'static str {
+        match self {
+            RobotMode::Moving | RobotMode::LongJogMoving => "robot is already moving",
+            RobotMode::FreeDriver
+            | RobotMode::RobotOpeningFreeDriver
+            | RobotMode::RobotClosingFreeDriver => "free drive is active, close it first",
+            RobotMode::RobotHolding => "motion is paused, continue or stop it first",
+            RobotMode::RobotLoadIdentify => "load identification is running",
+            mode if mode.is_script_state() => "a script is running",
+            mode if mode.is_safety_stop() => "robot is in a safety stop, clear it and reset first",
+            mode if mode.requires_reset() || mode.is_error() => {
+                "robot is in a fault state, reset first"
+            }
+            mode if mode.is_transitional() => "robot is changing state, wait until it settles",
+            mode => match mode.power_level(true) {
+                Some(3) => "robot is not enabled",
+                Some(2) => "master station is not started",
+                _ => "robot is not powered on",
+            },
+        }
+    }
+
+    /// 校验 `command` 能否在当前状态下执行，返回指令完成后机器人应处的状态
+    ///
+    /// 查询、设置类指令不改变状态，任何时候都可以执行；停止、断电等安全相关指令也总是允许。
+    /// 被拒绝时 [`ModeViolation`] 说明了原因，例如 `cannot MoveJ in FreeDriver`。
+    pub fn transition(&self, command: Command) -> Result<RobotMode, ModeViolation> {
+        let mode = *self;
+        let reject = |reason| Err(ModeViolation { command, mode, reason });
+        let level = mode.power_level(true);
+        match command {
+            Command::MoveRelJ
+            | Command::MoveRelL
+            | Command::WayPointRel
+            | Command::WayPointEx
+            | Command::WayPoint
+            | Command::WayPoint2
+            | Command::MoveJ
+            | Command::MoveL
+            | Command::MoveC
+            | Command::MovePath
+            | Command::MovePathL
+            | Command::StartServo => {
+                if mode.can_accept_motion() {
+                    Ok(RobotMode::Moving)
+                } else {
+                    reject(mode.motion_blocker())
+                }
+            }
+            Command::PushServoJ | Command::PushServoP => match mode {
+                RobotMode::StandBy | RobotMode::Moving => Ok(RobotMode::Moving),
+                _ => reject(mode.motion_blocker()),
+            },
+            Command::GrpStop => match mode {
+                RobotMode::Moving
+                | RobotMode::LongJogMoving
+                | RobotMode::RobotHolding
+                | RobotMode::RobotStopping => Ok(RobotMode::StandBy),
+                mode => Ok(mode),
+            },
+            Command::GrpInterrupt => match mode {
+                RobotMode::Moving | RobotMode::RobotHolding => Ok(RobotMode::RobotHolding),
+                _ => reject("no motion to pause"),
+            },
+            Command::GrpContinue => match mode {
+                RobotMode::RobotHolding | RobotMode::Moving => Ok(RobotMode::Moving),
+                _ => reject("motion is not paused"),
+            },
+            Command::GrpReset => match mode {
+                RobotMode::EmergencyStop => Ok(RobotMode::Blackout48V),
+                mode if mode.requires_reset() => Ok(RobotMode::Disable),
+                mode => Ok(mode),
+            },
+            Command::GrpEnable => match mode {
+                RobotMode::Disable | RobotMode::StandBy => Ok(RobotMode::StandBy),
+                mode if mode.requires_reset() => reject("robot is in a fault state, reset first"),
+                mode if level == Some(4) => reject(mode.motion_blocker()),
+                _ => reject("master station is not started"),
+            },
+            Command::GrpDisable => match level {
+                Some(3..) => Ok(RobotMode::Disable),
+                _ if mode.is_error() => Ok(mode),
+                _ => reject("master station is not started"),
+            },
+            Command::GrpOpenFreeDriver => match mode {
+                RobotMode::StandBy | RobotMode::FreeDriver => Ok(RobotMode::FreeDriver),
+                _ => reject(mode.motion_blocker()),
+            },
+            Command::GrpCloseFreeDriver => match mode {
+                RobotMode::FreeDriver | RobotMode::StandBy => Ok(RobotMode::StandBy),
+                _ => reject("free drive is not active"),
+            },
+            Command::ConnectToBox => match level {
+                Some(0) => Ok(RobotMode::Blackout48V),
+                _ => Ok(mode),
+            },
+            Command::Electrify => match level {
+                Some(0) => reject("electric box is not connected"),
+                Some(1) => Ok(RobotMode::ControllerDisconnect),
+                Some(_) => Ok(mode),
+                None if mode == RobotMode::EmergencyStop => reject("emergency stop is engaged"),
+                None => reject("robot is in a fault state, reset first"),
+            },
+            Command::StartMaster => match level {
+                Some(2) => Ok(RobotMode::Disable),
+                Some(3..) => Ok(mode),
+                Some(_) => reject("robot is not powered on"),
+                None => reject("robot is in a fault state, reset first"),
+            },
+            Command::CloseMaster => match level {
+                Some(2..) => Ok(RobotMode::ControllerDisconnect),
+                _ => Ok(mode),
+            },
+            Command::BlackOut => match mode {
+                RobotMode::EmergencyStop => Ok(mode),
+                _ => Ok(RobotMode::Blackout48V),
+            },
+            _ => Ok(mode),
+        }
+    }
+}
+
+/// 指令在当前状态下不能执行
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ModeViolation {
+    /// 被拒绝的指令
+    pub command: Command,
+    /// 拒绝时机器人所处的状态
+    pub mode: RobotMode,
+    /// 拒绝的原因
+    pub reason: &'static str,
+}
+
+impl Display for ModeViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "cannot {:?} in {:?}: {}",
+            self.command, self.mode, self.reason
+        )
+    }
+}
+
+impl From<ModeViolation> for RobotException {
+    fn from(e: ModeViolation) -> Self {
+        RobotException::InvalidInstruction(e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_robot_mode_transition() {
+        assert_eq!(
+            RobotMode::StandBy.transition(Command::MoveJ),
+            Ok(RobotMode::Moving)
+        );
+        assert_eq!(
+            RobotMode::Moving.transition(Command::ReadActPos),
+            Ok(RobotMode::Moving)
+        );
+        assert_eq!(
+            RobotMode::Moving.transition(Command::GrpStop),
+            Ok(RobotMode::StandBy)
+        );
+        assert_eq!(
+            RobotMode::Error.transition(Command::GrpReset),
+            Ok(RobotMode::Disable)
+        );
+        assert_eq!(
+            RobotMode::Blackout48V.transition(Command::Electrify),
+            Ok(RobotMode::ControllerDisconnect)
+        );
+
+        let violation = RobotMode::FreeDriver
+            .transition(Command::MoveJ)
+            .unwrap_err();
+        assert!(
+            violation
+                .to_string()
+                .starts_with("cannot MoveJ in FreeDriver")
+        );
+        assert!(RobotMode::Disable.transition(Command::MoveL).is_err());
+
+        // 过渡状态在关机方向上视为尚未完成
+        assert_eq!(RobotMode::Blackouting48V.power_level(true), Some(1));
+        assert_eq!(RobotMode::Blackouting48V.power_level(false), Some(2));
+        assert_eq!(RobotMode::RobotDisabling.power_level(false), Some(4));
+        assert!(
+            RobotMode::EmergencyStop
+                .transition(Command::GrpEnable)
+                .is_err()
+        );
+        assert!(RobotMode::StandBy.transition(Command::GrpContinue).is_err());
+    }
+}
@@ -3,253 +3,253 @@ use serde_with::{DisplayFromStr, serde_as};
 
 use crate::{robot_error::RobotError, robot_mode::RobotMode};
 
-#[derive(Default, Debug, Serialize, Deserialize, PartialEq)]
+#[derive(Default, Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct RobotState {
     /// 位置和速度
     #[serde(rename = "PosAndVel")]
-    pos_and_vel: PosAndVel,
+    pub pos_and_vel: PosAndVel,
     /// 末端IO
     #[serde(rename = "EndIO")]
-    end_io: EndIO,
+    pub end_io: EndIO,
     /// 电箱IO
     #[serde(rename = "ElectricBoxIO")]
-    electric_box_io: ElectricBoxIO,
+    pub electric_box_io: ElectricBoxIO,
     /// 电箱模拟IO
     #[serde(rename = "ElectricBoxAnalogIO")]
-    electric_box_analog_io: ElectricBoxAnalogIO,
+    pub electric_box_analog_io: ElectricBoxAnalogIO,
     /// 状态和错误
     #[serde(rename = "StateAndError")]
-    state_and_error: StateAndError,
+    pub state_and_error: StateAndError,
     /// 硬件负载
     #[serde(rename = "HardLoad")]
-    hard_load: HardLoad,
+    pub hard_load: HardLoad,
     /// 力控数据
     #[serde(rename = "FTData")]
-    ft_data: FTData,
+    pub ft_data: FTData,
     /// 脚本
     #[serde(rename = "Script")]
-    script: Script,
+    pub script: Script,
     /// 插件数据
     #[serde(rename = "pluginsdata")]
-    plugins_data: PluginsData,
+    pub plugins_data: PluginsData,
 }
 
 #[serde_as]
-#[derive(Default, Debug, Serialize, Deserialize, PartialEq)]
+#[derive(Default, Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct PosAndVel {
     /// 关节位置，当前用户坐标和工具坐标下的迪卡尔坐标位置
     #[serde(rename = "Actual_Position")]
     #[serde_as(as = "[DisplayFromStr; 12]")]
-    position: [f64; 12],
+    pub position: [f64; 12],
 
     /// 当前工具坐标下的迪卡尔坐标位置
     #[serde(rename = "Actual_PCS_TCP")]
     #[serde_as(as = "[DisplayFromStr; 6]")]
-    pose_f_to_ee: [f64; 6],
+    pub pose_f_to_ee: [f64; 6],
 
     /// 基于基座坐标系下的迪卡尔坐标位置
     #[serde(rename = "Actual_PCS_Base")]
     #[serde_as(as = "[DisplayFromStr; 6]")]
-    pose_o_to_ee: [f64; 6],
+    pub pose_o_to_ee: [f64; 6],
 
     /// 当前实际关节运行时电流，单位[A]
     #[serde(rename = "Actual_Joint_Current")]
     #[serde_as(as = "[DisplayFromStr; 6]")]
-    joint_current: [f64; 6],
+    pub joint_current: [f64; 6],
 
     /// 实际关节速度，单位[rad/s]
     #[serde(rename = "Actual_Joint_Velocity")]
     #[serde_as(as = "[DisplayFromStr; 6]")]
-    joint_velocity: [f64; 6],
+    pub joint_velocity: [f64; 6],
 
     /// 实际关节加速度，单位[rad/s^2]
     #[serde(rename = "Actual_Joint_Acceleration")]
     #[serde_as(as = "[DisplayFromStr; 6]")]
-    joint_acceleration: [f64; 6],
+    pub joint_acceleration: [f64; 6],
 }
 
-#[derive(Default, Debug, Serialize, Deserialize, PartialEq)]
+#[derive(Default, Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct EndIO {
     /// 末端数字输入
     #[serde(rename = "EndDI")]
-    digital_input: [u8; 4],
+    pub digital_input: [u8; 4],
     /// 末端数字输出
     #[serde(rename = "EndDO")]
-    digital_output: [u8; 4],
+    pub digital_output: [u8; 4],
     /// 末端按钮状态
     #[serde(rename = "EndButton")]
-    button: [u8; 4],
+    pub button: [u8; 4],
     /// 末端是否启用按钮
     #[serde(rename = "EnableEndBTN")]
-    enable_button: u8,
+    pub enable_button: u8,
     /// 末端模拟输入
     #[serde(rename = "EndAI")]
-    analog_input: [f64; 2],
+    pub analog_input: [f64; 2],
 }
 
 #[serde_as]
-#[derive(Default, Debug, Serialize, Deserialize, PartialEq)]
+#[derive(Default, Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct ElectricBoxIO {
     /// 电箱数字输入
     #[serde(rename = "BoxCI")]
-    digital_input_c: [u8; 8],
+    pub digital_input_c: [u8; 8],
     /// 电箱数字输出
     #[serde(rename = "BoxCO")]
-    digital_output_c: [u8; 8],
+    pub digital_output_c: [u8; 8],
     /// 电箱数字输入
     #[serde(rename = "BoxDI")]
-    digital_input_d: [u8; 8],
+    pub digital_input_d: [u8; 8],
     /// 电箱数字输出
     #[serde(rename = "BoxDO")]
-    digital_output_d: [u8; 8],
+    pub digital_output_d: [u8; 8],
     /// 传送带速度
     #[serde(rename = "Conveyor")]
     #[serde_as(as = "DisplayFromStr")]
-    conveyor_speed: f64,
+    pub conveyor_speed: f64,
     /// 编码器值
     #[serde(rename = "Encode")]
-    encoder: u32,
+    pub encoder: u32,
 }
 
 #[serde_as]
-#[derive(Default, Debug, Serialize, Deserialize, PartialEq)]
+#[derive(Default, Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct ElectricBoxAnalogIO {
     /// 模拟输出1模式
     #[serde(rename = "BoxAnalogOutMode_1")]
-    analog_output_mode_1: u8,
+    pub analog_output_mode_1: u8,
     /// 模拟输出2模式
     #[serde(rename = "BoxAnalogOutMode_2")]
-    analog_output_mode_2: u8,
+    pub analog_output_mode_2: u8,
     /// 模拟输出1
     #[serde(rename = "BoxAnalogOut_1")]
     #[serde_as(as = "DisplayFromStr")]
-    analog_output_1: f64,
+    pub analog_output_1: f64,
     /// 模拟输出2
     #[serde(rename = "BoxAnalogOut_2")]
     #[serde_as(as = "DisplayFromStr")]
-    analog_output_2: f64,
+    pub analog_output_2: f64,
     /// 模拟输入1
     #[serde(rename = "BoxAnalogIn_1")]
     #[serde_as(as = "DisplayFromStr")]
-    analog_input_1: f64,
+    pub analog_input_1: f64,
     /// 模拟输入2
     #[serde(rename = "BoxAnalogIn_2")]
     #[serde_as(as = "DisplayFromStr")]
-    analog_input_2: f64,
+    pub analog_input_2: f64,
 }
 
-#[derive(Default, Debug, Serialize, Deserialize, PartialEq)]
+#[derive(Default, Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct StateAndError {
     /// 机器人状态
     #[serde(rename = "robotState")]
-    state: RobotMode,
+    pub state: RobotMode,
     /// 机器人是否使能
     #[serde(rename = "robotEnabled")]
-    enabled: u8,
+    pub enabled: u8,
     /// 机器人是否暂停
     #[serde(rename = "robotPaused")]
-    paused: u8,
+    pub paused: u8,
     /// 机器人是否运动中
     #[serde(rename = "robotMoving")]
-    moving: u8,
+    pub moving: u8,
     /// 机器人是否平滑过渡完成
     #[serde(rename = "robotBlendingDone")]
-    blending_done: u8,
+    pub blending_done: u8,
     /// 是否到达目标位置
     #[serde(rename = "InPos")]
-    in_position: u8,
+    pub in_position: u8,
     /// 错误轴ID
     #[serde(rename = "Error_AxisID")]
-    error_axis_id: u8,
+    pub error_axis_id: u8,
     /// 错误代码
     #[serde(rename = "Error_Code")]
-    error_code: RobotError,
+    pub error_code: RobotError,
     /// 刹车状态
     #[serde(rename = "BrakeState")]
-    brake_state: [u8; 6],
+    pub brake_state: [u8; 6],
     /// 轴状态
     #[serde(rename = "nAxisStatus")]
-    axis_status: [u8; 6],
+    pub axis_status: [u8; 6],
     /// 轴错误代码
     #[serde(rename = "nAxisErrorCode")]
-    axis_error_code: [u8; 6],
+    pub axis_error_code: [u8; 6],
     /// 重置安全空间
     #[serde(rename = "nResetSafeSpace")]
-    reset_safe_space: [u8; 1],
+    pub reset_safe_space: [u8; 1],
     /// 轴组状态
     #[serde(rename = "nAxisGroupStatus")]
-    axis_group_status: [u8; 1],
+    pub axis_group_status: [u8; 1],
     /// 轴组错误代码
     #[serde(rename = "nAxisGroupErrorCode")]
-    axis_group_error_code: [u8; 1],
+    pub axis_group_error_code: [u8; 1],
 }
 
-#[derive(Default, Debug, Serialize, Deserialize, PartialEq)]
+#[derive(Default, Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct HardLoad {
     /// EtherCAT总帧数
     #[serde(rename = "EtherCAT_TotalFrame")]
-    total_frame: u32,
+    pub total_frame: u32,
     /// EtherCAT每秒帧数
     #[serde(rename = "EtherCAT_FramesPerSecond")]
-    frames_per_second: u32,
+    pub frames_per_second: u32,
     /// EtherCAT总丢帧数
     #[serde(rename = "EtherCAT_TotalLostFrame")]
-    total_lost_frame: u32,
+    pub total_lost_frame: u32,
     /// EtherCAT发送错误帧数
     #[serde(rename = "EtherCAT_TxErrorFrame")]
-    tx_error_frame: u32,
+    pub tx_error_frame: u32,
     /// EtherCAT接收错误帧数
     #[serde(rename = "EtherCAT_RxErrorFrame")]
-    rx_error_frame: u32,
+    pub rx_error_frame: u32,
     /// 48V输入电压
     #[serde(rename = "Box48IN_Voltage")]
-    input_voltage: f64,
+    pub input_voltage: f64,
     /// 48V输入电流
     #[serde(rename = "Box48IN_Current")]
-    input_current: f64,
+    pub input_current: f64,
     /// 48V输出电压
     #[serde(rename = "Box48Out_Voltage")]
-    output_voltage: f64,
+    pub output_voltage: f64,
     /// 48V输出电流
     #[serde(rename = "Box48Out_Current")]
-    output_current: f64,
+    pub output_current: f64,
     /// 从站温度
     #[serde(rename = "Slave_temperature")]
-    slave_temperature: [f64; 3],
+    pub slave_temperature: [f64; 3],
     /// 从站电压
     #[serde(rename = "Slave_Voltage")]
-    slave_voltage: [f64; 3],
+    pub slave_voltage: [f64; 3],
 }
 
-#[derive(Default, Debug, Serialize, Deserialize, PartialEq)]
+#[derive(Default, Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct FTData {
     /// 力控状态
     #[serde(rename = "FTControlState")]
-    control_state: u8,
+    pub control_state: u8,
     /// 力控数据
     #[serde(rename = "FTData")]
-    data: [f64; 6],
+    pub data: [f64; 6],
     /// 力控源数据
     #[serde(rename = "FTSrcData")]
-    src_data: [f64; 6],
+    pub src_data: [f64; 6],
 }
 
 #[serde_as]
-#[derive(Default, Debug, Serialize, Deserialize, PartialEq)]
+#[derive(Default, Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Script {
     /// 错误代码
     #[serde(rename = "errorCode")]
     #[serde_as(as = "DisplayFromStr")]
-    error_code: u16,
+    pub error_code: u16,
     /// 命令ID
     #[serde(rename = "cmdid")]
-    cmd_id: [String; 6],
+    pub cmd_id: [String; 6],
     /// 全局变量
     #[serde(rename = "GlobalVar")]
-    global_var: Vec<()>,
+    pub global_var: Vec<()>,
 }
 
-#[derive(Default, Debug, Serialize, Deserialize, PartialEq)]
+#[derive(Default, Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct PluginsData {}
 
 #[cfg(test)]
@@ -0,0 +1,394 @@
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{Receiver, SyncSender, TryRecvError, TrySendError, sync_channel};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle, sleep};
+use std::time::{Duration, Instant};
+
+use robot_behavior::{RobotException, RobotResult};
+
+use crate::{
+    Kinematics, ValidationError, Workspace, frame::pose_to_iso, robot_impl::RobotImpl,
+    validation::check_joint_limits,
+};
+
+/// 点到点的距离，单位与输入相同
+fn distance(a: &[f64; 3], b: &[f64; 3]) -> f64 {
+    (0..3).map(|i| (a[i] - b[i]).powi(2)).sum::<f64>().sqrt()
+}
+
+/// 默认的推送周期
+///
+/// 每个设定点都要经过指令端口并等待控制器应答，一次往返通常就要数毫秒，
+/// [`Robot::CONTROL_PERIOD`](robot_behavior::Robot::CONTROL_PERIOD) 的 1 ms 在 TCP 上无法维持。
+pub const DEFAULT_SERVO_PERIOD: Duration = Duration::from_millis(20);
+
+/// 伺服会话的参数
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ServoConfig {
+    /// 推送设定点的周期
+    pub period: Duration,
+    /// `StartServo` 的 servo_time，控制器由当前位置运动到新设定点所用的时间，通常与周期相同
+    pub servo_time: Duration,
+    /// `StartServo` 的 lookahead_time，控制器的前瞻时间，越大轨迹越平滑但跟随延迟越大
+    pub lookahead_time: Duration,
+    /// 队列模式下最多缓存的设定点数量
+    pub queue_capacity: usize,
+    /// 连续错过截止时间的周期数达到该值时结束会话，`None` 表示不限制
+    pub max_consecutive_overruns: Option<u32>,
+}
+
+impl Default for ServoConfig {
+    fn default() -> Self {
+        ServoConfig::new(DEFAULT_SERVO_PERIOD)
+    }
+}
+
+impl ServoConfig {
+    /// 以 `period` 为推送周期与 servo_time，不使用前瞻
+    pub fn new(period: Duration) -> Self {
+        ServoConfig {
+            period,
+            servo_time: period,
+            lookahead_time: Duration::ZERO,
+            queue_capacity: 64,
+            max_consecutive_overruns: None,
+        }
+    }
+
+    pub fn with_servo_time(mut self, servo_time: Duration) -> Self {
+        self.servo_time = servo_time;
+        self
+    }
+
+    pub fn with_lookahead_time(mut self, lookahead_time: Duration) -> Self {
+        self.lookahead_time = lookahead_time;
+        self
+    }
+
+    pub fn with_queue_capacity(mut self, capacity: usize) -> Self {
+        self.queue_capacity = capacity;
+        self
+    }
+
+    pub fn with_max_consecutive_overruns(mut self, overruns: u32) -> Self {
+        self.max_consecutive_overruns = Some(overruns);
+        self
+    }
+
+    pub(crate) fn check(&self) -> RobotResult<()> {
+        if self.period.is_zero() || self.servo_time.is_zero() {
+            return Err(RobotException::InvalidInstruction(
+                "servo period and servo time must be positive".into(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// 一个伺服设定点
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ServoTarget<const N: usize> {
+    /// 关节角，单位 °
+    Joint([f64; N]),
+    /// 工具末端在用户坐标系下的位姿，单位 mm 与 °，工具与用户坐标取自会话开始时的设置
+    Pose([f64; 6]),
+}
+
+/// 传给设定点回调的周期信息
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ServoTick {
+    /// 周期序号，从 0 开始
+    pub index: u64,
+    /// 距离会话开始经过的时间
+    pub elapsed: Duration,
+}
+
+/// 伺服循环的时序统计
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ServoStats {
+    /// 已推送的设定点数
+    pub cycles: u64,
+    /// 处理时间超过周期、错过下一个截止时间的次数
+    pub overruns: u64,
+    /// 队列模式下队列为空、没有推送设定点的周期数
+    pub starved: u64,
+    /// 周期实际开始时刻晚于计划时刻的最大值
+    pub max_jitter: Duration,
+    /// 周期实际开始时刻晚于计划时刻的平均值
+    pub mean_jitter: Duration,
+    /// 单个周期的最长处理时间，包括获取设定点与等待控制器应答
+    pub max_latency: Duration,
+}
+
+pub(crate) enum ServoSource<const N: usize> {
+    Callback(Box<dyn FnMut(ServoTick) -> Option<ServoTarget<N>> + Send>),
+    Queue(Receiver<ServoTarget<N>>),
+}
+
+/// 伺服线程对每个设定点做的本地检查，与普通运动的下发前校验一致
+pub(crate) struct ServoGuard<const N: usize> {
+    pub kinematics: Kinematics<N>,
+    pub workspace: Workspace,
+    pub joint_vel: [f64; N],
+    /// 末端最大线速度，单位 m/s
+    pub cartesian_vel: f64,
+    /// 工具坐标与用户坐标，单位 mm 与 °
+    pub tool: [f64; 6],
+    pub user_frame: [f64; 6],
+    /// 上一个关节设定点，会话开始时为当前关节角
+    pub last_joint: [f64; N],
+    /// 上一个设定点的工具末端位置，单位 mm
+    pub last_tcp: [f64; 3],
+}
+
+impl<const N: usize> ServoGuard<N> {
+    /// 检查限位与工作空间，以及相邻设定点在 `servo_time` 内的关节速度与末端线速度
+    fn check(&mut self, target: &ServoTarget<N>, servo_time: Duration) -> RobotResult<()> {
+        let tcp = match target {
+            ServoTarget::Joint(joint) => {
+                check_joint_limits(
+                    joint,
+                    &self.kinematics.joint_min,
+                    &self.kinematics.joint_max,
+                )?;
+                let vel: [f64; N] = std::array::from_fn(|i| {
+                    (joint[i] - self.last_joint[i]).abs() / servo_time.as_secs_f64()
+                });
+                if let Some(i) = (0..N).find(|&i| vel[i] > self.joint_vel[i]) {
+                    let (value, max) = (vel[i], self.joint_vel[i]);
+                    Err(ValidationError::JointVelocity { joint: i, value, max })?;
+                }
+                self.last_joint = *joint;
+                self.kinematics.forward_iso(joint) * pose_to_iso(&self.tool)
+            }
+            ServoTarget::Pose(pose) => pose_to_iso(&self.user_frame) * pose_to_iso(pose),
+        };
+        let point = tcp.translation.vector.into();
+        self.workspace.check(point)?;
+        // 单位由 mm/s 换算为 m/s
+        let vel = distance(&point, &self.last_tcp) / servo_time.as_secs_f64() / 1e3;
+        if vel > self.cartesian_vel {
+            Err(ValidationError::CartesianVelocity { value: vel, max: self.cartesian_vel })?;
+        }
+        self.last_tcp = point;
+        Ok(())
+    }
+}
+
+struct Shared {
+    running: AtomicBool,
+    stats: Mutex<ServoStats>,
+}
+
+/// 伺服会话的运行标志，[`HansRobot`](crate::HansRobot) 用它在会话结束后清除运动标志
+#[derive(Clone)]
+pub(crate) struct ServoHandle(Arc<Shared>);
+
+impl ServoHandle {
+    pub fn is_running(&self) -> bool {
+        self.0.running.load(Ordering::Acquire)
+    }
+}
+
+/// 运行中的伺服会话
+///
+/// 伺服线程使用独立的指令连接，按照固定周期取得设定点并推送给控制器，
+/// 主连接仍可用于读取状态或 [`stop`](robot_behavior::Robot::stop)。
+/// 会话以任何方式结束时都会发送 `GrpStop` 退出伺服模式：回调返回 `None`、队列被关闭、
+/// 调用 [`stop`](Self::stop) 或会话被丢弃时，先等待一个 servo_time 与 lookahead_time
+/// 让机器人到达最后一个设定点；推送失败、设定点未通过检查或回调 panic 时立即停止。
+pub struct ServoSession<const N: usize> {
+    shared: Arc<Shared>,
+    sender: Option<SyncSender<ServoTarget<N>>>,
+    handle: Option<JoinHandle<RobotResult<()>>>,
+}
+
+impl<const N: usize> ServoSession<N> {
+    pub(crate) fn start(
+        robot_impl: RobotImpl<N>,
+        guard: ServoGuard<N>,
+        config: ServoConfig,
+        source: Option<Box<dyn FnMut(ServoTick) -> Option<ServoTarget<N>> + Send>>,
+    ) -> RobotResult<Self> {
+        let (sender, source) = match source {
+            Some(callback) => (None, ServoSource::Callback(callback)),
+            None => {
+                let (sender, receiver) = sync_channel(config.queue_capacity.max(1));
+                (Some(sender), ServoSource::Queue(receiver))
+            }
+        };
+        let shared = Arc::new(Shared {
+            running: AtomicBool::new(true),
+            stats: Mutex::new(ServoStats::default()),
+        });
+
+        let thread_shared = shared.clone();
+        let handle = thread::Builder::new()
+            .name("hans-servo".into())
+            .spawn(move || run(thread_shared, robot_impl, guard, config, source))?;
+
+        Ok(ServoSession { shared, sender, handle: Some(handle) })
+    }
+
+    /// 将设定点加入队列，只能用于 [`start_servo_queue`](crate::HansRobot::start_servo_queue)
+    /// 开始的会话
+    ///
+    /// 队列已满时返回错误而不会阻塞，调用方应当降低写入速度。
+    pub fn push(&self, target: ServoTarget<N>) -> RobotResult<()> {
+        let Some(sender) = &self.sender else {
+            return Err(RobotException::InvalidInstruction(
+                "servo session is driven by a callback".into(),
+            ));
+        };
+        match sender.try_send(target) {
+            Ok(()) if self.is_running() => Ok(()),
+            Err(TrySendError::Full(_)) => Err(RobotException::UnprocessableInstructionError(
+                "servo queue is full".into(),
+            )),
+            _ => Err(RobotException::UnprocessableInstructionError(
+                "servo session has ended".into(),
+            )),
+        }
+    }
+
+    pub(crate) fn handle(&self) -> ServoHandle {
+        ServoHandle(self.shared.clone())
+    }
+
+    /// 伺服线程是否仍在运行
+    pub fn is_running(&self) -> bool {
+        self.shared.running.load(Ordering::Acquire)
+    }
+
+    /// 当前的时序统计
+    pub fn stats(&self) -> ServoStats {
+        *self.shared.stats.lock().unwrap()
+    }
+
+    /// 结束会话并等待伺服线程退出，返回最终的统计；会话因错误结束时返回该错误
+    pub fn stop(mut self) -> RobotResult<ServoStats> {
+        self.shutdown()?;
+        Ok(self.stats())
+    }
+
+    fn shutdown(&mut self) -> RobotResult<()> {
+        self.shared.running.store(false, Ordering::Release);
+        match self.handle.take().map(JoinHandle::join) {
+            Some(Ok(result)) => result,
+            Some(Err(_)) => Err(RobotException::UnprocessableInstructionError(
+                "servo thread panicked".into(),
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<const N: usize> Drop for ServoSession<N> {
+    fn drop(&mut self) {
+        let _ = self.shutdown();
+    }
+}
+
+fn run<const N: usize>(
+    shared: Arc<Shared>,
+    mut robot_impl: RobotImpl<N>,
+    mut guard: ServoGuard<N>,
+    config: ServoConfig,
+    mut source: ServoSource<N>,
+) -> RobotResult<()> {
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        servo_loop(&shared, &mut robot_impl, &mut guard, &config, &mut source)
+    }))
+    .unwrap_or_else(|_| {
+        Err(RobotException::UnprocessableInstructionError(
+            "servo callback panicked".into(),
+        ))
+    });
+    if result.is_ok() {
+        sleep(config.servo_time + config.lookahead_time);
+    }
+    let stopped = robot_impl.robot_move_stop(0);
+    shared.running.store(false, Ordering::Release);
+    // 会话本身的错误优先于退出伺服模式时的错误
+    result.and(stopped)
+}
+
+fn servo_loop<const N: usize>(
+    shared: &Shared,
+    robot_impl: &mut RobotImpl<N>,
+    guard: &mut ServoGuard<N>,
+    config: &ServoConfig,
+    source: &mut ServoSource<N>,
+) -> RobotResult<()> {
+    let started = Instant::now();
+    let mut deadline = started;
+    let mut index = 0;
+    let mut consecutive_overruns = 0;
+    let mut total_jitter = Duration::ZERO;
+
+    while shared.running.load(Ordering::Acquire) {
+        if let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+            sleep(remaining);
+        }
+        let tick_start = Instant::now();
+        let jitter = tick_start.saturating_duration_since(deadline);
+        let tick = ServoTick { index, elapsed: tick_start - started };
+
+        let target = match source {
+            ServoSource::Callback(callback) => match callback(tick) {
+                Some(target) => Some(target),
+                None => return Ok(()),
+            },
+            ServoSource::Queue(receiver) => match receiver.try_recv() {
+                Ok(target) => Some(target),
+                Err(TryRecvError::Empty) => None,
+                Err(TryRecvError::Disconnected) => return Ok(()),
+            },
+        };
+        if let Some(target) = &target {
+            guard.check(target, config.servo_time)?;
+            match *target {
+                ServoTarget::Joint(joint) => robot_impl.push_servo_j((0, joint))?,
+                ServoTarget::Pose(pose) => {
+                    robot_impl.push_servo_p((0, [pose, guard.tool, guard.user_frame]))?
+                }
+            }
+        }
+
+        let latency = tick_start.elapsed();
+        deadline += config.period;
+        let now = Instant::now();
+        let overrun = now > deadline;
+        total_jitter += jitter;
+        {
+            let mut stats = shared.stats.lock().unwrap();
+            match target {
+                Some(_) => stats.cycles += 1,
+                None => stats.starved += 1,
+            }
+            stats.overruns += overrun as u64;
+            stats.max_jitter = stats.max_jitter.max(jitter);
+            stats.mean_jitter = total_jitter.div_f64((index + 1) as f64);
+            stats.max_latency = stats.max_latency.max(latency);
+        }
+
+        if overrun {
+            // 错过的周期不再补推，从当前时刻重新计时
+            deadline = now;
+            consecutive_overruns += 1;
+            if config
+                .max_consecutive_overruns
+                .is_some_and(|max| consecutive_overruns >= max)
+            {
+                return Err(RobotException::UnprocessableInstructionError(format!(
+                    "servo loop missed {consecutive_overruns} deadlines in a row"
+                )));
+            }
+        } else {
+            consecutive_overruns = 0;
+        }
+        index += 1;
+    }
+    Ok(())
+}
@@ -0,0 +1,1853 @@
+use std::collections::HashMap;
+use std::f64::consts::TAU;
+use std::io::{ErrorKind, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle, sleep};
+use std::time::{Duration, Instant};
+
+use nalgebra::{Isometry3, Matrix6, Translation3, Vector3, Vector6};
+use robot_behavior::RobotResult;
+
+use crate::frame::{iso_to_pose, pose_to_iso};
+use crate::hans::{
+    HANS_ROBOT_DH, HANS_ROBOT_JOINT_ACC, HANS_ROBOT_JOINT_VEL, HANS_ROBOT_MAX_JOINTS,
+    HANS_ROBOT_MAX_LOAD, HANS_ROBOT_MIN_JOINTS,
+};
+use crate::kinematics::Kinematics;
+use crate::robot_error::RobotError;
+use crate::robot_mode::RobotMode;
+use crate::types::*;
+
+/// 仿真控制器的轴数
+const SIM_N: usize = 6;
+/// 空闲时检查新连接的间隔
+const ACCEPT_INTERVAL: Duration = Duration::from_millis(10);
+/// 客户端连接的读超时，用于及时响应停止请求
+const CLIENT_READ_TIMEOUT: Duration = Duration::from_millis(100);
+/// 上电后的初始关节角，避开零位的奇异位形
+const HOME_JOINT: [f64; SIM_N] = [0., -60., 90., -30., 90., 0.];
+/// 仿真机器人的运动学模型
+const SIM_KINEMATICS: Kinematics<SIM_N> =
+    Kinematics::new(HANS_ROBOT_DH, HANS_ROBOT_MIN_JOINTS, HANS_ROBOT_MAX_JOINTS);
+/// 默认上报的机器人型号
+const DEFAULT_ROBOT_MODEL: u16 = 30;
+/// 笛卡尔空间默认最大线速度，单位 mm/s
+const DEFAULT_LINEAR_MAX_VEL: f64 = 1000.;
+/// 笛卡尔空间默认最大线加速度，单位 mm/s^2
+const DEFAULT_LINEAR_MAX_ACC: f64 = 2500.;
+/// 笛卡尔运动中姿态变化的角速度，单位 deg/s
+const ROTATION_VEL: f64 = 90.;
+/// 平滑插值的峰值速度与平均速度之比
+const PEAK_VEL_RATIO: f64 = 1.5;
+/// 逆解时姿态误差相对位置误差的权重，单位 mm/rad
+const IK_ROT_WEIGHT: f64 = 1000.;
+const IK_MAX_ITERS: usize = 200;
+const IK_TOLERANCE: f64 = 1e-6;
+
+/// 路点轨迹的状态，与 [`ReadMovePathState`](Command::ReadMovePathState) 返回值一致
+const PATH_PUSHING: u8 = 1;
+const PATH_READY: u8 = 3;
+const PATH_FAILED: u8 = 5;
+
+type Joint = [f64; SIM_N];
+type Pose6 = [f64; 6];
+
+/// 本地仿真控制器
+///
+/// 在本机 TCP 端口上实现与控制器相同的文本指令协议：每条指令通过
+/// [`CommandSerde::from_str`] 解析，内部维护关节、位姿与状态机，并按时间积分运动过程，
+/// 运动结束后状态由 [`RobotMode::Moving`] 回到 [`RobotMode::StandBy`]。
+/// 状态不满足时返回与真实控制器一致的 `Fail` 错误码，
+/// 可以在没有硬件的情况下端到端地运行 [`HansS30`](crate::HansS30)。
+///
+/// 关节单位为度，位姿为 `[x, y, z, rx, ry, rz]`，单位为毫米与度。需要开启 `simulator` 特性。
+pub struct Simulator {
+    controller: Arc<Mutex<SimController>>,
+    addr: SocketAddr,
+    running: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Simulator {
+    /// 在本机随机端口上启动一个已上电并使能的仿真控制器
+    pub fn start() -> RobotResult<Self> {
+        Self::start_on("127.0.0.1:0", true)
+    }
+
+    /// 在本机随机端口上启动一个刚开机的仿真控制器，需要依次连接电箱、上电、启动主站并使能
+    pub fn start_cold() -> RobotResult<Self> {
+        Self::start_on("127.0.0.1:0", false)
+    }
+
+    /// 在指定地址启动仿真控制器，`ready` 为 `true` 时跳过启动流程直接进入待机状态
+    pub fn start_on(addr: &str, ready: bool) -> RobotResult<Self> {
+        let listener = TcpListener::bind(addr)?;
+        listener.set_nonblocking(true)?;
+        let addr = listener.local_addr()?;
+        let controller = Arc::new(Mutex::new(SimController::new(ready)));
+        let running = Arc::new(AtomicBool::new(true));
+
+        let thread_controller = controller.clone();
+        let thread_running = running.clone();
+        let handle = thread::Builder::new()
+            .name("hans-simulator".into())
+            .spawn(move || accept_loop(listener, thread_controller, thread_running))?;
+
+        Ok(Simulator { controller, addr, running, handle: Some(handle) })
+    }
+
+    /// 监听地址
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// 监听的 IP
+    pub fn host(&self) -> String {
+        self.addr.ip().to_string()
+    }
+
+    /// 监听的端口
+    pub fn port(&self) -> u16 {
+        self.addr.port()
+    }
+
+    /// 设置仿真时间相对真实时间的倍率，用于加速测试
+    pub fn set_time_scale(&self, scale: f64) {
+        let mut controller = self.lock();
+        controller.advance();
+        controller.time_scale = scale.max(0.);
+    }
+
+    /// 当前状态机
+    pub fn mode(&self) -> RobotMode {
+        let mut controller = self.lock();
+        controller.advance();
+        controller.mode
+    }
+
+    /// 当前关节角
+    pub fn joint(&self) -> [f64; SIM_N] {
+        let mut controller = self.lock();
+        controller.advance();
+        controller.joint
+    }
+
+    /// 当前工具末端在基坐标系下的位姿
+    pub fn pose(&self) -> [f64; 6] {
+        let mut controller = self.lock();
+        controller.advance();
+        iso_to_pose(&controller.tcp_pose())
+    }
+
+    /// 设置上报的机器人型号
+    pub fn set_robot_model(&self, model: u16) {
+        self.lock().robot_model = model;
+    }
+
+    /// 设置电箱数字输入
+    pub fn set_box_digital_input(&self, id: usize, value: bool) {
+        if let Some(input) = self.lock().box_di.get_mut(id) {
+            *input = value;
+        }
+    }
+
+    /// 设置末端数字输入
+    pub fn set_end_digital_input(&self, id: usize, value: bool) {
+        if let Some(input) = self.lock().end_di.get_mut(id) {
+            *input = value;
+        }
+    }
+
+    /// 注入一个轴错误，机器人停止运动并进入 [`RobotMode::Error`]，需要复位后重新使能
+    pub fn inject_error(&self, error: RobotError, axis: u8) {
+        let mut controller = self.lock();
+        controller.advance();
+        controller.fault(error, axis);
+    }
+
+    /// 注入一个伺服驱动器故障，`code` 为 `ReadAxisErrorCode` 返回的驱动器错误码，
+    /// 控制器报警码记为 [`RobotError::NoNameError`]
+    pub fn inject_axis_fault(&self, axis: u8, code: u16) {
+        let mut controller = self.lock();
+        controller.advance();
+        if let Some(axis_code) = controller.axis_error.get_mut(axis as usize) {
+            *axis_code = code;
+        }
+        controller.fault(RobotError::NoNameError, axis);
+    }
+
+    /// 按下急停，机器人断电并进入 [`RobotMode::EmergencyStop`]
+    pub fn press_emergency_stop(&self) {
+        let mut controller = self.lock();
+        controller.advance();
+        controller.estop = true;
+        controller.halt();
+        controller.enabled = false;
+        controller.powered = false;
+        controller.mode = RobotMode::EmergencyStop;
+    }
+
+    /// 松开急停，状态机保持急停状态直到复位
+    pub fn release_emergency_stop(&self) {
+        self.lock().estop = false;
+    }
+
+    /// 不经过网络直接处理一条指令，返回控制器的应答
+    pub fn handle_command(&self, frame: &str) -> String {
+        self.lock().handle(frame)
+    }
+
+    /// 停止监听并断开所有连接
+    pub fn stop(&mut self) {
+        self.running.store(false, Ordering::Release);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, SimController> {
+        self.controller.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Drop for Simulator {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+fn accept_loop(
+    listener: TcpListener,
+    controller: Arc<Mutex<SimController>>,
+    running: Arc<AtomicBool>,
+) {
+    let mut clients = Vec::new();
+    while running.load(Ordering::Acquire) {
+        match listener.accept() {
+            Ok((socket, _)) => {
+                let controller = controller.clone();
+                let running = running.clone();
+                clients.push(thread::spawn(move || {
+                    serve_client(socket, controller, running)
+                }));
+            }
+            Err(e) if e.kind() == ErrorKind::WouldBlock => sleep(ACCEPT_INTERVAL),
+            Err(_) => break,
+        }
+    }
+    for client in clients {
+        let _ = client.join();
+    }
+}
+
+fn serve_client(
+    mut socket: TcpStream,
+    controller: Arc<Mutex<SimController>>,
+    running: Arc<AtomicBool>,
+) {
+    if socket.set_nonblocking(false).is_err()
+        || socket.set_read_timeout(Some(CLIENT_READ_TIMEOUT)).is_err()
+    {
+        return;
+    }
+    let mut buffer = Vec::new();
+    let mut chunk = [0_u8; 1024];
+    while running.load(Ordering::Acquire) {
+        match socket.read(&mut chunk) {
+            Ok(0) => return,
+            Ok(n) => buffer.extend_from_slice(&chunk[..n]),
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => continue,
+            Err(_) => return,
+        }
+        while let Some(end) = buffer.iter().position(|b| *b == b';') {
+            let frame: Vec<u8> = buffer.drain(..=end).collect();
+            let frame = String::from_utf8_lossy(&frame);
+            let response = controller
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .handle(frame.trim());
+            if socket.write_all(response.as_bytes()).is_err() {
+                return;
+            }
+        }
+    }
+}
+
+/// 仿真中的一段运动
+struct Motion {
+    trajectory: Trajectory,
+    elapsed: f64,
+    duration: f64,
+}
+
+enum Trajectory {
+    /// 关节空间依次经过各点
+    Joint(Vec<Joint>),
+    /// 笛卡尔空间直线依次经过各点，`tool` 为运动时使用的工具坐标
+    Linear {
+        points: Vec<Isometry3<f64>>,
+        tool: Isometry3<f64>,
+    },
+    /// 笛卡尔空间圆弧
+    Arc(ArcPath),
+}
+
+struct ArcPath {
+    start: Isometry3<f64>,
+    end: Isometry3<f64>,
+    center: Vector3<f64>,
+    u: Vector3<f64>,
+    w: Vector3<f64>,
+    radius: f64,
+    angle: f64,
+    fixed_pose: bool,
+    tool: Isometry3<f64>,
+}
+
+struct MovePath {
+    state: u8,
+    cartesian: bool,
+    joints: Vec<Joint>,
+    poses: Vec<Pose6>,
+    /// 关节轨迹为速度比例，笛卡尔轨迹为线速度
+    speed: f64,
+}
+
+struct SimController {
+    mode: RobotMode,
+    box_connected: bool,
+    powered: bool,
+    master_started: bool,
+    enabled: bool,
+    estop: bool,
+    error: RobotError,
+    error_axis: u8,
+    axis_error: [u16; SIM_N],
+    robot_model: u16,
+
+    joint: Joint,
+    joint_vel: Joint,
+    tcp_vel: [f64; 6],
+    tcp: Pose6,
+    ucs: Pose6,
+    override_ratio: f64,
+    joint_max_vel: Joint,
+    joint_max_acc: Joint,
+    linear_max_vel: f64,
+    linear_max_acc: f64,
+    payload: (f64, [f64; 3]),
+    tool_motion: bool,
+
+    box_ci: [bool; 8],
+    box_di: [bool; 8],
+    box_co: [bool; 8],
+    box_do: [bool; 8],
+    box_ai: [f64; 2],
+    box_ao: [f64; 2],
+    box_ao_mode: [u8; 2],
+    end_di: [bool; 4],
+    end_do: [bool; 4],
+    end_ai: [f64; 2],
+
+    force_control: bool,
+    force_free_drive: bool,
+    ft_data: [f64; 6],
+
+    motion: Option<Motion>,
+    paused: bool,
+    paths: HashMap<String, MovePath>,
+    pushing_path_l: Option<String>,
+    path_override: f64,
+    servo_time: Option<f64>,
+
+    time_scale: f64,
+    last_update: Instant,
+}
+
+/// 将指令映射到对应的处理函数，请求与应答的类型由指令本身决定
+macro_rules! dispatch {
+    ($sim:expr, $frame:expr, $name:expr; $($cmd:ident => $handler:expr),* $(,)?) => {
+        match $name {
+            $(stringify!($cmd) => Some($sim.reply::<{ Command::$cmd }, _, _>($frame, $handler)),)*
+            _ => None,
+        }
+    };
+}
+
+impl SimController {
+    fn new(ready: bool) -> Self {
+        SimController {
+            mode: if ready {
+                RobotMode::StandBy
+            } else {
+                RobotMode::ElectricBoxDisconnect
+            },
+            box_connected: ready,
+            powered: ready,
+            master_started: ready,
+            enabled: ready,
+            estop: false,
+            error: RobotError::NoError,
+            error_axis: 0,
+            axis_error: [0; SIM_N],
+            robot_model: DEFAULT_ROBOT_MODEL,
+            joint: HOME_JOINT,
+            joint_vel: [0.; SIM_N],
+            tcp_vel: [0.; 6],
+            tcp: [0.; 6],
+            ucs: [0.; 6],
+            override_ratio: 1.,
+            joint_max_vel: HANS_ROBOT_JOINT_VEL,
+            joint_max_acc: HANS_ROBOT_JOINT_ACC,
+            linear_max_vel: DEFAULT_LINEAR_MAX_VEL,
+            linear_max_acc: DEFAULT_LINEAR_MAX_ACC,
+            payload: (0., [0.; 3]),
+            tool_motion: false,
+            box_ci: [false; 8],
+            box_di: [false; 8],
+            box_co: [false; 8],
+            box_do: [false; 8],
+            box_ai: [0.; 2],
+            box_ao: [0.; 2],
+            box_ao_mode: [0; 2],
+            end_di: [false; 4],
+            end_do: [false; 4],
+            end_ai: [0.; 2],
+            force_control: false,
+            force_free_drive: false,
+            ft_data: [0.; 6],
+            motion: None,
+            paused: false,
+            paths: HashMap::new(),
+            pushing_path_l: None,
+            path_override: 1.,
+            servo_time: None,
+            time_scale: 1.,
+            last_update: Instant::now(),
+        }
+    }
+
+    /// 处理一条完整的指令并返回应答
+    fn handle(&mut self, frame: &str) -> String {
+        self.advance();
+        let name = frame.split([',', ';']).next().unwrap_or_default().trim();
+        if let Some(response) = self.handle_raw(name, frame) {
+            return response;
+        }
+        let response = dispatch!(self, frame, name;
+            // ! 初始化指令
+            OSCmd => |s, _: u8| {
+                *s = SimController { time_scale: s.time_scale, ..SimController::new(false) };
+                Ok(())
+            },
+            ConnectToBox => |s, _: ()| s.connect_to_box(),
+            Electrify => |s, _: ()| s.electrify(),
+            BlackOut => |s, _: ()| s.black_out(),
+            StartMaster => |s, _: ()| s.start_master(),
+            CloseMaster => |s, _: ()| s.close_master(),
+            IsSimulation => |_, _: ()| Ok(true),
+            ReadControllerState => |s, _: ()| Ok(s.master_started),
+            ReadRobotModel => |s, _: u8| Ok(s.robot_model),
+            // ! 轴组控制指令
+            GrpEnable => |s, _: u8| s.enable(),
+            GrpDisable => |s, _: u8| s.disable(),
+            GrpReset => |s, _: u8| s.reset(),
+            GrpStop => |s, _: u8| s.stop_motion(),
+            GrpInterrupt => |s, _: u8| s.interrupt(),
+            GrpContinue => |s, _: u8| s.resume(),
+            GrpOpenFreeDriver => |s, _: u8| s.free_driver(true),
+            GrpCloseFreeDriver => |s, _: u8| s.free_driver(false),
+            // ! 电箱控制指令
+            ReadBoxInfo => |s, _: u8| Ok(BoxInfo {
+                is_connected: s.box_connected,
+                is_voltage48v_on: s.powered,
+                voltage48v_out_voltage: if s.powered { 48. } else { 0. },
+                voltage48v_out_current: if s.powered { 1.2 } else { 0. },
+                is_remote_button_on: false,
+                is_three_stage_button_on: false,
+            }),
+            SetBoxCO => |s, (id, value): (u8, bool)| set_io(&mut s.box_co, id, value),
+            SetBoxDO => |s, (id, value): (u8, bool)| set_io(&mut s.box_do, id, value),
+            SetBoxAOMode => |s, (id, mode): (u8, u8)| set_io(&mut s.box_ao_mode, id, mode),
+            SetBoxAO => |s, (id, value, mode): (u8, f64, u8)| {
+                set_io(&mut s.box_ao_mode, id, mode)?;
+                set_io(&mut s.box_ao, id, value)
+            },
+            SetEndDO => |s, (_, id, value): (u8, u8, bool)| set_io(&mut s.end_do, id, value),
+            ReadEAI => |s, (_, id): (u8, u8)| {
+                s.end_ai.get(id as usize).copied().ok_or(RobotError::RECParametersError)
+            },
+            // ! 状态读取与设置指令
+            SetOverride => |s, (_, value): (u8, f64)| {
+                if !(0.01..=1.).contains(&value) {
+                    return Err(RobotError::RECParametersError);
+                }
+                s.override_ratio = value;
+                Ok(())
+            },
+            SetToolMotion => |s, (_, value): (u8, bool)| {
+                s.tool_motion = value;
+                Ok(())
+            },
+            SetPayload => |s, (_, load): (u8, Load)| {
+                if !(0. ..=HANS_ROBOT_MAX_LOAD).contains(&load.mass) {
+                    return Err(RobotError::RECParametersError);
+                }
+                s.payload = (load.mass, load.centroid);
+                Ok(())
+            },
+            SetJointMaxVel => |s, (_, value): (u8, Joint)| {
+                s.joint_max_vel = positive(value)?;
+                Ok(())
+            },
+            SetJointMaxAcc => |s, (_, value): (u8, Joint)| {
+                s.joint_max_acc = positive(value)?;
+                Ok(())
+            },
+            SetLinearMaxVel => |s, (_, value): (u8, f64)| {
+                s.linear_max_vel = positive([value])?[0];
+                Ok(())
+            },
+            SetLinearMaxAcc => |s, (_, value): (u8, f64)| {
+                s.linear_max_acc = positive([value])?[0];
+                Ok(())
+            },
+            ReadJointMaxVel => |s, _: u8| Ok(s.joint_max_vel),
+            ReadJointMaxAcc => |s, _: u8| Ok(s.joint_max_acc),
+            ReadJointMaxJerk => |s, _: u8| Ok(s.joint_max_acc.map(|a| a * 10.)),
+            ReadLinearMaxVel => |s, _: u8| Ok([s.linear_max_vel; SIM_N]),
+            ReadEmergencyInfo => |s, _: u8| Ok(EmergencyInfo {
+                is_estop: s.estop,
+                esto_code: u8::from(s.estop),
+                is_safety_guard: false,
+                safety_guard_code: 0,
+            }),
+            ReadRobotState => |s, _: u8| Ok(s.robot_flag()),
+            ReadAxisErrorCode => |s, _: u8| Ok(s.axis_error),
+            ReadCurFSM => |s, _: u8| Ok(s.mode),
+            // ! 位置、速度、电流读取指令
+            ReadCmdPos => |s, _: u8| Ok(CmdPose {
+                joint: s.target_joint(),
+                pose_o_to_ee: iso_to_pose(&(flange_pose(&s.target_joint()) * pose_to_iso(&s.tcp))),
+            }),
+            ReadActPos => |s, _: u8| {
+                let pose = s.tcp_pose();
+                Ok(ActPose {
+                    joint: s.joint,
+                    pose_o_to_ee: iso_to_pose(&pose),
+                    pose_f_to_ee: s.tcp,
+                    pose_u_to_ee: iso_to_pose(&(pose_to_iso(&s.ucs).inverse() * pose)),
+                })
+            },
+            ReadCmdJointVel => |s, _: u8| Ok(s.joint_vel),
+            ReadActJointVel => |s, _: u8| Ok(s.joint_vel),
+            ReadCmdTcpVel => |s, _: u8| Ok(s.tcp_vel),
+            ReadActTcpVel => |s, _: u8| Ok(s.tcp_vel),
+            ReadCmdJointCur => |s, _: u8| Ok(s.joint_current()),
+            ReadActJointCur => |s, _: u8| Ok(s.joint_current()),
+            ReadTcpVelocity => |s, _: u8| {
+                let v = &s.tcp_vel;
+                Ok((
+                    Vector3::new(v[0], v[1], v[2]).norm(),
+                    Vector3::new(v[3], v[4], v[5]).norm(),
+                ))
+            },
+            // ! 工具坐标与用户坐标读写指令
+            SetCurTCP => |s, (_, pose): (u8, Pose6)| {
+                s.ensure_idle()?;
+                s.tcp = pose;
+                Ok(())
+            },
+            SetCurUCS => |s, (_, pose): (u8, Pose6)| {
+                s.ensure_idle()?;
+                s.ucs = pose;
+                Ok(())
+            },
+            ReadCurTCP => |s, _: u8| Ok(s.tcp),
+            ReadCurUCS => |s, _: u8| Ok(s.ucs),
+            // ! 力控控制指令
+            SetForceControlState => |s, (_, state): (u8, bool)| {
+                s.force_control = state;
+                Ok(())
+            },
+            ReadFTControlState => |s, _: u8| Ok(s.force_control),
+            SetForceToolCoordinateMotion => |_, _: (u8, bool)| Ok(()),
+            GrpFCInterrupt => |_, _: u8| Ok(()),
+            GrpFCContinue => |_, _: u8| Ok(()),
+            SetForceZero => |s, _: u8| {
+                s.ft_data = [0.; 6];
+                Ok(())
+            },
+            HRSetMaxSearchVelocities => |_, _: (u8, f64, f64)| Ok(()),
+            HRSetForceControlStrategy => |_, _: (u8, u8)| Ok(()),
+            SetFTPosition => |_, _: (u8, Pose6)| Ok(()),
+            HRSetPIDControlParams => |_, _: (u8, Joint)| Ok(()),
+            HRSetMassParams => |_, _: (u8, [f64; 6])| Ok(()),
+            HRSetDampParams => |_, _: (u8, [f64; 6])| Ok(()),
+            HRSetStiffParams => |_, _: (u8, [f64; 6])| Ok(()),
+            HRSetControlGoal => |_, _: (u8, [f64; 6], f64)| Ok(()),
+            SetForceFreeDriveMode => |s, (_, state): (u8, bool)| {
+                s.force_free_drive = state;
+                Ok(())
+            },
+            ReadFTCabData => |s, _: u8| Ok(s.ft_data),
+            // ! 通用运动类控制指令
+            MoveRelJ => |s, (_, rel): (u8, RelJ)| {
+                let mut target = s.joint;
+                let axis = target.get_mut(rel.id as usize).ok_or(RobotError::RECParametersError)?;
+                *axis += if rel.dir { rel.dis } else { -rel.dis };
+                s.move_joint(target, s.joint_max_vel[rel.id as usize])
+            },
+            MoveRelL => |s, (_, rel): (u8, RelL)| {
+                let mut delta = [0.; 6];
+                *delta.get_mut(rel.id as usize).ok_or(RobotError::RECParametersError)? =
+                    if rel.dir { rel.dis } else { -rel.dis };
+                let target = s.offset_pose(&delta, rel.coord == 1);
+                s.move_linear(vec![target], s.linear_max_vel)
+            },
+            // 直线插补时 nrelMoveType 的取值含义未经确认，仿真器只接受关节增量
+            WayPointRel => |s, (_, p): (u8, WayPointRel<SIM_N>)| {
+                if p.move_mode != 0 {
+                    return Err(RobotError::RECParametersError);
+                }
+                let target =
+                    std::array::from_fn(|i| if p.is_move[i] { s.joint[i] + p.dis[i] } else { s.joint[i] });
+                s.move_joint(target, p.vel)
+            },
+            WayPointEx => |s, (_, p): (u8, WayPointEx<SIM_N>)| {
+                let tool = pose_to_iso(&p.tcp);
+                let ucs = pose_to_iso(&p.ucs);
+                let target = s.resolve(p.use_joint, &p.joint, &p.pose, &ucs, &tool)?;
+                if p.move_mode == 0 {
+                    s.move_joint(target, p.vel)
+                } else {
+                    let target = flange_pose(&target) * tool;
+                    s.move_linear_with(vec![target], p.vel, tool)
+                }
+            },
+            WayPoint => |s, (_, p): (u8, WayPoint<SIM_N>)| {
+                let target = s.resolve_current(p.use_joint, &p.joint, &p.pose)?;
+                if p.move_mode == 0 {
+                    s.move_joint(target, p.vel)
+                } else {
+                    s.move_linear(vec![flange_pose(&target) * pose_to_iso(&s.tcp)], p.vel)
+                }
+            },
+            WayPoint2 => |s, (_, p): (u8, WayPoint2<SIM_N>)| {
+                let target = s.resolve_current(p.use_joint, &p.joint, &p.pose1)?;
+                match p.move_mode {
+                    0 => s.move_joint(target, p.vel),
+                    1 => s.move_linear(vec![flange_pose(&target) * pose_to_iso(&s.tcp)], p.vel),
+                    _ => {
+                        let start = s.tcp_pose();
+                        let pass = s.ucs_pose(&p.pose2);
+                        let end = flange_pose(&target) * pose_to_iso(&s.tcp);
+                        s.move_arc(start, pass, end, false, None, p.vel)
+                    }
+                }
+            },
+            MoveJ => |s, (_, p): (u8, MoveJ<SIM_N>)| {
+                let target = s.resolve_current(p.use_joint, &p.joint, &p.pose)?;
+                s.move_joint(target, p.vel)
+            },
+            MoveL => |s, (_, p): (u8, MoveL<SIM_N>)| {
+                let target = s.resolve_current(p.use_joint, &p.joint, &p.pose)?;
+                s.move_linear(vec![flange_pose(&target) * pose_to_iso(&s.tcp)], p.vel)
+            },
+            MoveC => |s, (_, p): (u8, MoveC)| {
+                let start = s.ucs_pose(&p.pose_start);
+                let pass = s.ucs_pose(&p.pose_pass);
+                let end = s.ucs_pose(&p.pose_end);
+                let turns = (p.move_mode == 1).then_some(p.rad_len);
+                s.move_arc(start, pass, end, p.is_fixed_pose, turns, p.vel)
+            },
+            // ! 连续轨迹运动类控制指令
+            StartPushMovePath => |s, (_, config): (u8, StartPushMovePathJ)| {
+                if !(0. ..=1.).contains(&config.speed) || config.speed == 0. {
+                    return Err(RobotError::RECParametersError);
+                }
+                s.paths.insert(config.path_name, MovePath {
+                    state: PATH_PUSHING,
+                    cartesian: false,
+                    joints: Vec::new(),
+                    poses: Vec::new(),
+                    speed: config.speed,
+                });
+                Ok(())
+            },
+            PushMovePathJ => |s, (_, name, joint): (u8, String, Joint)| {
+                check_joint(&joint)?;
+                s.pushing_path(&name)?.joints.push(joint);
+                Ok(())
+            },
+            EndPushMovePath => |s, (_, name): (u8, String)| {
+                let path = s.pushing_path(&name)?;
+                path.state = if path.joints.is_empty() && path.poses.is_empty() {
+                    PATH_FAILED
+                } else {
+                    PATH_READY
+                };
+                if s.pushing_path_l.as_deref() == Some(name.as_str()) {
+                    s.pushing_path_l = None;
+                }
+                Ok(())
+            },
+            MovePath => |s, (_, name): (u8, String)| s.run_path(&name, false),
+            ReadMovePathState => |s, (_, name): (u8, String)| {
+                s.paths.get(&name).map(|path| path.state).ok_or(RobotError::RECParametersError)
+            },
+            UpdateMovePathName => |s, (_, name, new_name): (u8, String, String)| {
+                let path = s.paths.remove(&name).ok_or(RobotError::RECParametersError)?;
+                s.paths.insert(new_name, path);
+                Ok(())
+            },
+            DelMovePath => |s, (_, name): (u8, String)| {
+                s.paths.remove(&name).map(|_| ()).ok_or(RobotError::RECParametersError)
+            },
+            ReadSoftMotionProcess => |s, _: u8| Ok(s.motion_progress()),
+            InitMovePathL => |s, (_, config): (u8, StartPushMovePathL)| {
+                if config.vel <= 0. {
+                    return Err(RobotError::RECParametersError);
+                }
+                s.pushing_path_l = Some(config.path_name.clone());
+                s.paths.insert(config.path_name, MovePath {
+                    state: PATH_PUSHING,
+                    cartesian: true,
+                    joints: Vec::new(),
+                    poses: Vec::new(),
+                    speed: config.vel,
+                });
+                Ok(())
+            },
+            PushMovePathL => |s, (_, pose): (u8, Pose6)| {
+                let name = s.pushing_path_l.clone().ok_or(RobotError::RECParametersError)?;
+                s.pushing_path(&name)?.poses.push(pose);
+                Ok(())
+            },
+            MovePathL => |s, (_, name): (u8, String)| s.run_path(&name, true),
+            SetMovePathOverride => |s, (_, value): (u8, f64)| {
+                if !(0.01..=1.).contains(&value) {
+                    return Err(RobotError::RECParametersError);
+                }
+                s.path_override = value;
+                Ok(())
+            },
+            // ! Servo运动类控制指令
+            StartServo => |s, (_, servo_time, _): (u8, f64, f64)| {
+                s.ensure_ready()?;
+                if servo_time <= 0. {
+                    return Err(RobotError::RECParametersError);
+                }
+                s.servo_time = Some(servo_time);
+                Ok(())
+            },
+            PushServoJ => |s, (_, joint): (u8, Joint)| s.servo_to(joint),
+            PushServoP => |s, (_, [pose, tcp, ucs]): (u8, [Pose6; 3])| {
+                let tool = pose_to_iso(&tcp);
+                let target = s.resolve(false, &s.joint.clone(), &pose, &pose_to_iso(&ucs), &tool)?;
+                s.servo_to(target)
+            },
+        );
+        response.unwrap_or_else(|| {
+            format!(
+                "{name},Fail,{},;",
+                CommandSerde::to_string(&RobotError::RECCmdFormatError)
+            )
+        })
+    }
+
+    /// 解析请求并调用处理函数，按应答类型序列化结果
+    fn reply<const C: Command, D, S>(
+        &mut self,
+        frame: &str,
+        handler: impl FnOnce(&mut Self, D) -> Result<S, RobotError>,
+    ) -> String
+    where
+        D: CommandSerde + 'static,
+        S: CommandSerde,
+    {
+        let status = match CommandRequest::<C, D>::from_str(frame) {
+            Ok(request) => handler(self, request.data),
+            Err(_) => Err(RobotError::RECCmdFormatError),
+        };
+        CommandResponse::<C, S>::from(status).to_string()
+    }
+
+    /// 参数数量可变的指令，应答的长度由请求决定
+    fn handle_raw(&mut self, name: &str, frame: &str) -> Option<String> {
+        let args: Vec<&str> = frame
+            .trim_end_matches(';')
+            .trim_end_matches(',')
+            .split(',')
+            .skip(1)
+            .collect();
+        let result = match name {
+            "ReadBoxCI" => read_io(&self.box_ci, args.len()),
+            "ReadBoxDI" => read_io(&self.box_di, args.len()),
+            "ReadBoxCO" => read_io(&self.box_co, args.len()),
+            "ReadBoxDO" => read_io(&self.box_do, args.len()),
+            "ReadBoxAI" => read_io(&self.box_ai, args.len()),
+            "ReadBoxAO" => read_io(&self.box_ao, args.len()),
+            "ReadEI" | "ReadEO" => {
+                let io = if name == "ReadEI" {
+                    &self.end_di
+                } else {
+                    &self.end_do
+                };
+                args.iter()
+                    .skip(1)
+                    .map(|port| {
+                        let port: usize =
+                            port.parse().map_err(|_| RobotError::RECCmdFormatError)?;
+                        io.get(port)
+                            .map(CommandSerde::to_string)
+                            .ok_or(RobotError::RECParametersError)
+                    })
+                    .collect()
+            }
+            "PushMovePaths" => self.push_move_paths(&args).map(|_| Vec::new()),
+            _ => return None,
+        };
+        Some(match result {
+            Ok(values) if values.is_empty() => format!("{name},OK,;"),
+            Ok(values) => format!("{name},OK,{},;", values.join(",")),
+            Err(e) => format!("{name},Fail,{},;", CommandSerde::to_string(&e)),
+        })
+    }
+
+    fn push_move_paths(&mut self, args: &[&str]) -> Result<(), RobotError> {
+        let [_, name, mode, count, points @ ..] = args else {
+            return Err(RobotError::RECCmdFormatError);
+        };
+        let count: usize = count.parse().map_err(|_| RobotError::RECCmdFormatError)?;
+        if points.len() != count * 6 {
+            return Err(RobotError::RECCmdFormatError);
+        }
+        let points = points
+            .chunks_exact(6)
+            .map(|point| Pose6::from_str(&point.join(",")))
+            .collect::<RobotResult<Vec<_>>>()
+            .map_err(|_| RobotError::RECCmdFormatError)?;
+        let path = self.pushing_path(name)?;
+        if *mode == "0" {
+            for point in points {
+                let joint: Joint = point;
+                check_joint(&joint)?;
+                path.joints.push(joint);
+            }
+        } else {
+            path.cartesian = true;
+            path.poses.extend(points);
+        }
+        Ok(())
+    }
+
+    // ! 时间积分
+
+    /// 将仿真时间推进到当前时刻
+    fn advance(&mut self) {
+        let now = Instant::now();
+        let dt = (now - self.last_update).as_secs_f64() * self.time_scale;
+        self.last_update = now;
+        if dt <= 0. {
+            return;
+        }
+
+        let previous_joint = self.joint;
+        let previous_pose = self.tcp_pose();
+        if let Some(mut motion) = self.motion.take() {
+            if !self.paused {
+                motion.elapsed = (motion.elapsed + dt).min(motion.duration);
+            }
+            let s = smooth_step(motion.elapsed / motion.duration);
+            match self.evaluate(&motion.trajectory, s) {
+                Some(joint) => {
+                    self.joint = joint;
+                    if motion.elapsed < motion.duration {
+                        self.motion = Some(motion);
+                    } else if self.mode == RobotMode::Moving {
+                        self.mode = RobotMode::StandBy;
+                    }
+                }
+                // 运动过程中经过不可达的位姿
+                None => self.fault(RobotError::RECParametersError, 0),
+            }
+        }
+
+        self.joint_vel = std::array::from_fn(|i| (self.joint[i] - previous_joint[i]) / dt);
+        let pose = self.tcp_pose();
+        let linear = (pose.translation.vector - previous_pose.translation.vector) / dt;
+        let angular = (pose.rotation * previous_pose.rotation.inverse())
+            .scaled_axis()
+            .map(f64::to_degrees)
+            / dt;
+        self.tcp_vel = [
+            linear.x, linear.y, linear.z, angular.x, angular.y, angular.z,
+        ];
+    }
+
+    fn evaluate(&self, trajectory: &Trajectory, s: f64) -> Option<Joint> {
+        match trajectory {
+            Trajectory::Joint(points) => {
+                let (i, t) = segment(points.len(), s);
+                Some(std::array::from_fn(|k| {
+                    points[i][k] + (points[i + 1][k] - points[i][k]) * t
+                }))
+            }
+            Trajectory::Linear { points, tool } => {
+                let (i, t) = segment(points.len(), s);
+                let pose = points[i].lerp_slerp(&points[i + 1], t);
+                solve_ik(&self.joint, &(pose * tool.inverse()))
+            }
+            Trajectory::Arc(arc) => {
+                let theta = arc.angle * s;
+                let position =
+                    arc.center + arc.radius * (arc.u * theta.cos() + arc.w * theta.sin());
+                let rotation = if arc.fixed_pose {
+                    arc.start.rotation
+                } else {
+                    arc.start.rotation.slerp(&arc.end.rotation, s)
+                };
+                let pose = Isometry3::from_parts(Translation3::from(position), rotation);
+                solve_ik(&self.joint, &(pose * arc.tool.inverse()))
+            }
+        }
+    }
+
+    fn motion_progress(&self) -> (f64, u16) {
+        let Some(motion) = &self.motion else {
+            return (0., 0);
+        };
+        let s = motion.elapsed / motion.duration;
+        let points = match &motion.trajectory {
+            Trajectory::Joint(points) => points.len(),
+            Trajectory::Linear { points, .. } => points.len(),
+            Trajectory::Arc(_) => 2,
+        };
+        (s, segment(points, smooth_step(s)).0 as u16)
+    }
+
+    // ! 状态机
+
+    fn connect_to_box(&mut self) -> Result<(), RobotError> {
+        self.box_connected = true;
+        if matches!(
+            self.mode,
+            RobotMode::UnInitialized | RobotMode::ElectricBoxDisconnect
+        ) {
+            self.mode = RobotMode::Blackout48V;
+        }
+        Ok(())
+    }
+
+    fn electrify(&mut self) -> Result<(), RobotError> {
+        if !self.box_connected || self.estop {
+            return Err(RobotError::ControllerNotInit);
+        }
+        if !self.powered {
+            self.powered = true;
+            self.mode = if self.master_started {
+                RobotMode::Disable
+            } else {
+                RobotMode::ControllerDisconnect
+            };
+        }
+        Ok(())
+    }
+
+    fn black_out(&mut self) -> Result<(), RobotError> {
+        self.halt();
+        self.powered = false;
+        self.enabled = false;
+        // 故障状态保持到复位为止
+        if !self.mode.requires_reset() {
+            self.mode = RobotMode::Blackout48V;
+        }
+        Ok(())
+    }
+
+    fn start_master(&mut self) -> Result<(), RobotError> {
+        if !self.powered {
+            return Err(RobotError::ControllerNotInit);
+        }
+        if !self.master_started {
+            self.master_started = true;
+            self.mode = RobotMode::Disable;
+        }
+        Ok(())
+    }
+
+    fn close_master(&mut self) -> Result<(), RobotError> {
+        self.halt();
+        self.master_started = false;
+        self.enabled = false;
+        if self.powered && !self.mode.requires_reset() {
+            self.mode = RobotMode::ControllerDisconnect;
+        }
+        Ok(())
+    }
+
+    fn enable(&mut self) -> Result<(), RobotError> {
+        if !self.master_started
+            || self.estop
+            || matches!(self.mode, RobotMode::Error | RobotMode::EmergencyStop)
+        {
+            return Err(RobotError::ControllerNotInit);
+        }
+        if !self.enabled {
+            self.enabled = true;
+            self.mode = RobotMode::StandBy;
+        }
+        Ok(())
+    }
+
+    fn disable(&mut self) -> Result<(), RobotError> {
+        if !self.master_started {
+            return Err(RobotError::ControllerNotInit);
+        }
+        self.halt();
+        self.enabled = false;
+        if !self.mode.requires_reset() {
+            self.mode = RobotMode::Disable;
+        }
+        Ok(())
+    }
+
+    fn reset(&mut self) -> Result<(), RobotError> {
+        if self.estop {
+            return Err(RobotError::ControllerNotInit);
+        }
+        self.error = RobotError::NoError;
+        self.error_axis = 0;
+        self.axis_error = [0; SIM_N];
+        self.mode = match self.mode {
+            RobotMode::EmergencyStop => RobotMode::Blackout48V,
+            RobotMode::Error => RobotMode::Disable,
+            mode => mode,
+        };
+        Ok(())
+    }
+
+    fn stop_motion(&mut self) -> Result<(), RobotError> {
+        self.halt();
+        if matches!(self.mode, RobotMode::Moving | RobotMode::RobotHolding) {
+            self.mode = RobotMode::StandBy;
+        }
+        Ok(())
+    }
+
+    fn interrupt(&mut self) -> Result<(), RobotError> {
+        if self.mode == RobotMode::Moving {
+            self.paused = true;
+            self.mode = RobotMode::RobotHolding;
+        }
+        Ok(())
+    }
+
+    fn resume(&mut self) -> Result<(), RobotError> {
+        if self.mode == RobotMode::RobotHolding {
+            self.paused = false;
+            self.mode = RobotMode::Moving;
+        }
+        Ok(())
+    }
+
+    fn free_driver(&mut self, open: bool) -> Result<(), RobotError> {
+        match (open, self.mode) {
+            (true, RobotMode::StandBy) => self.mode = RobotMode::FreeDriver,
+            (false, RobotMode::FreeDriver) => self.mode = RobotMode::StandBy,
+            (true, RobotMode::FreeDriver) | (false, RobotMode::StandBy) => {}
+            _ => return Err(RobotError::ControllerNotInit),
+        }
+        Ok(())
+    }
+
+    /// 停止当前运动，位置保持在当前位置
+    fn halt(&mut self) {
+        self.motion = None;
+        self.paused = false;
+        self.servo_time = None;
+        self.joint_vel = [0.; SIM_N];
+        self.tcp_vel = [0.; 6];
+    }
+
+    fn fault(&mut self, error: RobotError, axis: u8) {
+        self.halt();
+        self.enabled = false;
+        self.error = error;
+        self.error_axis = axis;
+        self.mode = RobotMode::Error;
+    }
+
+    fn robot_flag(&self) -> RobotFlag {
+        RobotFlag {
+            is_move: self.motion.is_some(),
+            is_enable: self.enabled,
+            is_error: self.mode == RobotMode::Error,
+            error_code: u8::try_from(self.error.code()).unwrap_or(u8::MAX),
+            error_id: self.error_axis,
+            is_breaking: !self.enabled,
+            is_emergency_stop: self.estop || self.mode == RobotMode::EmergencyStop,
+            is_safety_guard: false,
+            is_power_on: self.powered,
+            is_connect_to_box: self.box_connected,
+            is_move_waypoint: self.motion.is_some(),
+            is_arrived: self.motion.is_none(),
+        }
+    }
+
+    fn joint_current(&self) -> Joint {
+        // 简单地以负载与速度估计电流
+        std::array::from_fn(|i| 0.5 + 0.05 * self.payload.0 + 0.002 * self.joint_vel[i].abs())
+    }
+
+    /// 运动指令要求机器人使能且处于待机状态
+    fn ensure_ready(&self) -> Result<(), RobotError> {
+        if self.motion.is_some() || matches!(self.mode, RobotMode::Moving | RobotMode::RobotHolding)
+        {
+            return Err(RobotError::RECOnMoving);
+        }
+        if !self.enabled || self.mode != RobotMode::StandBy {
+            return Err(RobotError::ControllerNotInit);
+        }
+        Ok(())
+    }
+
+    /// 修改坐标系要求机器人不在运动中
+    fn ensure_idle(&self) -> Result<(), RobotError> {
+        if self.motion.is_some() {
+            return Err(RobotError::RECOnMoving);
+        }
+        Ok(())
+    }
+
+    // ! 运动生成
+
+    fn tcp_pose(&self) -> Isometry3<f64> {
+        flange_pose(&self.joint) * pose_to_iso(&self.tcp)
+    }
+
+    /// 当前运动的目标关节角，没有运动时为当前关节角
+    fn target_joint(&self) -> Joint {
+        match self.motion.as_ref().map(|m| &m.trajectory) {
+            Some(Trajectory::Joint(points)) => points[points.len() - 1],
+            _ => self.joint,
+        }
+    }
+
+    /// 用户坐标系下的位姿转换到基坐标系
+    fn ucs_pose(&self, pose: &Pose6) -> Isometry3<f64> {
+        pose_to_iso(&self.ucs) * pose_to_iso(pose)
+    }
+
+    /// 在当前用户坐标系或工具坐标系下偏移当前位姿，返回基坐标系下的目标
+    fn offset_pose(&self, delta: &[f64; 6], tool_frame: bool) -> Isometry3<f64> {
+        let ucs = pose_to_iso(&self.ucs);
+        let current = ucs.inverse() * self.tcp_pose();
+        let delta = pose_to_iso(delta);
+        if tool_frame {
+            ucs * current * delta
+        } else {
+            ucs * Isometry3::from_parts(
+                Translation3::from(current.translation.vector + delta.translation.vector),
+                delta.rotation * current.rotation,
+            )
+        }
+    }
+
+    /// 将关节或位姿形式的目标统一为关节角
+    fn resolve(
+        &self,
+        use_joint: bool,
+        joint: &Joint,
+        pose: &Pose6,
+        ucs: &Isometry3<f64>,
+        tool: &Isometry3<f64>,
+    ) -> Result<Joint, RobotError> {
+        if use_joint {
+            return Ok(*joint);
+        }
+        let target = ucs * pose_to_iso(pose) * tool.inverse();
+        solve_ik(&self.joint, &target).ok_or(RobotError::RECParametersError)
+    }
+
+    /// 使用当前的工具与用户坐标系解析目标
+    fn resolve_current(
+        &self,
+        use_joint: bool,
+        joint: &Joint,
+        pose: &Pose6,
+    ) -> Result<Joint, RobotError> {
+        self.resolve(
+            use_joint,
+            joint,
+            pose,
+            &pose_to_iso(&self.ucs),
+            &pose_to_iso(&self.tcp),
+        )
+    }
+
+    fn move_joint(&mut self, target: Joint, vel: f64) -> Result<(), RobotError> {
+        self.ensure_ready()?;
+        check_joint(&target)?;
+        if vel <= 0. {
+            return Err(RobotError::RECParametersError);
+        }
+        let duration = (0..SIM_N)
+            .map(|i| {
+                (target[i] - self.joint[i]).abs()
+                    / (vel.min(self.joint_max_vel[i]) * self.override_ratio)
+            })
+            .fold(0., f64::max);
+        self.start_motion(Trajectory::Joint(vec![self.joint, target]), duration)
+    }
+
+    fn move_linear(&mut self, points: Vec<Isometry3<f64>>, vel: f64) -> Result<(), RobotError> {
+        let tool = pose_to_iso(&self.tcp);
+        self.move_linear_with(points, vel, tool)
+    }
+
+    fn move_linear_with(
+        &mut self,
+        points: Vec<Isometry3<f64>>,
+        vel: f64,
+        tool: Isometry3<f64>,
+    ) -> Result<(), RobotError> {
+        self.ensure_ready()?;
+        if vel <= 0. {
+            return Err(RobotError::RECParametersError);
+        }
+        let mut points = points;
+        points.insert(0, flange_pose(&self.joint) * tool);
+        // 提前检查所有目标点是否可达
+        let mut seed = self.joint;
+        for point in &points[1..] {
+            seed =
+                solve_ik(&seed, &(point * tool.inverse())).ok_or(RobotError::RECParametersError)?;
+        }
+        let duration = points
+            .windows(2)
+            .map(|p| self.cartesian_duration(&p[0], &p[1], vel))
+            .sum();
+        self.start_motion(Trajectory::Linear { points, tool }, duration)
+    }
+
+    fn move_arc(
+        &mut self,
+        start: Isometry3<f64>,
+        pass: Isometry3<f64>,
+        end: Isometry3<f64>,
+        fixed_pose: bool,
+        turns: Option<f64>,
+        vel: f64,
+    ) -> Result<(), RobotError> {
+        self.ensure_ready()?;
+        if vel <= 0. || turns.is_some_and(|turns| turns <= 0.) {
+            return Err(RobotError::RECParametersError);
+        }
+        let a = start.translation.vector;
+        let ab = pass.translation.vector - a;
+        let ac = end.translation.vector - a;
+        let normal = ab.cross(&ac);
+        // 三点共线时无法确定圆弧
+        if normal.norm() < 1e-6 * ab.norm() * ac.norm() || normal.norm() < 1e-9 {
+            return Err(RobotError::RECParametersError);
+        }
+        let center = a
+            + (ac.norm_squared() * normal.cross(&ab) + ab.norm_squared() * ac.cross(&normal))
+                / (2. * normal.norm_squared());
+        let radius = (a - center).norm();
+        let u = (a - center) / radius;
+        let w = normal.normalize().cross(&u);
+        let angle_of = |p: &Vector3<f64>| {
+            let d = p - center;
+            d.dot(&w).atan2(d.dot(&u)).rem_euclid(TAU)
+        };
+        // 整圆运动经过三点确定圆后走完给定的圈数，回到起点
+        let (angle, end) = match turns {
+            Some(turns) => (TAU * turns, start),
+            None => (angle_of(&end.translation.vector), end),
+        };
+        let tool = pose_to_iso(&self.tcp);
+        let arc = ArcPath { start, end, center, u, w, radius, angle, fixed_pose, tool };
+
+        // 起点需要与当前位置一致，并检查终点可达
+        let current = self.tcp_pose();
+        if (current.translation.vector - a).norm() > 1. {
+            return Err(RobotError::RECParametersError);
+        }
+        solve_ik(&self.joint, &(end * tool.inverse())).ok_or(RobotError::RECParametersError)?;
+
+        let vel = vel.min(self.linear_max_vel) * self.override_ratio;
+        let duration = (radius * angle / vel).max(
+            start.rotation.angle_to(&end.rotation).to_degrees()
+                / (ROTATION_VEL * self.override_ratio),
+        );
+        self.start_motion(Trajectory::Arc(arc), duration)
+    }
+
+    fn cartesian_duration(&self, from: &Isometry3<f64>, to: &Isometry3<f64>, vel: f64) -> f64 {
+        let distance = (to.translation.vector - from.translation.vector).norm();
+        let angle = from.rotation.angle_to(&to.rotation).to_degrees();
+        (distance / (vel.min(self.linear_max_vel) * self.override_ratio))
+            .max(angle / (ROTATION_VEL * self.override_ratio))
+    }
+
+    fn start_motion(&mut self, trajectory: Trajectory, duration: f64) -> Result<(), RobotError> {
+        // 平滑插值的峰值速度高于平均速度，按峰值不超过限制延长时间
+        let duration = (duration * PEAK_VEL_RATIO).max(1e-3);
+        self.motion = Some(Motion { trajectory, elapsed: 0., duration });
+        self.mode = RobotMode::Moving;
+        Ok(())
+    }
+
+    fn pushing_path(&mut self, name: &str) -> Result<&mut MovePath, RobotError> {
+        self.paths
+            .get_mut(name)
+            .ok_or(RobotError::RECParametersError)
+    }
+
+    fn run_path(&mut self, name: &str, cartesian: bool) -> Result<(), RobotError> {
+        self.ensure_ready()?;
+        let path = self.paths.get(name).ok_or(RobotError::RECParametersError)?;
+        if path.state != PATH_READY || path.cartesian != cartesian {
+            return Err(RobotError::RECParametersError);
+        }
+        if cartesian {
+            let points = path.poses.iter().map(|p| self.ucs_pose(p)).collect();
+            let vel = path.speed * self.path_override;
+            self.move_linear(points, vel)
+        } else {
+            let mut points = path.joints.clone();
+            let ratio = path.speed * self.path_override * self.override_ratio;
+            points.insert(0, self.joint);
+            let duration = points
+                .windows(2)
+                .map(|p| {
+                    (0..SIM_N)
+                        .map(|i| (p[1][i] - p[0][i]).abs() / (self.joint_max_vel[i] * ratio))
+                        .fold(0., f64::max)
+                })
+                .sum();
+            self.start_motion(Trajectory::Joint(points), duration)
+        }
+    }
+
+    fn servo_to(&mut self, target: Joint) -> Result<(), RobotError> {
+        let Some(servo_time) = self.servo_time else {
+            return Err(RobotError::ControllerNotInit);
+        };
+        check_joint(&target)?;
+        if !self.enabled || !matches!(self.mode, RobotMode::StandBy | RobotMode::Moving) {
+            return Err(RobotError::ControllerNotInit);
+        }
+        // 伺服模式下新的目标直接替换当前运动
+        self.motion = Some(Motion {
+            trajectory: Trajectory::Joint(vec![self.joint, target]),
+            elapsed: 0.,
+            duration: servo_time.max(1e-3),
+        });
+        self.mode = RobotMode::Moving;
+        Ok(())
+    }
+}
+
+// ! 辅助函数
+
+fn set_io<T: Copy>(io: &mut [T], id: u8, value: T) -> Result<(), RobotError> {
+    *io.get_mut(id as usize)
+        .ok_or(RobotError::RECParametersError)? = value;
+    Ok(())
+}
+
+fn read_io<T: CommandSerde>(io: &[T], count: usize) -> Result<Vec<String>, RobotError> {
+    if count > io.len() {
+        return Err(RobotError::RECParametersError);
+    }
+    Ok(io[..count].iter().map(CommandSerde::to_string).collect())
+}
+
+fn positive<const M: usize>(values: [f64; M]) -> Result<[f64; M], RobotError> {
+    if values.iter().all(|v| *v > 0.) {
+        Ok(values)
+    } else {
+        Err(RobotError::RECParametersError)
+    }
+}
+
+fn check_joint(joint: &Joint) -> Result<(), RobotError> {
+    let in_range = (0..SIM_N)
+        .all(|i| (HANS_ROBOT_MIN_JOINTS[i]..=HANS_ROBOT_MAX_JOINTS[i]).contains(&joint[i]));
+    if in_range {
+        Ok(())
+    } else {
+        Err(RobotError::RECParametersError)
+    }
+}
+
+fn smooth_step(s: f64) -> f64 {
+    let s = s.clamp(0., 1.);
+    s * s * (3. - 2. * s)
+}
+
+/// 将 `[0, 1]` 上的进度映射到折线的段号与段内进度
+fn segment(points: usize, s: f64) -> (usize, f64) {
+    let segments = points.saturating_sub(1).max(1);
+    let u = s * segments as f64;
+    let i = (u.floor() as usize).min(segments - 1);
+    (i, u - i as f64)
+}
+
+/// 由 [`HANS_ROBOT_DH`] 计算法兰位姿，长度单位为毫米
+fn flange_pose(joint: &Joint) -> Isometry3<f64> {
+    SIM_KINEMATICS.forward_iso(joint)
+}
+
+/// 从 `seed` 出发以阻尼最小二乘迭代求解法兰到达 `target` 的关节角
+fn solve_ik(seed: &Joint, target: &Isometry3<f64>) -> Option<Joint> {
+    let error = |joint: &Joint| {
+        let pose = flange_pose(joint);
+        let dp = target.translation.vector - pose.translation.vector;
+        let dr = (target.rotation * pose.rotation.inverse()).scaled_axis() * IK_ROT_WEIGHT;
+        Vector6::new(dp.x, dp.y, dp.z, dr.x, dr.y, dr.z)
+    };
+
+    let mut joint = *seed;
+    for _ in 0..IK_MAX_ITERS {
+        let e = error(&joint);
+        if e.norm() < IK_TOLERANCE {
+            return Some(joint);
+        }
+        // 数值雅可比，列为单位关节角（度）引起的误差变化
+        let mut jacobian = Matrix6::zeros();
+        for i in 0..SIM_N {
+            let mut perturbed = joint;
+            perturbed[i] += 1e-6;
+            jacobian.set_column(i, &((e - error(&perturbed)) / 1e-6));
+        }
+        let damping = Matrix6::identity() * 1e-4;
+        let step =
+            jacobian.transpose() * (jacobian * jacobian.transpose() + damping).try_inverse()? * e;
+        let scale = (10. / step.amax()).min(1.);
+        for i in 0..SIM_N {
+            joint[i] += step[i] * scale;
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_simulator_state_machine() {
+        let simulator = Simulator::start_cold().unwrap();
+        let fail = |code: RobotError| CommandSerde::to_string(&code);
+
+        assert_eq!(
+            simulator.handle_command("GrpEnable,0,;"),
+            format!("GrpEnable,Fail,{},;", fail(RobotError::ControllerNotInit))
+        );
+        for command in [
+            "ConnectToBox,;",
+            "Electrify,;",
+            "StartMaster,;",
+            "GrpEnable,0,;",
+        ] {
+            let response = simulator.handle_command(command);
+            assert!(response.contains(",OK,"), "{command} -> {response}");
+        }
+        assert_eq!(simulator.mode(), RobotMode::StandBy);
+        assert_eq!(
+            simulator.handle_command("MoveRelJ,0,9,1,10,;"),
+            format!("MoveRelJ,Fail,{},;", fail(RobotError::RECParametersError))
+        );
+        assert_eq!(
+            simulator.handle_command("Unknown,;"),
+            format!("Unknown,Fail,{},;", fail(RobotError::RECCmdFormatError))
+        );
+
+        let start = simulator.joint();
+        assert_eq!(
+            simulator.handle_command("MoveRelJ,0,2,1,10,;"),
+            "MoveRelJ,OK,;"
+        );
+        assert_eq!(simulator.mode(), RobotMode::Moving);
+        assert_eq!(
+            simulator.handle_command("MoveRelJ,0,2,1,10,;"),
+            format!("MoveRelJ,Fail,{},;", fail(RobotError::RECOnMoving))
+        );
+        while simulator.mode() == RobotMode::Moving {
+            sleep(Duration::from_millis(5));
+        }
+        assert_eq!(simulator.mode(), RobotMode::StandBy);
+        assert!((simulator.joint()[2] - start[2] - 10.).abs() < 1e-9);
+
+        simulator.press_emergency_stop();
+        assert!(simulator.handle_command("GrpReset,0,;").contains("Fail"));
+        simulator.release_emergency_stop();
+        for command in ["GrpReset,0,;", "Electrify,;", "GrpEnable,0,;"] {
+            let response = simulator.handle_command(command);
+            assert!(response.contains(",OK,"), "{command} -> {response}");
+        }
+        assert_eq!(simulator.mode(), RobotMode::StandBy);
+    }
+
+    #[test]
+    fn test_simulator_linear_and_arc_motion() {
+        let simulator = Simulator::start().unwrap();
+        simulator.set_time_scale(100.);
+        let wait = || {
+            while simulator.mode() == RobotMode::Moving {
+                sleep(Duration::from_millis(5));
+            }
+        };
+
+        let start = simulator.pose();
+        assert_eq!(
+            simulator.handle_command("MoveRelL,0,0,1,50,0,;"),
+            "MoveRelL,OK,;"
+        );
+        wait();
+        let pose = simulator.pose();
+        assert!((pose[0] - start[0] - 50.).abs() < 1e-3);
+        assert!((pose[1] - start[1]).abs() < 1e-3);
+
+        let p = |dx: f64, dy: f64| {
+            [
+                pose[0] + dx,
+                pose[1] + dy,
+                pose[2],
+                pose[3],
+                pose[4],
+                pose[5],
+            ]
+            .map(|v| CommandSerde::to_string(&v))
+            .join(",")
+        };
+        let collinear = format!(
+            "MoveC,0,{},{},{},1,0,0,100,100,0,TCP,Base,0,;",
+            p(0., 0.),
+            p(10., 0.),
+            p(20., 0.)
+        );
+        assert!(simulator.handle_command(&collinear).contains("Fail"));
+        let arc = format!(
+            "MoveC,0,{},{},{},1,0,0,100,100,0,TCP,Base,0,;",
+            p(0., 0.),
+            p(30., 30.),
+            p(60., 0.)
+        );
+        assert_eq!(simulator.handle_command(&arc), "MoveC,OK,;");
+        wait();
+        let end = simulator.pose();
+        assert!((end[0] - pose[0] - 60.).abs() < 1e-3);
+        assert!((end[1] - pose[1]).abs() < 1e-3);
+    }
+
+    #[cfg(not(feature = "no_robot"))]
+    #[test]
+    fn test_simulator_end_to_end() {
+        use robot_behavior::{Coord, Pose, driver::*};
+
+        use crate::{HansS30, LifecycleStage, MoveCircular, StateSource, Workspace, WorkspaceBox};
+
+        let simulator = Simulator::start_cold().unwrap();
+        simulator.set_time_scale(100.);
+        let mut robot = HansS30::new_with_port(&simulator.host(), simulator.port()).unwrap();
+
+        assert_eq!(robot.read_model_code().unwrap(), DEFAULT_ROBOT_MODEL);
+        let cold = simulator.mode();
+        robot.set_expected_model_code(Some(DEFAULT_ROBOT_MODEL + 1));
+        assert!(robot.init().is_err());
+        assert_eq!(simulator.mode(), cold);
+        robot.set_expected_model_code(Some(DEFAULT_ROBOT_MODEL));
+        assert_eq!(robot.get_joint(), [0.; 6]);
+        robot.init().unwrap();
+        let cached = robot.cached_state().unwrap();
+        assert_eq!(
+            (cached.joint, cached.source),
+            (simulator.joint(), StateSource::Command)
+        );
+        assert_eq!(simulator.mode(), RobotMode::Disable);
+        robot.enable().unwrap();
+        assert_eq!(
+            robot.robot_impl.state_read_cur_fsm(0).unwrap(),
+            RobotMode::StandBy
+        );
+
+        let target = [10., -50., 80., -20., 80., 5.];
+        <HansS30 as MoveTo<JointSpace<6>>>::move_to(&mut robot, target).unwrap();
+        assert_eq!(
+            robot.robot_impl.state_read_cur_fsm(0).unwrap(),
+            RobotMode::Moving
+        );
+        robot.waiting_for_finish().unwrap();
+
+        let state = robot.state().unwrap();
+        let joint = state.joint.meas.q.unwrap();
+        for i in 0..6 {
+            assert!((joint[i] - target[i]).abs() < 1e-9);
+        }
+        let cached = robot.cached_state().unwrap();
+        assert_eq!((cached.joint, cached.source), (joint, StateSource::Command));
+        assert_eq!(robot.get_joint(), joint);
+
+        let offset = [5., 0., -10., 0., 0., 2.];
+        robot.with_coord(Coord::Inertial);
+        <HansS30 as MoveTo<JointSpace<6>>>::move_to(&mut robot, offset).unwrap();
+        robot.waiting_for_finish().unwrap();
+        let joint = robot.state().unwrap().joint.meas.q.unwrap();
+        for i in 0..6 {
+            assert!((joint[i] - target[i] - offset[i]).abs() < 1e-9);
+        }
+
+        // 笛卡尔增量：多方向沿用户坐标系（默认即基坐标系），单方向经 `MoveRelL` 沿工具坐标系
+        let relative_moves = [
+            (Coord::Inertial, [10., -5., 20., 0., 0., 0.]),
+            (Coord::Relative, [0., 0., 15., 0., 0., 0.]),
+            (Coord::Relative, [5., 5., 0., 0., 0., 10.]),
+        ];
+        for (coord, delta) in relative_moves {
+            let before = pose_to_iso(&simulator.pose());
+            let delta_iso = pose_to_iso(&delta);
+            let expected = if coord == Coord::Relative {
+                before * delta_iso
+            } else {
+                Isometry3::from_parts(before.translation * delta_iso.translation, before.rotation)
+            };
+            robot.with_coord(coord);
+            <HansS30 as MoveTo<FlangeSpace>>::move_to(&mut robot, Pose::from(delta)).unwrap();
+            robot.waiting_for_finish().unwrap();
+            let after = pose_to_iso(&simulator.pose());
+            assert!((after.translation.vector - expected.translation.vector).norm() < 1e-6);
+            assert!(after.rotation.angle_to(&expected.rotation) < 1e-9);
+        }
+
+        let beyond_limit = [400., 0., 0., 0., 0., 0.];
+        assert!(<HansS30 as MoveTo<JointSpace<6>>>::move_to(&mut robot, beyond_limit).is_err());
+        let everywhere = WorkspaceBox::new("everywhere", [-1e4; 3], [1e4; 3]);
+        robot.set_workspace(Workspace::new().with_keep_out(everywhere));
+        assert!(<HansS30 as MoveTo<JointSpace<6>>>::move_to(&mut robot, target).is_err());
+        robot.set_workspace(Workspace::default());
+        assert_eq!(simulator.mode(), RobotMode::StandBy);
+
+        // 位姿目标需要有限位内的逆解，直线路径上的禁止区域在两端之外也能发现
+        let [x, y, z, rx, ry, rz] = simulator.pose();
+        let unreachable = Pose::from([x + 5000., y, z, rx, ry, rz]);
+        let error = <HansS30 as MoveTo<FlangeSpace>>::move_to(&mut robot, unreachable).unwrap_err();
+        assert!(error.to_string().contains("unreachable"));
+        let obstacle = WorkspaceBox::new(
+            "obstacle",
+            [x + 95., y - 5., z - 5.],
+            [x + 105., y + 5., z + 5.],
+        );
+        robot.set_workspace(Workspace::new().with_keep_out(obstacle));
+        let across = Pose::from([x + 200., y, z, rx, ry, rz]);
+        let error = <HansS30 as MoveTo<FlangeSpace>>::move_to(&mut robot, across).unwrap_err();
+        assert!(error.to_string().contains("obstacle"));
+        robot.set_workspace(Workspace::default());
+        assert_eq!(simulator.mode(), RobotMode::StandBy);
+
+        // 重连后重新读取控制器上的工具坐标，不沿用连接断开前的记录
+        let gripper = [0., 0., 120., 0., 0., 0.];
+        robot.frames_mut().define_tool("gripper", gripper);
+        robot.set_tool("gripper").unwrap();
+        assert_eq!(robot.robot_impl.read_pose_o_to_t(0).unwrap(), gripper);
+        robot.robot_impl.set_pose_o_to_t((0, [0.; 6])).unwrap();
+        robot.robot_impl.network.reconnect().unwrap();
+        robot.set_tool("gripper").unwrap();
+        assert_eq!(robot.robot_impl.read_pose_o_to_t(0).unwrap(), gripper);
+        robot.set_tool(crate::DEFAULT_TCP_NAME).unwrap();
+
+        let start = simulator.pose();
+        let shifted = |dx: f64, dy: f64| {
+            let [x, y, z, rx, ry, rz] = start;
+            Pose::from([x + dx, y + dy, z, rx, ry, rz])
+        };
+        let collinear = [shifted(0., 0.), shifted(10., 0.), shifted(20., 0.)];
+        let [a, b, c] = collinear;
+        assert!(<HansS30 as MoveCircular<FlangeSpace>>::move_arc(&mut robot, a, b, c).is_err());
+        let [a, b, c] = [shifted(0., 0.), shifted(30., 30.), shifted(60., 0.)];
+        robot.with_coord(Coord::Relative);
+        assert!(<HansS30 as MoveCircular<FlangeSpace>>::move_arc(&mut robot, a, b, c).is_err());
+        <HansS30 as MoveCircular<FlangeSpace>>::move_circle_sync(
+            &mut robot,
+            shifted(0., 0.),
+            shifted(30., 30.),
+            shifted(60., 0.),
+            1.,
+        )
+        .unwrap();
+        let end = simulator.pose();
+        for i in 0..3 {
+            assert!((end[i] - start[i]).abs() < 1e-3);
+        }
+
+        simulator.inject_error(RobotError::RECParametersError, 2);
+        let flag = robot.robot_impl.state_read_robot_state(0).unwrap();
+        assert!(flag.is_error && !flag.is_enable);
+        assert!(robot.robot_impl.robot_enable(0).is_err());
+        robot.reset().unwrap();
+        robot.robot_impl.robot_enable(0).unwrap();
+
+        simulator.inject_axis_fault(4, 0x8611);
+        let report = robot.robot_impl.diagnose().unwrap();
+        let axis_errors: Vec<_> = report
+            .axis_errors
+            .iter()
+            .map(|e| (e.axis, e.fault))
+            .collect();
+        assert_eq!(axis_errors, [(4, Some(crate::AxisFault::FollowingError))]);
+        robot.reset().unwrap();
+        assert!(robot.robot_impl.diagnose().unwrap().axis_errors.is_empty());
+        robot.robot_impl.robot_enable(0).unwrap();
+
+        robot.shutdown().unwrap();
+        assert_eq!(simulator.mode(), RobotMode::Blackout48V);
+        robot.power_up().unwrap();
+        assert_eq!(simulator.mode(), RobotMode::StandBy);
+
+        // 急停状态下仍然可以断电，状态机保持急停直到复位
+        simulator.press_emergency_stop();
+        robot.power_down().unwrap();
+        assert!(!robot.robot_impl.box_info(0).unwrap().is_voltage48v_on);
+        assert_eq!(simulator.mode(), RobotMode::EmergencyStop);
+        let error = robot.power_up().unwrap_err();
+        assert_eq!(error.stage, LifecycleStage::ConnectToBox);
+        assert_eq!(error.mode, RobotMode::EmergencyStop);
+    }
+
+    #[test]
+    #[cfg(not(feature = "no_robot"))]
+    fn test_simulator_servo_session() {
+        use std::time::Duration;
+
+        use robot_behavior::driver::*;
+
+        use crate::{HansS30, ServoConfig, ServoTarget};
+
+        let simulator = Simulator::start().unwrap();
+        let mut robot = HansS30::new_with_port(&simulator.host(), simulator.port()).unwrap();
+        robot.enable().unwrap();
+        let start = simulator.joint();
+        let config = ServoConfig::new(Duration::from_millis(2));
+
+        let session = robot
+            .start_servo(config, move |tick| {
+                let mut joint = start;
+                joint[0] += 0.01 * (tick.index + 1) as f64;
+                (tick.index < 50).then_some(ServoTarget::Joint(joint))
+            })
+            .unwrap();
+        while session.is_running() {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        let stats = session.stop().unwrap();
+        assert_eq!((stats.cycles, stats.starved), (50, 0));
+        assert!(stats.max_jitter >= stats.mean_jitter);
+        // 会话结束后已经退出伺服模式，并且到达了最后一个设定点
+        assert!(!robot.is_moving().unwrap());
+        assert_eq!(simulator.mode(), RobotMode::StandBy);
+        assert!((simulator.joint()[0] - start[0] - 0.5).abs() < 1e-9);
+        assert!(robot.robot_impl.push_servo_j((0, start)).is_err());
+
+        // 丢弃会话同样退出伺服模式
+        let session = robot.start_servo_queue(config).unwrap();
+        session.push(ServoTarget::Joint(start)).unwrap();
+        drop(session);
+        assert!(!robot.is_moving().unwrap());
+        assert!(robot.robot_impl.push_servo_j((0, start)).is_err());
+
+        // 位姿设定点的末端速度超限
+        let session = robot.start_servo_queue(config).unwrap();
+        let mut pose = simulator.pose();
+        pose[2] += 100.;
+        session.push(ServoTarget::Pose(pose)).unwrap();
+        while session.is_running() {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        let error = session.stop().unwrap_err();
+        assert!(error.to_string().contains("cartesian velocity"));
+
+        // 速度超限的设定点会结束会话并停止运动
+        let session = robot.start_servo_queue(config).unwrap();
+        let mut joint = simulator.joint();
+        joint[0] += 0.01;
+        session.push(ServoTarget::Joint(joint)).unwrap();
+        joint[0] += 10.;
+        session.push(ServoTarget::Joint(joint)).unwrap();
+        while session.is_running() {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert!(session.push(ServoTarget::Joint(joint)).is_err());
+        assert!(session.stop().is_err());
+        assert_eq!(simulator.mode(), RobotMode::StandBy);
+    }
+
+    #[test]
+    #[cfg(not(feature = "no_robot"))]
+    fn test_simulator_waypoints() {
+        use robot_behavior::{Pose, driver::*};
+
+        use crate::HansS30;
+
+        let simulator = Simulator::start().unwrap();
+        simulator.set_time_scale(100.);
+        let mut robot = HansS30::new_with_port(&simulator.host(), simulator.port()).unwrap();
+        robot.enable().unwrap();
+
+        // 起点离当前位置较远，路点轨迹需要等待接近运动结束后才能下发
+        let start = simulator.joint();
+        let path: Vec<_> = (1..=3)
+            .map(|i| {
+                let mut joint = start;
+                joint[0] += 10. * i as f64;
+                joint[2] -= 5. * i as f64;
+                joint
+            })
+            .collect();
+        <HansS30 as MoveTraj<JointSpace<6>>>::move_waypoints(&mut robot, path.clone()).unwrap();
+        robot.waiting_for_finish().unwrap();
+        let joint = simulator.joint();
+        for i in 0..6 {
+            assert!((joint[i] - path[2][i]).abs() < 1e-6);
+        }
+
+        let [x, y, z, rx, ry, rz] = simulator.pose();
+        let path: Vec<_> = (1..=3)
+            .map(|i| Pose::from([x + 20. * i as f64, y, z - 10., rx, ry, rz]))
+            .collect();
+        <HansS30 as MoveTraj<FlangeSpace>>::move_waypoints(&mut robot, path).unwrap();
+        robot.waiting_for_finish().unwrap();
+        let pose = simulator.pose();
+        assert!((pose[0] - x - 60.).abs() < 1e-3);
+        assert!((pose[2] - z + 10.).abs() < 1e-3);
+        assert_eq!(simulator.mode(), RobotMode::StandBy);
+    }
+
+    #[test]
+    #[cfg(not(feature = "no_robot"))]
+    fn test_simulator_emergency_stop_recovery() {
+        use robot_behavior::Robot;
+
+        use crate::HansS30;
+
+        let simulator = Simulator::start().unwrap();
+        let mut robot = HansS30::new_with_port(&simulator.host(), simulator.port()).unwrap();
+
+        robot.emergency_stop().unwrap();
+        assert_eq!(simulator.mode(), RobotMode::Blackout48V);
+        robot.clear_emergency_stop().unwrap();
+        assert_eq!(simulator.mode(), RobotMode::StandBy);
+
+        simulator.press_emergency_stop();
+        assert!(robot.clear_emergency_stop().is_err());
+        simulator.release_emergency_stop();
+        robot.clear_emergency_stop().unwrap();
+        assert_eq!(simulator.mode(), RobotMode::StandBy);
+    }
+}
@@ -0,0 +1,77 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use arc_swap::ArcSwapOption;
+
+use crate::robot_state::RobotState;
+
+/// 缓存状态的来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StateSource {
+    /// 通过指令端口读取，例如 [`Arm::state`](robot_behavior::behavior::Arm::state)
+    Command,
+    /// 控制器推送的数据，来自后台状态流或 [`read_state`](robot_behavior::Robot::read_state)
+    Datasheet,
+}
+
+/// 最近一次获得的关节与末端状态
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CachedState<const N: usize> {
+    /// 关节角，单位 °
+    pub joint: [f64; N],
+    /// 末端在基坐标系下的位姿，单位 mm 与 °
+    pub pose: [f64; 6],
+    /// 收到该状态的时刻：指令读取为收到应答时，推送数据为读到该帧时，而不是写入缓存时
+    pub timestamp: Instant,
+    pub source: StateSource,
+}
+
+impl<const N: usize> CachedState<N> {
+    /// 距离获得该状态经过的时间
+    pub fn age(&self) -> Duration {
+        self.timestamp.elapsed()
+    }
+
+    /// 状态是否在 `max_age` 内获得
+    pub fn is_fresh(&self, max_age: Duration) -> bool {
+        self.age() <= max_age
+    }
+}
+
+/// 可在状态流线程中无锁更新的状态缓存
+#[derive(Clone, Default)]
+pub(crate) struct StateCache<const N: usize> {
+    latest: Arc<ArcSwapOption<CachedState<N>>>,
+}
+
+impl<const N: usize> StateCache<N> {
+    pub fn latest(&self) -> Option<CachedState<N>> {
+        self.latest.load().as_deref().copied()
+    }
+
+    /// 写入在 `timestamp` 收到的状态，早于缓存中已有状态的旧数据会被丢弃
+    pub fn update(
+        &self,
+        joint: [f64; N],
+        pose: [f64; 6],
+        source: StateSource,
+        timestamp: Instant,
+    ) -> CachedState<N> {
+        let state = CachedState { joint, pose, timestamp, source };
+        self.latest.rcu(|latest| match latest {
+            Some(latest) if latest.timestamp > timestamp => Some(latest.clone()),
+            _ => Some(Arc::new(state)),
+        });
+        state
+    }
+
+    pub fn update_from_datasheet(&self, state: &RobotState, received: Instant) {
+        let pos_and_vel = &state.pos_and_vel;
+        self.update(
+            std::array::from_fn(|i| pos_and_vel.position[i]),
+            pos_and_vel.pose_o_to_ee,
+            StateSource::Datasheet,
+            received,
+        );
+    }
+}
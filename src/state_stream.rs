@@ -0,0 +1,266 @@
+use std::net::{Shutdown, TcpStream};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::mpsc::{Receiver, SyncSender, TrySendError, sync_channel};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle, sleep};
+use std::time::{Duration, Instant};
+
+use arc_swap::ArcSwapOption;
+use robot_behavior::{RobotException, RobotResult};
+
+use crate::datasheet::DatasheetReader;
+use crate::robot_state::RobotState;
+
+/// 推送连接断开后重新连接的间隔
+const RECONNECT_INTERVAL: Duration = Duration::from_millis(500);
+
+/// 订阅者的标识，用于取消订阅
+pub type SubscriptionId = usize;
+
+/// 同时接收读到该帧时刻的回调
+type TimedCallback = Box<dyn FnMut(&RobotState, Instant) + Send>;
+
+enum Sink {
+    Channel(SyncSender<Arc<RobotState>>),
+    Callback(Box<dyn FnMut(&RobotState) + Send>),
+    Timed(TimedCallback),
+}
+
+struct Subscriber {
+    decimation: usize,
+    counter: usize,
+    sink: Sink,
+}
+
+struct Shared {
+    latest: ArcSwapOption<RobotState>,
+    /// 收到最新一帧时距 `started` 的纳秒数，尚未收到数据时为 0
+    received: AtomicU64,
+    started: Instant,
+    running: AtomicBool,
+    frames: AtomicU64,
+    next_id: AtomicUsize,
+    /// 分发时先复制列表再逐个调用，回调中可以订阅或取消订阅
+    subscribers: Mutex<Vec<(SubscriptionId, Arc<Mutex<Subscriber>>)>>,
+    /// 当前连接的副本，用于在停止时打断阻塞的读取
+    socket: Mutex<Option<TcpStream>>,
+}
+
+/// 后台状态流
+///
+/// 在独立线程中持续读取控制器推送的数据，无锁地保存最新的 [`RobotState`]，
+/// 并按照各自的抽取率分发给通道或回调订阅者。连接断开时后台线程会自动重连。
+pub struct StateStream {
+    shared: Arc<Shared>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl StateStream {
+    /// 连接到指定 IP 与 JSON 数据推送端口并启动后台接收线程
+    pub fn start(host: &str, port: u16) -> RobotResult<Self> {
+        let reader = DatasheetReader::connect(host, port)?;
+        let shared = Arc::new(Shared {
+            latest: ArcSwapOption::empty(),
+            received: AtomicU64::new(0),
+            started: Instant::now(),
+            running: AtomicBool::new(true),
+            frames: AtomicU64::new(0),
+            next_id: AtomicUsize::new(0),
+            subscribers: Mutex::new(Vec::new()),
+            socket: Mutex::new(reader.try_clone_stream().ok()),
+        });
+
+        let host = host.to_string();
+        let thread_shared = shared.clone();
+        let handle = thread::Builder::new()
+            .name("hans-state-stream".into())
+            .spawn(move || receive_loop(thread_shared, reader, host, port))?;
+
+        Ok(StateStream { shared, handle: Some(handle) })
+    }
+
+    /// 后台线程是否仍在运行
+    pub fn is_running(&self) -> bool {
+        self.shared.running.load(Ordering::Acquire)
+    }
+
+    /// 最新一帧状态，尚未收到任何数据时返回 `None`
+    ///
+    /// 连接断开或正在重连时返回的是断开前的最后一帧，需要判断新旧时使用
+    /// [`latest_within`](Self::latest_within)。
+    pub fn latest(&self) -> Option<Arc<RobotState>> {
+        self.shared.latest.load_full()
+    }
+
+    /// 在 `max_age` 内收到的最新一帧状态，没有时返回 `None`
+    pub fn latest_within(&self, max_age: Duration) -> Option<Arc<RobotState>> {
+        self.last_frame_age()
+            .filter(|age| *age <= max_age)
+            .and_then(|_| self.latest())
+    }
+
+    /// 距离收到最新一帧经过的时间，尚未收到任何数据时返回 `None`
+    pub fn last_frame_age(&self) -> Option<Duration> {
+        match self.shared.received.load(Ordering::Acquire) {
+            0 => None,
+            nanos => Some(self.shared.started.elapsed() - Duration::from_nanos(nanos)),
+        }
+    }
+
+    /// 自启动以来收到的帧数
+    pub fn frame_count(&self) -> u64 {
+        self.shared.frames.load(Ordering::Acquire)
+    }
+
+    /// 以通道方式订阅状态，每 `decimation` 帧推送一次
+    ///
+    /// 通道容量为 `capacity`，消费者来不及处理时新帧会被丢弃而不会阻塞接收线程；
+    /// 接收端被丢弃后订阅会自动取消。
+    pub fn subscribe(&self, decimation: usize, capacity: usize) -> Receiver<Arc<RobotState>> {
+        let (sender, receiver) = sync_channel(capacity.max(1));
+        self.add_subscriber(decimation, Sink::Channel(sender));
+        receiver
+    }
+
+    /// 以回调方式订阅状态，每 `decimation` 帧调用一次
+    ///
+    /// 回调在接收线程中执行，应当尽快返回。
+    pub fn on_state<F>(&self, decimation: usize, callback: F) -> SubscriptionId
+    where
+        F: FnMut(&RobotState) + Send + 'static,
+    {
+        self.add_subscriber(decimation, Sink::Callback(Box::new(callback)))
+    }
+
+    /// 与 [`on_state`](Self::on_state) 相同，回调同时收到读到该帧的时刻
+    pub(crate) fn on_state_timed<F>(&self, decimation: usize, callback: F) -> SubscriptionId
+    where
+        F: FnMut(&RobotState, Instant) + Send + 'static,
+    {
+        self.add_subscriber(decimation, Sink::Timed(Box::new(callback)))
+    }
+
+    /// 取消订阅
+    pub fn unsubscribe(&self, id: SubscriptionId) {
+        self.shared
+            .subscribers
+            .lock()
+            .unwrap()
+            .retain(|(subscriber, _)| *subscriber != id);
+    }
+
+    /// 停止后台线程并断开连接
+    pub fn stop(&mut self) {
+        self.shared.running.store(false, Ordering::Release);
+        if let Some(socket) = self.shared.socket.lock().unwrap().take() {
+            let _ = socket.shutdown(Shutdown::Both);
+        }
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+
+    fn add_subscriber(&self, decimation: usize, sink: Sink) -> SubscriptionId {
+        let id = self.shared.next_id.fetch_add(1, Ordering::Relaxed);
+        let subscriber = Subscriber { decimation: decimation.max(1), counter: 0, sink };
+        let subscriber = Arc::new(Mutex::new(subscriber));
+        self.shared
+            .subscribers
+            .lock()
+            .unwrap()
+            .push((id, subscriber));
+        id
+    }
+}
+
+impl Drop for StateStream {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+fn receive_loop(shared: Arc<Shared>, reader: DatasheetReader, host: String, port: u16) {
+    let mut reader = Some(reader);
+    while shared.running.load(Ordering::Acquire) {
+        let Some(current) = reader.as_mut() else {
+            sleep(RECONNECT_INTERVAL);
+            if let Ok(new_reader) = DatasheetReader::connect(&host, port) {
+                *shared.socket.lock().unwrap() = new_reader.try_clone_stream().ok();
+                reader = Some(new_reader);
+            }
+            continue;
+        };
+
+        match current.next_state() {
+            Ok(state) => publish(&shared, Arc::new(state), Instant::now()),
+            Err(RobotException::NetworkError(_)) => reader = None,
+            // 单帧解析失败不影响后续数据
+            Err(_) => {}
+        }
+    }
+}
+
+fn publish(shared: &Shared, state: Arc<RobotState>, received: Instant) {
+    shared.latest.store(Some(state.clone()));
+    let nanos = received.duration_since(shared.started).as_nanos().max(1) as u64;
+    shared.received.store(nanos, Ordering::Release);
+    shared.frames.fetch_add(1, Ordering::AcqRel);
+
+    let subscribers = shared.subscribers.lock().unwrap().clone();
+    let mut closed = Vec::new();
+    for (id, subscriber) in subscribers {
+        let mut subscriber = subscriber.lock().unwrap();
+        subscriber.counter += 1;
+        if subscriber.counter < subscriber.decimation {
+            continue;
+        }
+        subscriber.counter = 0;
+        match &mut subscriber.sink {
+            Sink::Channel(sender) => {
+                if let Err(TrySendError::Disconnected(_)) = sender.try_send(state.clone()) {
+                    closed.push(id);
+                }
+            }
+            Sink::Callback(callback) => callback(&state),
+            Sink::Timed(callback) => callback(&state, received),
+        }
+    }
+    if !closed.is_empty() {
+        (shared.subscribers.lock().unwrap()).retain(|(id, _)| !closed.contains(id));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::net::TcpListener;
+
+    use super::*;
+
+    #[test]
+    fn test_state_stream_decimation() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let frame = serde_json::to_string(&RobotState::default()).unwrap();
+        let (ready, wait_ready) = sync_channel(1);
+        let server = thread::spawn(move || {
+            let (mut socket, _) = listener.accept().unwrap();
+            wait_ready.recv().unwrap();
+            for _ in 0..10 {
+                socket.write_all(frame.as_bytes()).unwrap();
+            }
+            sleep(Duration::from_millis(200));
+        });
+
+        let stream = StateStream::start("127.0.0.1", port).unwrap();
+        let receiver = stream.subscribe(5, 8);
+        ready.send(()).unwrap();
+        server.join().unwrap();
+
+        assert_eq!(stream.frame_count(), 10);
+        assert_eq!(*stream.latest().unwrap(), RobotState::default());
+        assert!(stream.latest_within(Duration::from_millis(50)).is_none());
+        assert!(stream.latest_within(Duration::from_secs(10)).is_some());
+        assert_eq!(receiver.try_iter().count(), 2);
+    }
+}
@@ -37,12 +37,12 @@ pub type ReadEAIResponse = CommandResponse<{ Command::ReadEAI }, f64>;
 
 #[derive(Default, libhans_derive::CommandSerde)]
 pub struct BoxInfo {
-    is_connected: bool,
-    is_voltage48v_on: bool,
-    voltage48v_out_voltage: f64,
-    voltage48v_out_current: f64,
-    is_remote_button_on: bool,
-    is_three_stage_button_on: bool,
+    pub is_connected: bool,
+    pub is_voltage48v_on: bool,
+    pub voltage48v_out_voltage: f64,
+    pub voltage48v_out_current: f64,
+    pub is_remote_button_on: bool,
+    pub is_three_stage_button_on: bool,
 }
 
 #[cfg(test)]
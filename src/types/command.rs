@@ -5,7 +5,7 @@ use std::marker::ConstParamTy;
 use super::command_serde::CommandSerde;
 use crate::robot_error::RobotError;
 
-#[derive(ConstParamTy, PartialEq, Eq, Serialize, Deserialize, Debug)]
+#[derive(ConstParamTy, PartialEq, Eq, Clone, Copy, Serialize, Deserialize, Debug)]
 pub enum Command {
     // ! 初始化指令
     OSCmd,
@@ -171,13 +171,14 @@ where
         }
     }
     fn from_str(data: &str) -> RobotResult<Self> {
-        let command = format!("{C:?}");
-        if data.starts_with(&command) {
-            let data = D::from_str(&data[command.len()..data.len() - 2])?;
-            Ok(CommandRequest { _handler: CommandHander {}, data })
-        } else {
-            Err(deserialize_error::<CommandRequest<C, D>, _>(data)(()))
-        }
+        let command = format!("{C:?},");
+        let Some(args) = data.trim_end().strip_prefix(&command) else {
+            return Err(deserialize_error::<CommandRequest<C, D>, _>(data)(()));
+        };
+        let args = args.strip_suffix(';').unwrap_or(args);
+        let args = args.strip_suffix(',').unwrap_or(args);
+        let data = D::from_str(args)?;
+        Ok(CommandRequest { _handler: CommandHander {}, data })
     }
     fn try_default() -> Self {
         CommandRequest { _handler: CommandHander {}, data: D::try_default() }
@@ -192,22 +193,30 @@ where
     S: CommandSerde,
 {
     fn to_string(&self) -> String {
-        format!("{:?},{},;", C, self.status.as_ref().unwrap().to_string())
+        match &self.status {
+            Ok(_) if S::num_args() == 0 => format!("{C:?},OK,;"),
+            Ok(data) => format!("{C:?},OK,{},;", data.to_string()),
+            Err(e) => format!("{C:?},Fail,{},;", CommandSerde::to_string(e)),
+        }
     }
     fn from_str(data: &str) -> RobotResult<Self> {
         let command = format!("{C:?}");
-        if data.starts_with(&(command.clone() + ",OK,")) {
-            let data = &data[&command.len() + 3..data.len() - 2];
-            let data = S::from_str(if data.is_empty() { "" } else { &data[1..] })?;
-            Ok(CommandResponse { _handler: CommandHander {}, status: Ok(data) })
-        } else if data.starts_with(&(command.clone() + ",Fail,")) {
-            let data = &data[&command.len() + 5..data.len() - 2];
-            let data = RobotError::from_str(if data.is_empty() { "" } else { &data[1..] })?;
-            Ok(CommandResponse { _handler: CommandHander {}, status: Err(data) })
-        } else {
-            println!("data: {data:?}");
-            Err(deserialize_error::<CommandResponse<C, S>, _>(data)(()))
-        }
+        let body = data
+            .trim()
+            .strip_prefix(&command)
+            .and_then(|body| body.strip_suffix(';'))
+            .map(|body| body.strip_suffix(',').unwrap_or(body));
+        let status = match body {
+            Some(",OK") => Ok(S::from_str("")?),
+            Some(",Fail") => Err(RobotError::from_str("")?),
+            Some(body) => match (body.strip_prefix(",OK,"), body.strip_prefix(",Fail,")) {
+                (Some(data), _) => Ok(S::from_str(data)?),
+                (_, Some(data)) => Err(RobotError::from_str(data)?),
+                _ => return Err(deserialize_error::<CommandResponse<C, S>, _>(data)(())),
+            },
+            None => return Err(deserialize_error::<CommandResponse<C, S>, _>(data)(())),
+        };
+        Ok(CommandResponse { _handler: CommandHander {}, status })
     }
     fn try_default() -> Self {
         CommandResponse { _handler: CommandHander {}, status: Ok(S::try_default()) }
@@ -255,5 +264,25 @@ mod test {
             CommandRequest::<{ Command::GrpEnable }, ()>::from_str(request_str).unwrap(),
             request
         );
+
+        let request = CommandRequest::<{ Command::SetCurTCP }, (u8, [f64; 6])>::from_str(
+            "SetCurTCP,0,1,2,3,4,5,6,;",
+        )
+        .unwrap();
+        assert_eq!(request.data, (0, [1., 2., 3., 4., 5., 6.]));
+        assert!(
+            CommandRequest::<{ Command::MovePath }, (u8, String)>::from_str("MovePathL,0,path,;")
+                .is_err()
+        );
+
+        let response = CommandResponse::<{ Command::ReadCurFSM }, u8>::from(Err(
+            RobotError::RECParametersError,
+        ));
+        let response_str = response.to_string();
+        assert_eq!(response_str, "ReadCurFSM,Fail,40034,;");
+        assert_eq!(
+            CommandResponse::<{ Command::ReadCurFSM }, u8>::from_str(&response_str).unwrap(),
+            response
+        );
     }
 }
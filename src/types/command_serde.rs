@@ -15,10 +15,13 @@ pub trait CommandSerde: Sized {
 
 impl CommandSerde for RobotError {
     fn to_string(&self) -> String {
-        serde_json::to_string(self).unwrap()
+        CommandSerde::to_string(&self.code())
     }
     fn from_str(data: &str) -> RobotResult<Self> {
-        serde_json::from_str(data).map_err(deserialize_error::<RobotError, _>(data))
+        data.trim()
+            .parse::<u16>()
+            .map(RobotError::from)
+            .map_err(deserialize_error::<RobotError, _>(data))
     }
     fn try_default() -> Self {
         Self::default()
@@ -101,17 +104,20 @@ where
         format!("{},{}", self.0.to_string(), self.1.to_string())
     }
     fn from_str(data: &str) -> RobotResult<Self> {
-        let mut data = data.split(',');
-        Ok((
-            T1::from_str(data.next().unwrap())?,
-            T2::from_str(data.next().unwrap())?,
-        ))
+        let parts: Vec<&str> = data.split(',').collect();
+        let mut index = 0;
+        let value = (
+            take_arg::<T1>(&parts, &mut index)?,
+            take_arg::<T2>(&parts, &mut index)?,
+        );
+        check_args::<Self>(&parts, index)?;
+        Ok(value)
     }
     fn try_default() -> Self {
         (T1::try_default(), T2::try_default())
     }
     fn num_args() -> usize {
-        2
+        T1::num_args() + T2::num_args()
     }
 }
 
@@ -130,18 +136,21 @@ where
         )
     }
     fn from_str(data: &str) -> RobotResult<Self> {
-        let mut data = data.split(',');
-        Ok((
-            T1::from_str(data.next().unwrap())?,
-            T2::from_str(data.next().unwrap())?,
-            T3::from_str(data.next().unwrap())?,
-        ))
+        let parts: Vec<&str> = data.split(',').collect();
+        let mut index = 0;
+        let value = (
+            take_arg::<T1>(&parts, &mut index)?,
+            take_arg::<T2>(&parts, &mut index)?,
+            take_arg::<T3>(&parts, &mut index)?,
+        );
+        check_args::<Self>(&parts, index)?;
+        Ok(value)
     }
     fn try_default() -> Self {
         (T1::try_default(), T2::try_default(), T3::try_default())
     }
     fn num_args() -> usize {
-        3
+        T1::num_args() + T2::num_args() + T3::num_args()
     }
 }
 
@@ -156,19 +165,22 @@ where
             .join(",")
     }
     fn from_str(data: &str) -> RobotResult<Self> {
-        let try_data = data
-            .split(',')
-            .map(|x| T::from_str(x).unwrap())
-            .collect::<Vec<T>>()
+        let parts: Vec<&str> = data.split(',').collect();
+        let mut index = 0;
+        let mut values = Vec::with_capacity(N);
+        for _ in 0..N {
+            values.push(take_arg::<T>(&parts, &mut index)?);
+        }
+        check_args::<Self>(&parts, index)?;
+        values
             .try_into()
-            .map_err(deserialize_error::<[T; N], _>(data))?;
-        Ok(try_data)
+            .map_err(deserialize_error::<[T; N], _>(data))
     }
     fn try_default() -> Self {
         [T::try_default(); N]
     }
     fn num_args() -> usize {
-        N
+        N * T::num_args()
     }
 }
 
@@ -183,3 +195,30 @@ impl CommandSerde for String {
         String::new()
     }
 }
+
+/// 按类型所需的参数数量从切分后的参数中取出一个值
+fn take_arg<T: CommandSerde>(parts: &[&str], index: &mut usize) -> RobotResult<T> {
+    let needed = T::num_args();
+    if *index + needed > parts.len() {
+        return Err(RobotException::DeserializeError(format!(
+            "invalid number of arguments of {}",
+            type_name::<T>()
+        )));
+    }
+    let value = T::from_str(&parts[*index..*index + needed].join(","))?;
+    *index += needed;
+    Ok(value)
+}
+
+/// 检查参数是否恰好被全部消耗
+fn check_args<T>(parts: &[&str], index: usize) -> RobotResult<()> {
+    // 无参数的类型会留下一个空字符串
+    if index == parts.len() || (index == 0 && parts == [""]) {
+        Ok(())
+    } else {
+        Err(RobotException::DeserializeError(format!(
+            "invalid number of arguments of {}",
+            type_name::<T>()
+        )))
+    }
+}
@@ -259,15 +259,23 @@ impl<const N: usize> CommandSerde for MovePaths<N> {
     }
 
     fn from_str(data: &str) -> RobotResult<Self> {
-        let mut iter = data.split(',');
-        let path_name = CommandSerde::from_str(iter.next().unwrap())?;
-        let move_mode = CommandSerde::from_str(iter.next().unwrap())?;
-        let _: u16 = CommandSerde::from_str(iter.next().unwrap())?;
-        let mut points = [[0.0; 6]; N];
-        for point in points.iter_mut().take(N) {
-            *point = CommandSerde::from_str(iter.next().unwrap())?;
+        let parts: Vec<&str> = data.split(',').collect();
+        let [path_name, move_mode, count, points @ ..] = parts.as_slice() else {
+            return Err(RobotException::DeserializeError(format!(
+                "invalid number of arguments of MovePaths: {data}"
+            )));
+        };
+        let count: u16 = CommandSerde::from_str(count)?;
+        if count as usize != N || points.len() != N * 6 {
+            return Err(RobotException::DeserializeError(format!(
+                "invalid number of points of MovePaths: {data}"
+            )));
         }
-        Ok(MovePaths { path_name, move_mode, points })
+        Ok(MovePaths {
+            path_name: CommandSerde::from_str(path_name)?,
+            move_mode: CommandSerde::from_str(move_mode)?,
+            points: CommandSerde::from_str(&points.join(","))?,
+        })
     }
 
     fn try_default() -> Self {
@@ -107,8 +107,8 @@ pub struct ActPose<const N: usize> {
 
 #[derive(libhans_derive::CommandSerde, Debug, PartialEq)]
 pub struct CmdPose<const N: usize> {
-    joint: [f64; N],
-    pose_o_to_ee: [f64; 6],
+    pub joint: [f64; N],
+    pub pose_o_to_ee: [f64; 6],
 }
 
 #[cfg(test)]
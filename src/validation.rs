@@ -0,0 +1,283 @@
+use std::fmt::Display;
+
+use robot_behavior::RobotException;
+
+/// 沿直线与圆弧检查工作空间时相邻采样点的最大间距，单位 mm
+pub const WORKSPACE_SAMPLE_STEP: f64 = 1.;
+
+/// 基坐标系中与坐标轴对齐的长方体区域，单位 mm
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorkspaceBox {
+    pub name: String,
+    pub min: [f64; 3],
+    pub max: [f64; 3],
+}
+
+impl WorkspaceBox {
+    /// 由两个对角点构造区域，两点的顺序不限
+    pub fn new(name: impl Into<String>, a: [f64; 3], b: [f64; 3]) -> Self {
+        WorkspaceBox {
+            name: name.into(),
+            min: std::array::from_fn(|i| a[i].min(b[i])),
+            max: std::array::from_fn(|i| a[i].max(b[i])),
+        }
+    }
+
+    pub fn contains(&self, point: &[f64; 3]) -> bool {
+        (0..3).all(|i| (self.min[i]..=self.max[i]).contains(&point[i]))
+    }
+}
+
+/// 工具末端允许到达的笛卡尔空间
+///
+/// 设置了允许区域时，末端必须位于其中至少一个区域内；任何情况下末端都不能进入禁止区域。
+/// 两者均为空时不做限制。运动下发前沿插补路径采样检查：关节插补在关节空间中采样，
+/// 直线、圆弧与整圆按 [`WORKSPACE_SAMPLE_STEP`] 采样，过渡半径造成的偏离不在检查范围内。
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Workspace {
+    keep_in: Vec<WorkspaceBox>,
+    keep_out: Vec<WorkspaceBox>,
+}
+
+impl Workspace {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 添加一个允许区域
+    pub fn with_keep_in(mut self, region: WorkspaceBox) -> Self {
+        self.keep_in.push(region);
+        self
+    }
+
+    /// 添加一个禁止区域
+    pub fn with_keep_out(mut self, region: WorkspaceBox) -> Self {
+        self.keep_out.push(region);
+        self
+    }
+
+    pub fn keep_in(&self) -> &[WorkspaceBox] {
+        &self.keep_in
+    }
+
+    pub fn keep_out(&self) -> &[WorkspaceBox] {
+        &self.keep_out
+    }
+
+    /// 没有设置任何区域，不做限制
+    pub fn is_empty(&self) -> bool {
+        self.keep_in.is_empty() && self.keep_out.is_empty()
+    }
+
+    /// 按 [`WORKSPACE_SAMPLE_STEP`] 采样检查从 `from` 到 `to` 的直线段，单位 mm
+    pub fn check_line(&self, from: [f64; 3], to: [f64; 3]) -> Result<(), ValidationError> {
+        let length = (0..3)
+            .map(|i| (to[i] - from[i]).powi(2))
+            .sum::<f64>()
+            .sqrt();
+        let steps = (length / WORKSPACE_SAMPLE_STEP).ceil().max(1.) as usize;
+        (0..=steps).try_for_each(|k| {
+            let t = k as f64 / steps as f64;
+            self.check(std::array::from_fn(|i| from[i] + (to[i] - from[i]) * t))
+        })
+    }
+
+    /// 检查工具末端位置，单位 mm
+    pub fn check(&self, point: [f64; 3]) -> Result<(), ValidationError> {
+        if !self.keep_in.is_empty() && !self.keep_in.iter().any(|region| region.contains(&point)) {
+            return Err(ValidationError::OutsideWorkspace { point });
+        }
+        match self.keep_out.iter().find(|region| region.contains(&point)) {
+            Some(region) => Err(ValidationError::KeepOut { region: region.name.clone(), point }),
+            None => Ok(()),
+        }
+    }
+}
+
+/// 下发运动前在本地发现的问题
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValidationError {
+    /// 关节目标超出限位，`joint` 从 0 开始计数
+    JointLimit {
+        joint: usize,
+        value: f64,
+        min: f64,
+        max: f64,
+    },
+    /// 关节速度不为正或超过机型上限
+    JointVelocity { joint: usize, value: f64, max: f64 },
+    /// 关节加速度不为正或超过机型上限
+    JointAcceleration { joint: usize, value: f64, max: f64 },
+    /// 末端线速度不为正或超过机型上限
+    CartesianVelocity { value: f64, max: f64 },
+    /// 末端线加速度不为正或超过机型上限
+    CartesianAcceleration { value: f64, max: f64 },
+    /// 负载质量为负或超过机型额定负载
+    Payload { mass: f64, max: f64 },
+    /// 工具末端不在任何允许区域内
+    OutsideWorkspace { point: [f64; 3] },
+    /// 工具末端进入了禁止区域
+    KeepOut { region: String, point: [f64; 3] },
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValidationError::JointLimit { joint, value, min, max } => write!(
+                f,
+                "joint {} target {value:.3}° is outside [{min}°, {max}°]",
+                joint + 1
+            ),
+            ValidationError::JointVelocity { joint, value, max } => write!(
+                f,
+                "joint {} velocity {value:.3}°/s is outside (0, {max}°/s]",
+                joint + 1
+            ),
+            ValidationError::JointAcceleration { joint, value, max } => write!(
+                f,
+                "joint {} acceleration {value:.3}rad/s² is outside (0, {max}rad/s²]",
+                joint + 1
+            ),
+            ValidationError::CartesianVelocity { value, max } => {
+                write!(
+                    f,
+                    "cartesian velocity {value:.3}m/s is outside (0, {max}m/s]"
+                )
+            }
+            ValidationError::CartesianAcceleration { value, max } => {
+                write!(
+                    f,
+                    "cartesian acceleration {value:.3}m/s² is outside (0, {max}m/s²]"
+                )
+            }
+            ValidationError::Payload { mass, max } => {
+                write!(f, "payload {mass:.3}kg is outside [0, {max}kg]")
+            }
+            ValidationError::OutsideWorkspace { point } => {
+                write!(f, "tool point {point:.1?} is outside every keep-in box")
+            }
+            ValidationError::KeepOut { region, point } => {
+                write!(f, "tool point {point:.1?} enters keep-out box {region}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+impl From<ValidationError> for RobotException {
+    fn from(error: ValidationError) -> Self {
+        RobotException::InvalidInstruction(error.to_string())
+    }
+}
+
+pub(crate) fn check_joint_limits<const N: usize>(
+    joint: &[f64; N],
+    min: &[f64; N],
+    max: &[f64; N],
+) -> Result<(), ValidationError> {
+    match (0..N).find(|&i| !(min[i]..=max[i]).contains(&joint[i])) {
+        Some(i) => {
+            Err(ValidationError::JointLimit { joint: i, value: joint[i], min: min[i], max: max[i] })
+        }
+        None => Ok(()),
+    }
+}
+
+pub(crate) fn check_joint_vel<const N: usize>(
+    vel: &[f64; N],
+    bound: &[f64; N],
+) -> Result<(), ValidationError> {
+    match (0..N).find(|&i| !(vel[i] > 0. && vel[i] <= bound[i])) {
+        Some(i) => Err(ValidationError::JointVelocity { joint: i, value: vel[i], max: bound[i] }),
+        None => Ok(()),
+    }
+}
+
+pub(crate) fn check_joint_acc<const N: usize>(
+    acc: &[f64; N],
+    bound: &[f64; N],
+) -> Result<(), ValidationError> {
+    match (0..N).find(|&i| !(acc[i] > 0. && acc[i] <= bound[i])) {
+        Some(i) => {
+            Err(ValidationError::JointAcceleration { joint: i, value: acc[i], max: bound[i] })
+        }
+        None => Ok(()),
+    }
+}
+
+pub(crate) fn check_cartesian_vel(vel: f64, bound: f64) -> Result<(), ValidationError> {
+    if vel > 0. && vel <= bound {
+        Ok(())
+    } else {
+        Err(ValidationError::CartesianVelocity { value: vel, max: bound })
+    }
+}
+
+pub(crate) fn check_cartesian_acc(acc: f64, bound: f64) -> Result<(), ValidationError> {
+    if acc > 0. && acc <= bound {
+        Ok(())
+    } else {
+        Err(ValidationError::CartesianAcceleration { value: acc, max: bound })
+    }
+}
+
+pub(crate) fn check_payload(mass: f64, max: f64) -> Result<(), ValidationError> {
+    if (0. ..=max).contains(&mass) {
+        Ok(())
+    } else {
+        Err(ValidationError::Payload { mass, max })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validation() {
+        let workspace = Workspace::new()
+            .with_keep_in(WorkspaceBox::new(
+                "cell",
+                [-1000., -1000., 0.],
+                [1000., 1000., 1500.],
+            ))
+            .with_keep_out(WorkspaceBox::new(
+                "fixture",
+                [300., 300., 0.],
+                [500., 200., 200.],
+            ));
+        assert!(workspace.check([0., 0., 500.]).is_ok());
+        // 两端都在禁止区域之外，但直线穿过了它
+        assert!(workspace.check([400., 100., 100.]).is_ok());
+        assert!(workspace.check([400., 400., 100.]).is_ok());
+        assert!(matches!(
+            workspace.check_line([400., 100., 100.], [400., 400., 100.]),
+            Err(ValidationError::KeepOut { .. })
+        ));
+        assert_eq!(
+            workspace.check([0., 0., -10.]),
+            Err(ValidationError::OutsideWorkspace { point: [0., 0., -10.] })
+        );
+        let error = workspace.check([400., 250., 100.]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "tool point [400.0, 250.0, 100.0] enters keep-out box fixture"
+        );
+
+        let min = [-360., -360., -165.];
+        let max = [360., 360., 165.];
+        assert!(check_joint_limits(&[0., 90., 165.], &min, &max).is_ok());
+        assert_eq!(
+            check_joint_limits(&[0., 90., 170.], &min, &max)
+                .unwrap_err()
+                .to_string(),
+            "joint 3 target 170.000° is outside [-165°, 165°]"
+        );
+        assert!(check_joint_vel(&[10., 0., 10.], &[120.; 3]).is_err());
+        assert!(check_cartesian_vel(f64::NAN, 2.).is_err());
+        assert!(check_joint_acc(&[2.5, 3., 2.5], &[2.5; 3]).is_err());
+        assert!(check_cartesian_acc(2., 2.).is_ok() && check_cartesian_acc(0., 2.).is_err());
+        assert!(check_payload(30., 30.).is_ok() && check_payload(30.5, 30.).is_err());
+    }
+}
@@ -0,0 +1,242 @@
+use std::fmt::Display;
+use std::time::{Duration, Instant};
+
+use robot_behavior::{RobotException, RobotResult};
+
+use crate::RobotMode;
+
+/// [`WaitPolicy`] 默认的总超时
+///
+/// 单段运动一般不会超过这个时间，超过时多半是机器人卡在了某个状态；运行时间更长的运动
+/// 需要用 [`WaitPolicy::with_timeout`] 放宽或用 [`WaitPolicy::without_timeout`] 关闭。
+pub const DEFAULT_WAIT_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// 等待运动结束的策略
+#[derive(Debug, Clone)]
+pub struct WaitPolicy {
+    /// 两次查询状态机之间的间隔
+    pub poll_interval: Duration,
+    /// 总超时，默认为 [`DEFAULT_WAIT_TIMEOUT`]，`None` 表示一直等待
+    pub timeout: Option<Duration>,
+    /// 进入故障或安全停止状态时立即返回；关闭后会继续等待，直到机器人被复位并回到待机
+    pub abort_on_fault: bool,
+    /// 状态流已开启时从推送的状态中读取状态机，不再占用指令端口
+    pub use_state_stream: bool,
+}
+
+impl Default for WaitPolicy {
+    fn default() -> Self {
+        WaitPolicy {
+            poll_interval: Duration::from_millis(20),
+            timeout: Some(DEFAULT_WAIT_TIMEOUT),
+            abort_on_fault: true,
+            use_state_stream: true,
+        }
+    }
+}
+
+impl WaitPolicy {
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// 关闭总超时，一直等待到运动结束
+    pub fn without_timeout(mut self) -> Self {
+        self.timeout = None;
+        self
+    }
+
+    pub fn with_abort_on_fault(mut self, abort_on_fault: bool) -> Self {
+        self.abort_on_fault = abort_on_fault;
+        self
+    }
+
+    pub fn with_state_stream(mut self, use_state_stream: bool) -> Self {
+        self.use_state_stream = use_state_stream;
+        self
+    }
+}
+
+/// 运动结束的方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WaitOutcome {
+    /// 运动正常完成，机器人回到待机
+    Arrived,
+    /// 运动被停止、去使能或断电中断，机器人没有进入故障
+    Stopped,
+    /// 机器人进入了故障或安全停止状态
+    Faulted,
+    /// 超时时机器人仍未停止
+    TimedOut,
+}
+
+/// 等待运动结束的结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WaitResult {
+    pub outcome: WaitOutcome,
+    /// 返回时机器人所处的状态
+    pub mode: RobotMode,
+    /// 等待的总时间
+    pub elapsed: Duration,
+    /// 读取状态机的次数
+    pub polls: u32,
+}
+
+impl WaitResult {
+    pub fn is_arrived(&self) -> bool {
+        self.outcome == WaitOutcome::Arrived
+    }
+
+    /// 正常完成或被主动停止时返回 `Ok`，故障与超时转换为错误
+    pub fn into_result(self) -> RobotResult<()> {
+        match self.outcome {
+            WaitOutcome::Arrived | WaitOutcome::Stopped => Ok(()),
+            WaitOutcome::Faulted | WaitOutcome::TimedOut => Err(
+                RobotException::UnprocessableInstructionError(self.to_string()),
+            ),
+        }
+    }
+}
+
+impl Display for WaitResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let elapsed = self.elapsed.as_secs_f64();
+        match self.outcome {
+            WaitOutcome::Arrived => write!(f, "motion finished after {elapsed:.3}s"),
+            WaitOutcome::Stopped => {
+                write!(f, "motion stopped in {:?} after {elapsed:.3}s", self.mode)
+            }
+            WaitOutcome::Faulted => {
+                write!(f, "motion faulted in {:?} after {elapsed:.3}s", self.mode)
+            }
+            WaitOutcome::TimedOut => {
+                write!(f, "motion timed out in {:?} after {elapsed:.3}s", self.mode)
+            }
+        }
+    }
+}
+
+/// 根据依次读到的状态机判断运动是否结束，同步与异步接口共用
+///
+/// - 进入 [`StandBy`](RobotMode::StandBy)：之前经过停止或故障状态时为 `Stopped`，否则为 `Arrived`
+/// - 进入故障或安全停止状态：`abort_on_fault` 时为 `Faulted`，否则继续等待
+/// - 稳定停留在未使能的层级，例如被去使能或断电：`Stopped`
+/// - 暂停、脚本运行以及其他过渡状态：继续等待
+pub(crate) struct MotionWatch {
+    policy: WaitPolicy,
+    started: Instant,
+    polls: u32,
+    interrupted: bool,
+}
+
+impl MotionWatch {
+    /// `stop_requested` 表示调用方已经下发过停止指令，此后回到待机视为被停止
+    pub(crate) fn new(policy: &WaitPolicy, stop_requested: bool) -> Self {
+        MotionWatch {
+            policy: policy.clone(),
+            started: Instant::now(),
+            polls: 0,
+            interrupted: stop_requested,
+        }
+    }
+
+    /// 等待过程中下发了停止指令，此后回到待机视为被停止
+    #[cfg(feature = "async")]
+    pub(crate) fn stop_requested(&mut self) {
+        self.interrupted = true;
+    }
+
+    pub(crate) fn poll_interval(&self) -> Duration {
+        self.policy.poll_interval
+    }
+
+    /// 记录一次读到的状态机，运动结束或超时时返回结果
+    pub(crate) fn observe(&mut self, mode: RobotMode) -> Option<WaitResult> {
+        self.polls += 1;
+        let faulted = mode.is_error() || mode.is_safety_stop();
+        if faulted || matches!(mode, RobotMode::RobotStopping | RobotMode::Braking) {
+            self.interrupted = true;
+        }
+
+        let outcome = if mode == RobotMode::StandBy {
+            Some(if self.interrupted {
+                WaitOutcome::Stopped
+            } else {
+                WaitOutcome::Arrived
+            })
+        } else if faulted {
+            self.policy.abort_on_fault.then_some(WaitOutcome::Faulted)
+        } else if !mode.is_transitional() && mode.power_level(true).is_some_and(|level| level < 4) {
+            Some(WaitOutcome::Stopped)
+        } else {
+            None
+        };
+        let outcome = outcome.or_else(|| {
+            self.policy
+                .timeout
+                .filter(|timeout| self.started.elapsed() >= *timeout)
+                .map(|_| WaitOutcome::TimedOut)
+        });
+        outcome.map(|outcome| WaitResult {
+            outcome,
+            mode,
+            elapsed: self.started.elapsed(),
+            polls: self.polls,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(policy: &WaitPolicy, stop_requested: bool, modes: &[RobotMode]) -> Option<WaitResult> {
+        let mut watch = MotionWatch::new(policy, stop_requested);
+        modes.iter().find_map(|mode| watch.observe(*mode))
+    }
+
+    #[test]
+    fn test_motion_watch_outcome() {
+        use RobotMode::*;
+        let policy = WaitPolicy::default();
+        assert_eq!(policy.timeout, Some(DEFAULT_WAIT_TIMEOUT));
+
+        let result = run(&policy, false, &[Moving, RobotHolding, Moving, StandBy]).unwrap();
+        assert_eq!((result.outcome, result.polls), (WaitOutcome::Arrived, 4));
+        let result = run(&policy, false, &[Moving, RobotStopping, StandBy]).unwrap();
+        assert_eq!(result.outcome, WaitOutcome::Stopped);
+        assert_eq!(
+            run(&policy, true, &[StandBy]).unwrap().outcome,
+            WaitOutcome::Stopped
+        );
+        assert_eq!(
+            run(&policy, false, &[Moving, Disable]).unwrap().outcome,
+            WaitOutcome::Stopped
+        );
+
+        let result = run(&policy, false, &[Moving, RobotCollisionStop]).unwrap();
+        assert_eq!(
+            (result.outcome, result.mode),
+            (WaitOutcome::Faulted, RobotCollisionStop)
+        );
+        assert!(result.into_result().is_err());
+
+        let lenient = policy.clone().with_abort_on_fault(false);
+        assert!(run(&lenient, false, &[Moving, Error, Reseting]).is_none());
+        assert!(run(&lenient.clone().without_timeout(), false, &[Moving]).is_none());
+        let result = run(&lenient, false, &[Error, StandBy]).unwrap();
+        assert_eq!(result.outcome, WaitOutcome::Stopped);
+
+        let timed = policy.with_timeout(Duration::ZERO);
+        assert_eq!(
+            run(&timed, false, &[Moving]).unwrap().outcome,
+            WaitOutcome::TimedOut
+        );
+    }
+}